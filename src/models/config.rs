@@ -2,7 +2,13 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 use crate::utils::error::ConfigError;
+use crate::utils::ActionResult;
+use super::conflict::HotkeyConflict;
+use super::hotkey::validate_hotkey_spec;
+use super::key::{validate_key_spec, KeyCombo};
+use super::operation::ActionSequence;
 
 /// 应用程序主配置
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -33,7 +39,9 @@ impl Default for AppConfig {
 
 impl AppConfig {
     /// 验证配置的有效性
-    pub fn validate(&self) -> Result<(), ConfigError> {
+    ///
+    /// 配置有效时返回热键冲突警告，冲突不会使验证失败
+    pub fn validate(&self) -> Result<Vec<HotkeyConflict>, ConfigError> {
         // 验证宏模式配置
         self.macro_config.validate()
             .map_err(|e| ConfigError::ValidationError(format!("宏模式配置无效: {}", e)))?;
@@ -50,7 +58,7 @@ impl AppConfig {
         self.ui_settings.validate()
             .map_err(|e| ConfigError::ValidationError(format!("UI设置无效: {}", e)))?;
         
-        Ok(())
+        Ok(self.hotkey_conflicts())
     }
     
     /// 修复无效的配置项，使用默认值替换
@@ -92,6 +100,15 @@ pub enum OperationMode {
     Intelligent,
 }
 
+impl std::fmt::Display for OperationMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationMode::Macro => write!(f, "宏模式"),
+            OperationMode::Intelligent => write!(f, "智能模式"),
+        }
+    }
+}
+
 /// 宏模式配置
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct MacroModeConfig {
@@ -101,6 +118,20 @@ pub struct MacroModeConfig {
     pub overlay_settings: OverlaySettings,
     /// 是否启用战斗状态检测
     pub battle_detection_enabled: bool,
+    /// 自定义操作序列 (操作名 -> 序列)，替换同名操作的默认序列，配置文件中保存为序列脚本
+    #[serde(default, with = "super::dsl::script_map")]
+    pub sequences: HashMap<String, ActionSequence>,
+    /// 槽位操作在游戏内的按键 (操作名 -> 逗号分隔的按键，第 n 个按键对应槽位 n)，序列中以 `$slot_key` 引用
+    #[serde(default = "default_slot_keys")]
+    pub slot_keys: HashMap<String, String>,
+}
+
+fn default_slot_keys() -> HashMap<String, String> {
+    let mut slot_keys = HashMap::new();
+    slot_keys.insert("deploy_operator".to_string(), "1,2,3,4,5,6,7,8,9,0,-,=".to_string());
+    slot_keys.insert("activate_skill".to_string(), "Q,W,E,R,T,Y,U,I,O,P".to_string());
+    slot_keys.insert("retreat_operator".to_string(), "A,S,D,F,G,H,J,K,L".to_string());
+    slot_keys
 }
 
 impl Default for MacroModeConfig {
@@ -116,6 +147,8 @@ impl Default for MacroModeConfig {
             hotkeys,
             overlay_settings: OverlaySettings::default(),
             battle_detection_enabled: true,
+            sequences: HashMap::new(),
+            slot_keys: default_slot_keys(),
         }
     }
 }
@@ -136,6 +169,10 @@ impl MacroModeConfig {
             }
         }
         
+        // 验证按键名称
+        validate_key_map(&self.hotkeys, "按键配置", validate_hotkey_spec)?;
+        validate_key_map(&self.slot_keys, "槽位按键配置", validate_key_spec)?;
+        
         // 验证悬浮窗设置
         self.overlay_settings.validate()?;
         
@@ -146,6 +183,8 @@ impl MacroModeConfig {
     pub fn fix_invalid_values(&mut self) {
         // 确保所有必需的按键配置都存在
         let default_config = MacroModeConfig::default();
+        fix_key_map(&mut self.hotkeys, &default_config.hotkeys, validate_hotkey_spec);
+        fix_key_map(&mut self.slot_keys, &default_config.slot_keys, validate_key_spec);
         for operation in AppConfig::get_supported_operations() {
             if !self.hotkeys.contains_key(operation) || self.hotkeys[operation].trim().is_empty() {
                 if let Some(default_key) = default_config.hotkeys.get(operation) {
@@ -159,6 +198,41 @@ impl MacroModeConfig {
     }
 }
 
+/// 验证按键表中的所有按键名称
+fn validate_key_map(
+    keys: &HashMap<String, String>,
+    kind: &str,
+    validate_spec: fn(&str) -> ActionResult<()>,
+) -> Result<(), String> {
+    for (name, spec) in keys {
+        if spec.trim().is_empty() {
+            continue;
+        }
+        validate_spec(spec).map_err(|e| format!("{}无效 {}: {}", kind, name, e))?;
+    }
+    Ok(())
+}
+
+/// 将按键表中无效的按键替换为默认值，没有默认值的项直接移除
+fn fix_key_map(
+    keys: &mut HashMap<String, String>,
+    defaults: &HashMap<String, String>,
+    validate_spec: fn(&str) -> ActionResult<()>,
+) {
+    keys.retain(|name, spec| {
+        if spec.trim().is_empty() || validate_spec(spec).is_ok() {
+            return true;
+        }
+        match defaults.get(name) {
+            Some(default_key) => {
+                *spec = default_key.clone();
+                true
+            }
+            None => false,
+        }
+    });
+}
+
 /// 智能模式配置
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct IntelligentModeConfig {
@@ -206,6 +280,9 @@ impl IntelligentModeConfig {
             }
         }
         
+        // 验证按键名称
+        validate_key_map(&self.hotkeys, "按键配置", validate_hotkey_spec)?;
+        
         // 验证智能功能配置
         let supported_features = Self::get_supported_intelligent_features();
         for feature in &self.intelligent_features {
@@ -224,6 +301,7 @@ impl IntelligentModeConfig {
     pub fn fix_invalid_values(&mut self) {
         // 确保所有必需的按键配置都存在
         let default_config = IntelligentModeConfig::default();
+        fix_key_map(&mut self.hotkeys, &default_config.hotkeys, validate_hotkey_spec);
         for operation in AppConfig::get_supported_operations() {
             if !self.hotkeys.contains_key(operation) || self.hotkeys[operation].trim().is_empty() {
                 if let Some(default_key) = default_config.hotkeys.get(operation) {
@@ -262,6 +340,12 @@ pub struct GlobalSettings {
     pub game_keys: HashMap<String, String>,
     /// 识别到窗口后立即运行
     pub auto_start_on_detection: bool,
+    /// 安全设置
+    #[serde(default)]
+    pub safety: SafetySettings,
+    /// 热键触发方式设置
+    #[serde(default)]
+    pub triggers: TriggerSettings,
 }
 
 impl Default for GlobalSettings {
@@ -275,6 +359,8 @@ impl Default for GlobalSettings {
         Self {
             game_keys,
             auto_start_on_detection: false,
+            safety: SafetySettings::default(),
+            triggers: TriggerSettings::default(),
         }
     }
 }
@@ -295,6 +381,12 @@ impl GlobalSettings {
             }
         }
         
+        // 验证按键名称
+        validate_key_map(&self.game_keys, "游戏内按键配置", validate_key_spec)?;
+        
+        self.safety.validate()?;
+        self.triggers.validate()?;
+        
         Ok(())
     }
     
@@ -302,6 +394,7 @@ impl GlobalSettings {
     pub fn fix_invalid_values(&mut self) {
         // 确保所有必需的游戏内按键配置都存在
         let default_config = GlobalSettings::default();
+        fix_key_map(&mut self.game_keys, &default_config.game_keys, validate_key_spec);
         for function in AppConfig::get_supported_game_functions() {
             if !self.game_keys.contains_key(function) || self.game_keys[function].trim().is_empty() {
                 if let Some(default_key) = default_config.game_keys.get(function) {
@@ -309,6 +402,99 @@ impl GlobalSettings {
                 }
             }
         }
+        
+        self.safety.fix_invalid_values();
+        self.triggers.fix_invalid_values();
+    }
+}
+
+/// 安全设置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SafetySettings {
+    /// 每秒最多发送的输入事件数量，0 表示不限制
+    pub max_events_per_second: u32,
+    /// 单个序列的最长执行时间（毫秒），0 表示不限制
+    pub max_sequence_duration_ms: u64,
+    /// 紧急停止组合键，为空时不启用
+    pub panic_key: String,
+}
+
+impl Default for SafetySettings {
+    fn default() -> Self {
+        Self {
+            max_events_per_second: 200,
+            max_sequence_duration_ms: 30_000,
+            panic_key: "Ctrl+Shift+F12".to_string(),
+        }
+    }
+}
+
+impl SafetySettings {
+    /// 验证安全设置的有效性
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.panic_key.trim().is_empty() {
+            self.panic_key.parse::<KeyCombo>()
+                .map_err(|e| format!("紧急停止按键无效: {}", e))?;
+        }
+        
+        Ok(())
+    }
+    
+    /// 修复无效的配置项
+    pub fn fix_invalid_values(&mut self) {
+        if !self.panic_key.trim().is_empty() && self.panic_key.parse::<KeyCombo>().is_err() {
+            self.panic_key = SafetySettings::default().panic_key;
+        }
+    }
+}
+
+/// 热键触发方式设置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TriggerSettings {
+    /// 长按触发时间（毫秒），短于此时间释放为单击
+    pub hold_threshold_ms: u64,
+    /// 双击间隔（毫秒），从第一次释放到第二次按下
+    pub double_tap_window_ms: u64,
+}
+
+impl Default for TriggerSettings {
+    fn default() -> Self {
+        Self {
+            hold_threshold_ms: 300,
+            double_tap_window_ms: 250,
+        }
+    }
+}
+
+impl TriggerSettings {
+    /// 长按触发时间
+    pub fn hold_threshold(&self) -> Duration {
+        Duration::from_millis(self.hold_threshold_ms)
+    }
+    
+    /// 双击间隔
+    pub fn double_tap_window(&self) -> Duration {
+        Duration::from_millis(self.double_tap_window_ms)
+    }
+    
+    /// 验证触发方式设置的有效性
+    pub fn validate(&self) -> Result<(), String> {
+        if !(50..=5000).contains(&self.hold_threshold_ms) {
+            return Err(format!("长按触发时间无效: {}, 应在50-5000毫秒之间", self.hold_threshold_ms));
+        }
+        if !(50..=2000).contains(&self.double_tap_window_ms) {
+            return Err(format!("双击间隔无效: {}, 应在50-2000毫秒之间", self.double_tap_window_ms));
+        }
+        
+        Ok(())
+    }
+    
+    /// 修复无效的配置项
+    pub fn fix_invalid_values(&mut self) {
+        self.hold_threshold_ms = self.hold_threshold_ms.clamp(50, 5000);
+        self.double_tap_window_ms = self.double_tap_window_ms.clamp(50, 2000);
     }
 }
 
@@ -537,6 +723,34 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_unknown_key_names_rejected() {
+        let mut config = AppConfig::default();
+        
+        // UI 默认的按键列表应该是有效的
+        config.macro_config.hotkeys.insert("deploy".to_string(), "1,2,3,4,5,6,7,8,9,0,-,=".to_string());
+        config.macro_config.hotkeys.insert("skill".to_string(), "q,w,e,r,t,y,u,i,o,p".to_string());
+        config.global_settings.game_keys.insert("exit_return".to_string(), "Esc".to_string());
+        assert!(config.validate().is_ok());
+        
+        // 未知的按键名称
+        config.macro_config.hotkeys.insert("pause_game".to_string(), "Spcae".to_string());
+        config.macro_config.hotkeys.insert("custom".to_string(), "1,Foo".to_string());
+        config.global_settings.game_keys.insert("retreat_operator".to_string(), "Dell".to_string());
+        config.macro_config.slot_keys.insert("activate_skill".to_string(), "Q,W,Foo".to_string());
+        assert!(config.macro_config.validate().is_err());
+        assert!(config.global_settings.validate().is_err());
+        assert!(config.validate().is_err());
+        
+        // 修复后无效的按键被替换为默认值或移除
+        config.fix_invalid_values();
+        assert_eq!(config.macro_config.hotkeys["pause_game"], "Space");
+        assert!(!config.macro_config.hotkeys.contains_key("custom"));
+        assert_eq!(config.global_settings.game_keys["retreat_operator"], "Delete");
+        assert_eq!(config.macro_config.slot_keys["activate_skill"], "Q,W,E,R,T,Y,U,I,O,P");
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_intelligent_mode_config_validation() {
         let mut config = IntelligentModeConfig::default();
@@ -576,6 +790,42 @@ mod tests {
         assert!(settings.validate().is_ok());
     }
 
+    #[test]
+    fn test_safety_settings_validation() {
+        let mut settings = GlobalSettings::default();
+        assert_eq!(settings.safety.panic_key, "Ctrl+Shift+F12");
+
+        // 测试无效的紧急停止按键
+        settings.safety.panic_key = "Ctrl+NoSuchKey".to_string();
+        assert!(settings.validate().is_err());
+
+        // 修复无效的按键
+        settings.fix_invalid_values();
+        assert_eq!(settings.safety.panic_key, "Ctrl+Shift+F12");
+
+        // 空按键表示不启用
+        settings.safety.panic_key.clear();
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn test_trigger_settings_validation() {
+        let mut settings = GlobalSettings::default();
+        assert_eq!(settings.triggers.hold_threshold(), Duration::from_millis(300));
+        
+        settings.triggers.double_tap_window_ms = 0;
+        assert!(settings.validate().is_err());
+        settings.fix_invalid_values();
+        assert_eq!(settings.triggers.double_tap_window_ms, 50);
+        
+        // 触发方式只能用于热键
+        let mut config = AppConfig::default();
+        config.macro_config.hotkeys.insert("deploy_operator".to_string(), "hold:1,tap:1".to_string());
+        assert!(config.macro_config.validate().is_ok());
+        config.global_settings.game_keys.insert("battle_speed".to_string(), "hold:2".to_string());
+        assert!(config.global_settings.validate().is_err());
+    }
+
     #[test]
     fn test_ui_settings_validation() {
         let mut settings = UISettings::default();
@@ -677,6 +927,30 @@ mod tests {
         assert_eq!(config, deserialized);
     }
 
+    #[test]
+    fn test_sequences_saved_as_scripts() {
+        let mut json = serde_json::to_value(MacroModeConfig::default()).unwrap();
+        json["sequences"] = serde_json::json!({
+            "deploy_operator": "key 1\nwait 3f\ndrag (0.9,0.92)->(0.5,0.5) 180ms",
+        });
+
+        let config: MacroModeConfig = serde_json::from_value(json.clone()).unwrap();
+        let sequence = &config.sequences["deploy_operator"];
+        assert_eq!(sequence.name, "deploy_operator");
+        assert_eq!(sequence.actions.len(), 3);
+
+        // 保存为脚本文本
+        let saved = serde_json::to_value(&config).unwrap();
+        let script = saved["sequences"]["deploy_operator"].as_str().unwrap();
+        assert!(script.starts_with("@name deploy_operator\n"));
+        assert_eq!(serde_json::from_value::<MacroModeConfig>(saved).unwrap(), config);
+
+        // 脚本错误带有位置
+        json["sequences"]["deploy_operator"] = serde_json::json!("key 1\nwait soon");
+        let error = serde_json::from_value::<MacroModeConfig>(json).unwrap_err();
+        assert!(error.to_string().contains("第 2 行第 6 列"), "{}", error);
+    }
+
     #[test]
     fn test_config_fix_comprehensive() {
         let mut config = AppConfig::default();
@@ -0,0 +1,398 @@
+//! 热键冲突检查
+//!
+//! 检查两种模式的热键和游戏内按键之间的冲突。冲突只作为警告报告，不影响配置是否有效
+
+use super::config::{AppConfig, OperationMode};
+use super::hotkey::{Hotkey, TriggerKind};
+use super::key::KeyCombo;
+use super::operation::{expand_params, is_slot_operation, slot_key, ActionType, DefaultOperations, SLOT_PARAM};
+use crate::utils::ActionResult;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// 遮蔽组合键的按键
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShadowSource {
+    /// 游戏内按键（功能名）
+    GameKey(String),
+    /// 紧急停止按键
+    PanicKey,
+}
+
+/// 热键冲突
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HotkeyConflict {
+    /// 同一模式下多个操作使用相同的热键（组合键和触发方式都相同）
+    DuplicateTrigger {
+        mode: OperationMode,
+        combo: KeyCombo,
+        trigger: TriggerKind,
+        operations: Vec<String>,
+    },
+    /// 热键与游戏内按键相同，按下时游戏会同时响应
+    SharedGameKey {
+        mode: OperationMode,
+        operation: String,
+        combo: KeyCombo,
+        game_function: String,
+    },
+    /// 操作的序列发送了某个操作（可能是自身）的热键，发送时可能触发该操作
+    TriggerLoop {
+        mode: OperationMode,
+        operation: String,
+        combo: KeyCombo,
+        triggered: String,
+    },
+    /// 组合键被其他按键遮蔽
+    ShadowedChord {
+        mode: OperationMode,
+        operation: String,
+        combo: KeyCombo,
+        shadowed_by: ShadowSource,
+    },
+}
+
+impl HotkeyConflict {
+    /// 冲突所在的模式
+    pub fn mode(&self) -> OperationMode {
+        match self {
+            HotkeyConflict::DuplicateTrigger { mode, .. }
+            | HotkeyConflict::SharedGameKey { mode, .. }
+            | HotkeyConflict::TriggerLoop { mode, .. }
+            | HotkeyConflict::ShadowedChord { mode, .. } => *mode,
+        }
+    }
+
+    /// 冲突的热键
+    pub fn combo(&self) -> &KeyCombo {
+        match self {
+            HotkeyConflict::DuplicateTrigger { combo, .. }
+            | HotkeyConflict::SharedGameKey { combo, .. }
+            | HotkeyConflict::TriggerLoop { combo, .. }
+            | HotkeyConflict::ShadowedChord { combo, .. } => combo,
+        }
+    }
+}
+
+impl fmt::Display for HotkeyConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotkeyConflict::DuplicateTrigger { mode, combo, trigger, operations } => {
+                let hotkey = Hotkey::new(combo.clone(), *trigger);
+                write!(f, "{}热键 {} 同时绑定到多个操作: {}", mode, hotkey, operations.join(", "))
+            }
+            HotkeyConflict::SharedGameKey { mode, operation, combo, game_function } => {
+                write!(f, "{}操作 {} 的热键 {} 与游戏内按键 {} 相同，游戏会同时响应", mode, operation, combo, game_function)
+            }
+            HotkeyConflict::TriggerLoop { mode, operation, combo, triggered } if operation == triggered => {
+                write!(f, "{}操作 {} 的序列会发送自身的热键 {}，可能再次触发", mode, operation, combo)
+            }
+            HotkeyConflict::TriggerLoop { mode, operation, combo, triggered } => {
+                write!(f, "{}操作 {} 的序列会发送操作 {} 的热键 {}，可能触发该操作", mode, operation, triggered, combo)
+            }
+            HotkeyConflict::ShadowedChord { mode, operation, combo, shadowed_by: ShadowSource::GameKey(function) } => {
+                write!(f, "{}操作 {} 的组合键 {} 包含游戏内按键 {}，游戏会同时响应", mode, operation, combo, function)
+            }
+            HotkeyConflict::ShadowedChord { mode, operation, combo, shadowed_by: ShadowSource::PanicKey } => {
+                write!(f, "{}操作 {} 的热键 {} 与紧急停止按键相同，不会被触发", mode, operation, combo)
+            }
+        }
+    }
+}
+
+impl AppConfig {
+    /// 检查热键冲突
+    ///
+    /// 每种模式分别检查：重复的热键、与游戏内按键相同的热键、序列（展开参数后）发送的按键
+    /// 是已绑定热键的操作、主按键是游戏内按键或与紧急停止按键相同的组合键。
+    /// 触发方式不同的热键可以共用按键，但序列发送按键时各种触发方式都可能被触发。无效的按键配置会被跳过
+    pub fn hotkey_conflicts(&self) -> Vec<HotkeyConflict> {
+        let game_keys = parse_bindings(&self.global_settings.game_keys, KeyCombo::parse_list);
+        let panic_key = self.global_settings.safety.panic_key.parse::<KeyCombo>().ok();
+
+        // 配置中的自定义序列替换默认序列
+        let mut sequences: HashMap<String, _> = DefaultOperations::get_default_operations().into_iter()
+            .map(|operation| (operation.name, operation.sequence))
+            .collect();
+        for (name, sequence) in &self.macro_config.sequences {
+            if let Some(default) = sequences.get_mut(name) {
+                *default = sequence.clone();
+            }
+        }
+
+        let mut conflicts = Vec::new();
+        for (mode, hotkeys) in [
+            (OperationMode::Macro, &self.macro_config.hotkeys),
+            (OperationMode::Intelligent, &self.intelligent_config.hotkeys),
+        ] {
+            let triggers = parse_bindings(hotkeys, Hotkey::parse_list);
+
+            // 重复的热键
+            let mut groups: Vec<(Hotkey, Vec<String>)> = Vec::new();
+            for (operation, hotkey) in &triggers {
+                match groups.iter_mut().find(|(grouped, _)| grouped.matches(hotkey)) {
+                    Some((_, operations)) if !operations.contains(operation) => operations.push(operation.clone()),
+                    Some(_) => {}
+                    None => groups.push((hotkey.generic(), vec![operation.clone()])),
+                }
+            }
+            conflicts.extend(groups.into_iter()
+                .filter(|(_, operations)| operations.len() > 1)
+                .map(|(hotkey, operations)| HotkeyConflict::DuplicateTrigger {
+                    mode,
+                    combo: hotkey.combo().clone(),
+                    trigger: hotkey.trigger(),
+                    operations,
+                }));
+
+            // 每个热键触发时序列发送的按键，槽位操作的第 n 个热键以槽位 n 及其槽位按键执行序列
+            let mut slots: HashMap<(&str, TriggerKind), usize> = HashMap::new();
+            let sent: Vec<Vec<KeyCombo>> = triggers.iter()
+                .map(|(operation, hotkey)| {
+                    let mut keys = Vec::new();
+                    if let Some(sequence) = sequences.get(operation) {
+                        let mut sequence = sequence.clone();
+                        if is_slot_operation(operation) {
+                            let slot = slots.entry((operation.as_str(), hotkey.trigger())).or_default();
+                            *slot += 1;
+                            sequence.params.insert(SLOT_PARAM.to_string(), *slot as i64);
+                            if let Some(key) = self.macro_config.slot_keys.get(operation).and_then(|keys| slot_key(keys, *slot)) {
+                                sequence = sequence.with_slot_key(&key);
+                            }
+                        }
+                        sent_keys(&sequence.actions, &sequence.params, &mut keys);
+                    }
+                    keys
+                })
+                .collect();
+
+            for ((operation, hotkey), sent) in triggers.iter().zip(&sent) {
+                let combo = hotkey.combo();
+
+                // 发送的按键会触发的操作
+                for (triggered, bound) in &triggers {
+                    if !sent.iter().any(|key| bound.combo().matches(key)) {
+                        continue;
+                    }
+                    let conflict = HotkeyConflict::TriggerLoop {
+                        mode,
+                        operation: operation.clone(),
+                        combo: bound.combo().generic(),
+                        triggered: triggered.clone(),
+                    };
+                    if !conflicts.contains(&conflict) {
+                        conflicts.push(conflict);
+                    }
+                }
+
+                let plain = KeyCombo::single(combo.key());
+                for (function, game_key) in &game_keys {
+                    if combo.matches(game_key) {
+                        conflicts.push(HotkeyConflict::SharedGameKey {
+                            mode,
+                            operation: operation.clone(),
+                            combo: combo.generic(),
+                            game_function: function.clone(),
+                        });
+                    } else if !combo.modifiers().is_empty() && plain.matches(game_key) {
+                        conflicts.push(HotkeyConflict::ShadowedChord {
+                            mode,
+                            operation: operation.clone(),
+                            combo: combo.generic(),
+                            shadowed_by: ShadowSource::GameKey(function.clone()),
+                        });
+                    }
+                }
+
+                if panic_key.as_ref().is_some_and(|panic_key| panic_key.matches(combo)) {
+                    conflicts.push(HotkeyConflict::ShadowedChord {
+                        mode,
+                        operation: operation.clone(),
+                        combo: combo.generic(),
+                        shadowed_by: ShadowSource::PanicKey,
+                    });
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+/// 收集序列发送的按键（包括循环和分支中的按键，不展开调用的序列）
+fn sent_keys(actions: &[ActionType], params: &BTreeMap<String, i64>, keys: &mut Vec<KeyCombo>) {
+    for action in actions {
+        match action {
+            ActionType::KeyPress(key) | ActionType::KeyDown(key) | ActionType::KeyHold(key, _) => {
+                if let Some(combo) = expand_params(key, params).ok().and_then(|key| key.parse().ok()) {
+                    keys.push(combo);
+                }
+            }
+            ActionType::Repeat { body, .. } | ActionType::Loop { body, .. } => sent_keys(body, params, keys),
+            ActionType::If { then, otherwise, .. } => {
+                sent_keys(then, params, keys);
+                sent_keys(otherwise, params, keys);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 解析按键表，按名称排序，跳过空的和无效的配置
+fn parse_bindings<T>(keys: &HashMap<String, String>, parse: fn(&str) -> ActionResult<Vec<T>>) -> Vec<(String, T)> {
+    let mut names: Vec<&String> = keys.keys().collect();
+    names.sort();
+
+    names.into_iter()
+        .filter(|name| !keys[*name].trim().is_empty())
+        .filter_map(|name| parse(&keys[name]).ok().map(|entries| (name, entries)))
+        .flat_map(|(name, entries)| entries.into_iter().map(move |entry| (name.clone(), entry)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 没有冲突的配置
+    fn config() -> AppConfig {
+        let mut config = AppConfig::default();
+        for hotkeys in [&mut config.macro_config.hotkeys, &mut config.intelligent_config.hotkeys] {
+            hotkeys.insert("deploy_operator".to_string(), "F1".to_string());
+            hotkeys.insert("pause_game".to_string(), "P".to_string());
+        }
+        config.global_settings.game_keys.insert("battle_speed".to_string(), "F10".to_string());
+        config
+    }
+
+    #[test]
+    fn test_default_config_conflicts() {
+        let conflicts = AppConfig::default().validate().unwrap();
+        let expected = |mode| vec![
+            HotkeyConflict::SharedGameKey {
+                mode,
+                operation: "activate_skill".to_string(),
+                combo: "2".parse().unwrap(),
+                game_function: "battle_speed".to_string(),
+            },
+            // 槽位 1 的按键与热键相同
+            HotkeyConflict::TriggerLoop {
+                mode,
+                operation: "deploy_operator".to_string(),
+                combo: "1".parse().unwrap(),
+                triggered: "deploy_operator".to_string(),
+            },
+            HotkeyConflict::SharedGameKey {
+                mode,
+                operation: "pause_game".to_string(),
+                combo: "Space".parse().unwrap(),
+                game_function: "skill_activation".to_string(),
+            },
+        ];
+        assert_eq!(conflicts, [expected(OperationMode::Macro), expected(OperationMode::Intelligent)].concat());
+        assert_eq!(conflicts[1].to_string(), "宏模式操作 deploy_operator 的序列会发送自身的热键 1，可能再次触发");
+        assert_eq!(conflicts[2].to_string(), "宏模式操作 pause_game 的热键 Space 与游戏内按键 skill_activation 相同，游戏会同时响应");
+    }
+
+    #[test]
+    fn test_conflicts_reported() {
+        assert!(config().hotkey_conflicts().is_empty());
+
+        let mut config = config();
+        for (name, script) in [
+            ("activate_skill", "repeat 2 {\n    key F$slot\n}"),
+            ("deploy_operator", "key $slot"),
+            ("retreat_operator", "key $slot_key\nkey Delete"),
+        ] {
+            config.macro_config.sequences.insert(name.to_string(), script.parse().unwrap());
+        }
+        config.macro_config.slot_keys.insert("retreat_operator".to_string(), "F3,F4".to_string());
+        let hotkeys = &mut config.macro_config.hotkeys;
+        hotkeys.insert("activate_skill".to_string(), "F3,F2".to_string());
+        hotkeys.insert("focus_view".to_string(), "lctrl+1".to_string());
+        hotkeys.insert("deploy_operator".to_string(), "1,Ctrl+1".to_string());
+        hotkeys.insert("pause_game".to_string(), "2".to_string());
+        hotkeys.insert("retreat_operator".to_string(), "Shift+Delete".to_string());
+        let hotkeys = &mut config.intelligent_config.hotkeys;
+        hotkeys.insert("focus_view".to_string(), "Ctrl+Shift+F12".to_string());
+        hotkeys.insert("pause_game".to_string(), "Space".to_string());
+
+        let conflicts = config.validate().unwrap();
+        assert_eq!(conflicts, vec![
+            HotkeyConflict::DuplicateTrigger {
+                mode: OperationMode::Macro,
+                combo: "Ctrl+1".parse().unwrap(),
+                trigger: TriggerKind::Press,
+                operations: vec!["deploy_operator".to_string(), "focus_view".to_string()],
+            },
+            // 槽位 2 的序列发送 F2
+            HotkeyConflict::TriggerLoop {
+                mode: OperationMode::Macro,
+                operation: "activate_skill".to_string(),
+                combo: "F2".parse().unwrap(),
+                triggered: "activate_skill".to_string(),
+            },
+            HotkeyConflict::TriggerLoop {
+                mode: OperationMode::Macro,
+                operation: "deploy_operator".to_string(),
+                combo: "1".parse().unwrap(),
+                triggered: "deploy_operator".to_string(),
+            },
+            HotkeyConflict::TriggerLoop {
+                mode: OperationMode::Macro,
+                operation: "deploy_operator".to_string(),
+                combo: "2".parse().unwrap(),
+                triggered: "pause_game".to_string(),
+            },
+            // 槽位 1 的按键 F3
+            HotkeyConflict::TriggerLoop {
+                mode: OperationMode::Macro,
+                operation: "retreat_operator".to_string(),
+                combo: "F3".parse().unwrap(),
+                triggered: "activate_skill".to_string(),
+            },
+            HotkeyConflict::ShadowedChord {
+                mode: OperationMode::Macro,
+                operation: "retreat_operator".to_string(),
+                combo: "Shift+Delete".parse().unwrap(),
+                shadowed_by: ShadowSource::GameKey("retreat_operator".to_string()),
+            },
+            // 自定义序列在两种模式下都生效
+            HotkeyConflict::TriggerLoop {
+                mode: OperationMode::Intelligent,
+                operation: "activate_skill".to_string(),
+                combo: "F1".parse().unwrap(),
+                triggered: "deploy_operator".to_string(),
+            },
+            HotkeyConflict::ShadowedChord {
+                mode: OperationMode::Intelligent,
+                operation: "focus_view".to_string(),
+                combo: "Ctrl+Shift+F12".parse().unwrap(),
+                shadowed_by: ShadowSource::PanicKey,
+            },
+            HotkeyConflict::SharedGameKey {
+                mode: OperationMode::Intelligent,
+                operation: "pause_game".to_string(),
+                combo: "Space".parse().unwrap(),
+                game_function: "skill_activation".to_string(),
+            },
+        ]);
+        assert_eq!(conflicts[0].to_string(), "宏模式热键 Ctrl+1 同时绑定到多个操作: deploy_operator, focus_view");
+        assert_eq!(conflicts[2].to_string(), "宏模式操作 deploy_operator 的序列会发送自身的热键 1，可能再次触发");
+        assert_eq!(conflicts[7].mode(), OperationMode::Intelligent);
+
+        // 触发方式不同的热键可以共用按键
+        let mut config = self::config();
+        let hotkeys = &mut config.macro_config.hotkeys;
+        hotkeys.insert("deploy_operator".to_string(), "tap:F1,hold:F1".to_string());
+        hotkeys.insert("focus_view".to_string(), "hold:lctrl+1".to_string());
+        hotkeys.insert("pause_game".to_string(), "hold:Ctrl+1".to_string());
+        let conflicts = config.hotkey_conflicts();
+        assert_eq!(conflicts, vec![HotkeyConflict::DuplicateTrigger {
+            mode: OperationMode::Macro,
+            combo: "Ctrl+1".parse().unwrap(),
+            trigger: TriggerKind::Hold,
+            operations: vec!["focus_view".to_string(), "pause_game".to_string()],
+        }]);
+        assert_eq!(conflicts[0].to_string(), "宏模式热键 hold:Ctrl+1 同时绑定到多个操作: focus_view, pause_game");
+    }
+}
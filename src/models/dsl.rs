@@ -0,0 +1,1232 @@
+//! 操作序列脚本
+//!
+//! 按行书写的操作序列文本格式，与 `ActionSequence` 可以无损地相互转换：
+//!
+//! ```text
+//! @name deploy
+//! key 1
+//! wait 3f
+//! drag (0.9,0.92)->(0.5,0.5) 180ms
+//! click right client(640,360)
+//! repeat 3 {
+//!     hold Space 200ms
+//! }
+//! if game(InBattle) {
+//!     call skill
+//! } else {
+//!     wait game 1s
+//! }
+//! ```
+//!
+//! - 时长单位：`ns`、`us`、`ms`、`s`，以及按 30 帧每秒计算的游戏帧 `f`
+//! - 位置：`(x,y)` 为整数时是屏幕像素坐标，含小数时是窗口比例坐标；
+//!   `client(x,y)`、`screen(x,y)`、`norm(x,y)` 显式指定坐标系
+//! - 条件：`program(Running)`、`game(InBattle)`、`vision(名称)`、`not(..)`、`all(..)`、`any(..)`
+//! - `@param 名称 值` 声明整数参数，按键名称和 `call` 的序列名称中的 `$名称` 在执行时替换为参数值
+//! - `#` 开头到行尾为注释；含空白或特殊字符的名称用双引号括起
+
+use super::operation::{
+    is_param_name, ActionSequence, ActionType, Condition, CoordinateSpace, Easing, HumanizeConfig, MouseButton, Position,
+};
+use super::state::{GameState, ProgramState};
+use crate::utils::{ActionError, ActionResult};
+use std::borrow::Cow;
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// 游戏逻辑帧率，`f` 单位按此换算
+pub const GAME_FRAME_RATE: u64 = 30;
+
+/// 拖拽未指定步数时的默认步数
+pub const DEFAULT_DRAG_STEPS: u32 = 10;
+
+/// 缩进宽度
+const INDENT: &str = "    ";
+
+/// 指定帧数对应的时长
+pub fn frames(count: u64) -> Duration {
+    // 按 u128 计算，任意帧数都不会溢出
+    let nanos = u128::from(count) * 1_000_000_000 / u128::from(GAME_FRAME_RATE);
+    Duration::new((nanos / 1_000_000_000) as u64, (nanos % 1_000_000_000) as u32)
+}
+
+impl FromStr for ActionSequence {
+    type Err = ActionError;
+
+    /// 解析序列脚本，出错时返回带行列位置的 `ScriptSyntax`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = tokenize_script(s)?;
+        Parser { lines: &lines, next: 0 }.parse()
+    }
+}
+
+impl fmt::Display for ActionSequence {
+    /// 输出序列脚本
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut header = false;
+        if !self.name.is_empty() {
+            writeln!(f, "@name {}", quote(&self.name))?;
+            header = true;
+        }
+        if let Some(description) = &self.description {
+            writeln!(f, "@description {}", quote(description))?;
+            header = true;
+        }
+        if self.humanize != HumanizeConfig::default() {
+            writeln!(f, "@humanize {}", format_humanize(&self.humanize))?;
+            header = true;
+        }
+        for (name, value) in &self.params {
+            writeln!(f, "@param {} {}", name, value)?;
+            header = true;
+        }
+        if header && !self.actions.is_empty() {
+            writeln!(f)?;
+        }
+
+        let mut out = String::new();
+        write_block(&mut out, &self.actions, 0)?;
+        f.write_str(&out)
+    }
+}
+
+// ===== 输出 =====
+
+/// 按缩进层级输出一组操作
+fn write_block(out: &mut String, actions: &[ActionType], depth: usize) -> fmt::Result {
+    let indent = INDENT.repeat(depth);
+    for action in actions {
+        out.push_str(&indent);
+        match action {
+            ActionType::KeyPress(key) => writeln!(out, "key {}", quote(key))?,
+            ActionType::KeyDown(key) => writeln!(out, "down {}", quote(key))?,
+            ActionType::KeyUp(key) => writeln!(out, "up {}", quote(key))?,
+            ActionType::KeyHold(key, duration) => {
+                writeln!(out, "hold {} {}", quote(key), format_duration(*duration))?
+            }
+            ActionType::MouseMove(x, y) => writeln!(out, "move ({},{})", x, y)?,
+            ActionType::MouseMoveRelative(dx, dy) => writeln!(out, "move by ({},{})", dx, dy)?,
+            ActionType::MouseMoveTo(position) => writeln!(out, "move {}", format_position(position))?,
+            ActionType::MouseClick(button, x, y) => {
+                writeln!(out, "click {}({},{})", format_button(*button), x, y)?
+            }
+            ActionType::MouseClickAt(button, position) => {
+                writeln!(out, "click {}{}", format_button(*button), format_position(position))?
+            }
+            ActionType::Scroll { dx, dy } => writeln!(out, "scroll {} {}", dx, dy)?,
+            ActionType::MouseDrag { button, from, to, duration, steps, easing } => writeln!(
+                out,
+                "drag {}({},{})->({},{}) {}{}",
+                format_button(*button), from.0, from.1, to.0, to.1,
+                format_duration(*duration), format_drag_options(*steps, *easing),
+            )?,
+            ActionType::MouseDragAt { button, from, to, duration, steps, easing } => writeln!(
+                out,
+                "drag {}{}->{} {}{}",
+                format_button(*button), format_position(from), format_position(to),
+                format_duration(*duration), format_drag_options(*steps, *easing),
+            )?,
+            ActionType::Wait(duration) => writeln!(out, "wait {}", format_duration(*duration))?,
+            ActionType::GameWait(duration) => writeln!(out, "wait game {}", format_duration(*duration))?,
+            ActionType::Repeat { count, body } => {
+                writeln!(out, "repeat {} {{", count)?;
+                write_block(out, body, depth + 1)?;
+                writeln!(out, "{}}}", indent)?;
+            }
+            ActionType::Loop { until, max_iterations, body } => {
+                writeln!(out, "loop until {} max {} {{", format_condition(until), max_iterations)?;
+                write_block(out, body, depth + 1)?;
+                writeln!(out, "{}}}", indent)?;
+            }
+            ActionType::Call(name) => writeln!(out, "call {}", quote(name))?,
+            ActionType::If { condition, then, otherwise } => {
+                writeln!(out, "if {} {{", format_condition(condition))?;
+                write_block(out, then, depth + 1)?;
+                if otherwise.is_empty() {
+                    writeln!(out, "{}}}", indent)?;
+                } else {
+                    writeln!(out, "{}}} else {{", indent)?;
+                    write_block(out, otherwise, depth + 1)?;
+                    writeln!(out, "{}}}", indent)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 格式化时长，优先使用能精确表示的最大单位
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    if nanos == 0 {
+        return "0ms".to_string();
+    }
+    if nanos.is_multiple_of(1_000_000_000) {
+        return format!("{}s", nanos / 1_000_000_000);
+    }
+    if nanos.is_multiple_of(1_000_000) {
+        return format!("{}ms", nanos / 1_000_000);
+    }
+    if nanos.is_multiple_of(1_000) {
+        return format!("{}us", nanos / 1_000);
+    }
+
+    let count = (nanos * GAME_FRAME_RATE as u128 + 500_000_000) / 1_000_000_000;
+    if u64::try_from(count).is_ok_and(|count| frames(count) == duration) {
+        return format!("{}f", count);
+    }
+    format!("{}ns", nanos)
+}
+
+/// 格式化位置
+fn format_position(position: &Position) -> String {
+    match position.space {
+        CoordinateSpace::Screen => format!("screen({},{})", position.x, position.y),
+        CoordinateSpace::WindowClient => format!("client({},{})", position.x, position.y),
+        // Debug 格式总是带小数点，与整数的屏幕坐标区分
+        CoordinateSpace::Normalized => format!("({:?},{:?})", position.x, position.y),
+    }
+}
+
+/// 格式化鼠标按钮（左键省略），带尾随空格
+fn format_button(button: MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "",
+        MouseButton::Right => "right ",
+        MouseButton::Middle => "middle ",
+    }
+}
+
+/// 格式化拖拽的步数和缓动曲线（默认值省略），带前导空格
+fn format_drag_options(steps: u32, easing: Easing) -> String {
+    let mut options = String::new();
+    if steps != DEFAULT_DRAG_STEPS {
+        options.push_str(&format!(" steps {}", steps));
+    }
+    if easing != Easing::Linear {
+        options.push(' ');
+        options.push_str(easing_name(easing));
+    }
+    options
+}
+
+/// 缓动曲线名称
+fn easing_name(easing: Easing) -> &'static str {
+    match easing {
+        Easing::Linear => "linear",
+        Easing::EaseIn => "ease-in",
+        Easing::EaseOut => "ease-out",
+        Easing::EaseInOut => "ease-in-out",
+    }
+}
+
+/// 格式化条件
+fn format_condition(condition: &Condition) -> String {
+    let list = |conditions: &[Condition]| {
+        conditions.iter().map(format_condition).collect::<Vec<_>>().join(", ")
+    };
+    match condition {
+        Condition::ProgramState(state) => format!("program({:?})", state),
+        Condition::GameState(state) => format!("game({:?})", state),
+        Condition::Vision(name) => format!("vision({})", quote(name)),
+        Condition::Not(inner) => format!("not({})", format_condition(inner)),
+        Condition::All(conditions) => format!("all({})", list(conditions)),
+        Condition::Any(conditions) => format!("any({})", list(conditions)),
+    }
+}
+
+/// 格式化拟人化设置（默认值省略）
+fn format_humanize(config: &HumanizeConfig) -> String {
+    let mut out = if config.enabled { "on" } else { "off" }.to_string();
+    if !config.wait_jitter.is_zero() {
+        out.push_str(&format!(" wait {}", format_duration(config.wait_jitter)));
+    }
+    if config.position_jitter != 0 {
+        out.push_str(&format!(" position {}", config.position_jitter));
+    }
+    if config.drag_jitter != 0 {
+        out.push_str(&format!(" drag {}", config.drag_jitter));
+    }
+    if let Some(seed) = config.seed {
+        out.push_str(&format!(" seed {}", seed));
+    }
+    out
+}
+
+/// 必要时给名称加引号
+fn quote(text: &str) -> Cow<'_, str> {
+    let plain = !text.is_empty()
+        && !text.starts_with('#')
+        && !text.chars().any(|c| c.is_whitespace() || is_delimiter(c) || c == '\\');
+    if plain {
+        return Cow::Borrowed(text);
+    }
+
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    Cow::Owned(quoted)
+}
+
+// ===== 解析 =====
+
+/// 单词之间的分隔符
+fn is_delimiter(c: char) -> bool {
+    matches!(c, '(' | ')' | ',' | '{' | '}' | '"')
+}
+
+/// 词法单元
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    /// 单词（关键字、按键、数字等）
+    Word(String),
+    /// 带引号的文本
+    Quoted(String),
+    /// `(`
+    Open,
+    /// `)`
+    Close,
+    /// `,`
+    Comma,
+    /// `{`
+    BlockOpen,
+    /// `}`
+    BlockClose,
+}
+
+/// 带位置的词法单元
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// 列号（从 1 开始）
+    column: usize,
+}
+
+/// 一行的词法单元
+#[derive(Debug)]
+struct Line {
+    /// 行号（从 1 开始）
+    number: usize,
+    tokens: Vec<Token>,
+    /// 行尾之后的列号，用于报告缺少的内容
+    end_column: usize,
+}
+
+/// 将一行拆分为词法单元
+fn tokenize(number: usize, text: &str) -> ActionResult<Line> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index];
+        let column = index + 1;
+        if c.is_whitespace() {
+            index += 1;
+            continue;
+        }
+        if c == '#' {
+            break;
+        }
+
+        let kind = match c {
+            '(' => TokenKind::Open,
+            ')' => TokenKind::Close,
+            ',' => TokenKind::Comma,
+            '{' => TokenKind::BlockOpen,
+            '}' => TokenKind::BlockClose,
+            '"' => {
+                let mut text = String::new();
+                index += 1;
+                loop {
+                    let Some(&c) = chars.get(index) else {
+                        return Err(syntax_error(number, column, "引号未闭合"));
+                    };
+                    index += 1;
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            let escaped = match chars.get(index) {
+                                Some('"') => '"',
+                                Some('\\') => '\\',
+                                Some('n') => '\n',
+                                Some('r') => '\r',
+                                Some('t') => '\t',
+                                _ => return Err(syntax_error(number, index, "无效的转义字符")),
+                            };
+                            text.push(escaped);
+                            index += 1;
+                        }
+                        c => text.push(c),
+                    }
+                }
+                tokens.push(Token { kind: TokenKind::Quoted(text), column });
+                continue;
+            }
+            _ => {
+                let start = index;
+                while index < chars.len() && !chars[index].is_whitespace() && !is_delimiter(chars[index]) {
+                    index += 1;
+                }
+                let word: String = chars[start..index].iter().collect();
+                tokens.push(Token { kind: TokenKind::Word(word), column });
+                continue;
+            }
+        };
+        tokens.push(Token { kind, column });
+        index += 1;
+    }
+
+    Ok(Line {
+        number,
+        tokens,
+        end_column: chars.len() + 1,
+    })
+}
+
+/// 创建语法错误
+fn syntax_error(line: usize, column: usize, message: impl Into<String>) -> ActionError {
+    ActionError::ScriptSyntax {
+        line,
+        column,
+        message: message.into(),
+    }
+}
+
+/// 块的结束方式
+enum BlockEnd {
+    /// 文本结束
+    Eof,
+    /// `}`
+    Close,
+    /// `} else {`
+    Else,
+}
+
+/// 拆分脚本中的非空行
+fn tokenize_script(text: &str) -> ActionResult<Vec<Line>> {
+    text.lines()
+        .enumerate()
+        .map(|(index, line)| tokenize(index + 1, line))
+        .filter(|line| !matches!(line, Ok(line) if line.tokens.is_empty()))
+        .collect()
+}
+
+/// 序列脚本解析器
+struct Parser<'a> {
+    /// 非空行
+    lines: &'a [Line],
+    /// 下一行的位置
+    next: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(mut self) -> ActionResult<ActionSequence> {
+        let lines = self.lines;
+        let mut sequence = ActionSequence::new(String::new());
+        let (mut name, mut description, mut humanize) = (None, None, None);
+
+        // 文件头的指令
+        while let Some(line) = lines.get(self.next) {
+            let mut cursor = Cursor::new(line);
+            let directive = match cursor.peek() {
+                Some(TokenKind::Word(word)) if word.starts_with('@') => word.clone(),
+                _ => break,
+            };
+            let column = line.tokens[0].column;
+            cursor.advance();
+            let slot_taken = match directive.as_str() {
+                "@name" => name.replace(cursor.text("序列名称")?).is_some(),
+                "@description" => description.replace(cursor.text("描述")?).is_some(),
+                "@humanize" => humanize.replace(cursor.humanize()?).is_some(),
+                "@param" => {
+                    let (param, param_column) = cursor.word("参数名称")?;
+                    if !is_param_name(&param) {
+                        return Err(syntax_error(line.number, param_column, format!("无效的参数名称: {}", param)));
+                    }
+                    let value = cursor.number("参数值")?;
+                    if sequence.params.insert(param.clone(), value).is_some() {
+                        return Err(syntax_error(line.number, param_column, format!("重复的参数: {}", param)));
+                    }
+                    false
+                }
+                _ => return Err(syntax_error(line.number, column, format!("未知的指令: {}", directive))),
+            };
+            if slot_taken {
+                return Err(syntax_error(line.number, column, format!("重复的指令: {}", directive)));
+            }
+            cursor.finish()?;
+            self.next += 1;
+        }
+
+        sequence.name = name.unwrap_or_default();
+        sequence.description = description;
+        sequence.humanize = humanize.unwrap_or_default();
+
+        let (actions, end) = self.block()?;
+        if let BlockEnd::Close | BlockEnd::Else = end {
+            let line = &self.lines[self.next - 1];
+            return Err(syntax_error(line.number, line.tokens[0].column, "多余的 }"));
+        }
+        sequence.actions = actions;
+        Ok(sequence)
+    }
+
+    /// 解析一组操作，直到 `}` 或文本结束
+    fn block(&mut self) -> ActionResult<(Vec<ActionType>, BlockEnd)> {
+        let lines = self.lines;
+        let mut actions = Vec::new();
+        while let Some(line) = lines.get(self.next) {
+            self.next += 1;
+            let number = line.number;
+            let mut cursor = Cursor::new(line);
+
+            if cursor.peek() == Some(&TokenKind::BlockClose) {
+                cursor.advance();
+                if cursor.is_done() {
+                    return Ok((actions, BlockEnd::Close));
+                }
+                cursor.keyword("else")?;
+                cursor.expect(TokenKind::BlockOpen, "{")?;
+                cursor.finish()?;
+                return Ok((actions, BlockEnd::Else));
+            }
+
+            let (keyword, column) = cursor.word("操作")?;
+            let action = match keyword.as_str() {
+                "key" => ActionType::KeyPress(cursor.text("按键")?),
+                "down" => ActionType::KeyDown(cursor.text("按键")?),
+                "up" => ActionType::KeyUp(cursor.text("按键")?),
+                "hold" => ActionType::KeyHold(cursor.text("按键")?, cursor.duration()?),
+                "wait" => {
+                    if cursor.peek_word() == Some("game") {
+                        cursor.advance();
+                        ActionType::GameWait(cursor.duration()?)
+                    } else {
+                        ActionType::Wait(cursor.duration()?)
+                    }
+                }
+                "move" => {
+                    if cursor.peek_word() == Some("by") {
+                        cursor.advance();
+                        let (dx, dy) = cursor.int_pair()?;
+                        ActionType::MouseMoveRelative(dx, dy)
+                    } else {
+                        match cursor.point()? {
+                            Point::Pixels(x, y) => ActionType::MouseMove(x, y),
+                            Point::Position(position) => ActionType::MouseMoveTo(position),
+                        }
+                    }
+                }
+                "click" => {
+                    let button = cursor.button();
+                    match cursor.point()? {
+                        Point::Pixels(x, y) => ActionType::MouseClick(button, x, y),
+                        Point::Position(position) => ActionType::MouseClickAt(button, position),
+                    }
+                }
+                "drag" => cursor.drag()?,
+                "scroll" => {
+                    let dx = cursor.number("水平滚动格数")?;
+                    let dy = cursor.number("垂直滚动格数")?;
+                    ActionType::Scroll { dx, dy }
+                }
+                "call" => ActionType::Call(cursor.text("序列名称")?),
+                "repeat" => {
+                    let count = cursor.number("重复次数")?;
+                    cursor.expect(TokenKind::BlockOpen, "{")?;
+                    cursor.finish()?;
+                    let body = self.body(number, column)?;
+                    ActionType::Repeat { count, body }
+                }
+                "loop" => {
+                    cursor.keyword("until")?;
+                    let until = cursor.condition()?;
+                    cursor.keyword("max")?;
+                    let max_iterations = cursor.number("最大循环次数")?;
+                    cursor.expect(TokenKind::BlockOpen, "{")?;
+                    cursor.finish()?;
+                    let body = self.body(number, column)?;
+                    ActionType::Loop { until, max_iterations, body }
+                }
+                "if" => {
+                    let condition = cursor.condition()?;
+                    cursor.expect(TokenKind::BlockOpen, "{")?;
+                    cursor.finish()?;
+                    let (then, end) = self.block()?;
+                    let otherwise = match end {
+                        BlockEnd::Close => Vec::new(),
+                        BlockEnd::Else => self.body(number, column)?,
+                        BlockEnd::Eof => return Err(syntax_error(number, column, "块缺少对应的 }")),
+                    };
+                    ActionType::If { condition, then, otherwise }
+                }
+                _ => return Err(syntax_error(number, column, format!("未知的操作: {}", keyword))),
+            };
+
+            // 块开头的行已在读取块之前检查过结尾
+            if !matches!(action, ActionType::Repeat { .. } | ActionType::Loop { .. } | ActionType::If { .. }) {
+                cursor.finish()?;
+            }
+            actions.push(action);
+        }
+        Ok((actions, BlockEnd::Eof))
+    }
+
+    /// 解析以 `}` 结束的块，`line`、`column` 是块开头的位置
+    fn body(&mut self, line: usize, column: usize) -> ActionResult<Vec<ActionType>> {
+        let (actions, end) = self.block()?;
+        match end {
+            BlockEnd::Close => Ok(actions),
+            BlockEnd::Else => {
+                let line = &self.lines[self.next - 1];
+                Err(syntax_error(line.number, line.tokens[0].column, "只有 if 块可以有 else"))
+            }
+            BlockEnd::Eof => Err(syntax_error(line, column, "块缺少对应的 }")),
+        }
+    }
+}
+
+/// 解析得到的位置
+enum Point {
+    /// 整数屏幕坐标（`MouseMove` / `MouseClick` / `MouseDrag`）
+    Pixels(i32, i32),
+    /// 带坐标系的位置
+    Position(Position),
+}
+
+impl Point {
+    fn into_position(self) -> Position {
+        match self {
+            Point::Pixels(x, y) => Position::screen(x, y),
+            Point::Position(position) => position,
+        }
+    }
+}
+
+/// 一行词法单元的读取位置
+struct Cursor<'a> {
+    line: &'a Line,
+    index: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(line: &'a Line) -> Self {
+        Self { line, index: 0 }
+    }
+
+    fn peek(&self) -> Option<&'a TokenKind> {
+        self.line.tokens.get(self.index).map(|token| &token.kind)
+    }
+
+    fn peek_word(&self) -> Option<&'a str> {
+        match self.peek() {
+            Some(TokenKind::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+
+    fn advance(&mut self) {
+        self.index += 1;
+    }
+
+    fn is_done(&self) -> bool {
+        self.index >= self.line.tokens.len()
+    }
+
+    /// 当前位置的列号，行已结束时为行尾
+    fn column(&self) -> usize {
+        self.line.tokens.get(self.index)
+            .map_or(self.line.end_column, |token| token.column)
+    }
+
+    /// 当前位置的错误
+    fn error(&self, message: impl Into<String>) -> ActionError {
+        syntax_error(self.line.number, self.column(), message)
+    }
+
+    /// 当前位置缺少预期内容的错误
+    fn expected(&self, what: &str) -> ActionError {
+        if self.is_done() {
+            self.error(format!("缺少{}", what))
+        } else {
+            self.error(format!("应为{}", what))
+        }
+    }
+
+    /// 检查行已结束
+    fn finish(&self) -> ActionResult<()> {
+        if self.is_done() {
+            Ok(())
+        } else {
+            Err(self.error("多余的内容"))
+        }
+    }
+
+    /// 读取指定符号
+    fn expect(&mut self, kind: TokenKind, symbol: &str) -> ActionResult<()> {
+        if self.peek() == Some(&kind) {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.expected(&format!(" {}", symbol)))
+        }
+    }
+
+    /// 读取指定关键字
+    fn keyword(&mut self, keyword: &str) -> ActionResult<()> {
+        if self.peek_word() == Some(keyword) {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.expected(&format!(" {}", keyword)))
+        }
+    }
+
+    /// 读取单词，返回单词和列号
+    fn word(&mut self, what: &str) -> ActionResult<(String, usize)> {
+        let column = self.column();
+        match self.peek() {
+            Some(TokenKind::Word(word)) => {
+                self.advance();
+                Ok((word.clone(), column))
+            }
+            _ => Err(self.expected(what)),
+        }
+    }
+
+    /// 读取单词或带引号的文本
+    fn text(&mut self, what: &str) -> ActionResult<String> {
+        match self.peek() {
+            Some(TokenKind::Word(text)) | Some(TokenKind::Quoted(text)) => {
+                self.advance();
+                Ok(text.clone())
+            }
+            _ => Err(self.expected(what)),
+        }
+    }
+
+    /// 读取数字
+    fn number<T: FromStr>(&mut self, what: &str) -> ActionResult<T> {
+        let column = self.column();
+        let (word, _) = self.word(what)?;
+        word.parse()
+            .map_err(|_| syntax_error(self.line.number, column, format!("无效的{}: {}", what, word)))
+    }
+
+    /// 读取时长
+    fn duration(&mut self) -> ActionResult<Duration> {
+        let column = self.column();
+        let (word, _) = self.word("时长")?;
+        parse_duration(&word)
+            .map_err(|message| syntax_error(self.line.number, column, message))
+    }
+
+    /// 读取可选的鼠标按钮，默认左键
+    fn button(&mut self) -> MouseButton {
+        let button = match self.peek_word() {
+            Some("left") => MouseButton::Left,
+            Some("right") => MouseButton::Right,
+            Some("middle") => MouseButton::Middle,
+            _ => return MouseButton::Left,
+        };
+        self.advance();
+        button
+    }
+
+    /// 读取 `(a,b)`，返回两个单词及其列号
+    fn pair(&mut self) -> ActionResult<[(String, usize); 2]> {
+        self.expect(TokenKind::Open, "坐标 (x,y)")?;
+        let first = self.word("坐标")?;
+        self.expect(TokenKind::Comma, " ,")?;
+        let second = self.word("坐标")?;
+        self.expect(TokenKind::Close, " )")?;
+        Ok([first, second])
+    }
+
+    /// 读取整数对 `(x,y)`
+    fn int_pair(&mut self) -> ActionResult<(i32, i32)> {
+        let [(x, x_column), (y, y_column)] = self.pair()?;
+        let parse = |text: &str, column| {
+            text.parse::<i32>()
+                .map_err(|_| syntax_error(self.line.number, column, format!("应为整数坐标: {}", text)))
+        };
+        Ok((parse(&x, x_column)?, parse(&y, y_column)?))
+    }
+
+    /// 读取浮点数对 `(x,y)`
+    fn float_pair(&mut self) -> ActionResult<(f64, f64)> {
+        let [(x, x_column), (y, y_column)] = self.pair()?;
+        let parse = |text: &str, column| {
+            text.parse::<f64>()
+                .map_err(|_| syntax_error(self.line.number, column, format!("无效的坐标: {}", text)))
+        };
+        Ok((parse(&x, x_column)?, parse(&y, y_column)?))
+    }
+
+    /// 读取位置
+    fn point(&mut self) -> ActionResult<Point> {
+        let space = match self.peek_word() {
+            Some("screen") => Some(CoordinateSpace::Screen),
+            Some("client") => Some(CoordinateSpace::WindowClient),
+            Some("norm") => Some(CoordinateSpace::Normalized),
+            Some(_) => return Err(self.expected("坐标 (x,y)")),
+            None => None,
+        };
+
+        let Some(space) = space else {
+            // 整数为屏幕像素坐标，否则为比例坐标
+            let start = self.index;
+            if let Ok((x, y)) = self.int_pair() {
+                return Ok(Point::Pixels(x, y));
+            }
+            self.index = start;
+            let (x, y) = self.float_pair()?;
+            return Ok(Point::Position(Position::normalized(x, y)));
+        };
+
+        self.advance();
+        let (x, y) = self.float_pair()?;
+        Ok(Point::Position(Position { x, y, space }))
+    }
+
+    /// 读取拖拽参数
+    fn drag(&mut self) -> ActionResult<ActionType> {
+        let button = self.button();
+        let from = self.point()?;
+        self.keyword("->")?;
+        let to = self.point()?;
+        let duration = self.duration()?;
+
+        let mut steps = None;
+        let mut easing = None;
+        while let Some(word) = self.peek_word() {
+            let column = self.column();
+            let duplicate = if word == "steps" {
+                self.advance();
+                steps.replace(self.number("插值步数")?).is_some()
+            } else {
+                let parsed = match word {
+                    "linear" => Easing::Linear,
+                    "ease-in" => Easing::EaseIn,
+                    "ease-out" => Easing::EaseOut,
+                    "ease-in-out" => Easing::EaseInOut,
+                    _ => return Err(self.error(format!("未知的拖拽选项: {}", word))),
+                };
+                self.advance();
+                easing.replace(parsed).is_some()
+            };
+            if duplicate {
+                return Err(syntax_error(self.line.number, column, format!("重复的拖拽选项: {}", word)));
+            }
+        }
+        let steps = steps.unwrap_or(DEFAULT_DRAG_STEPS);
+        let easing = easing.unwrap_or_default();
+
+        Ok(match (from, to) {
+            (Point::Pixels(x0, y0), Point::Pixels(x1, y1)) => ActionType::MouseDrag {
+                button, from: (x0, y0), to: (x1, y1), duration, steps, easing,
+            },
+            (from, to) => ActionType::MouseDragAt {
+                button, from: from.into_position(), to: to.into_position(), duration, steps, easing,
+            },
+        })
+    }
+
+    /// 读取条件
+    fn condition(&mut self) -> ActionResult<Condition> {
+        let (name, column) = self.word("条件")?;
+        self.expect(TokenKind::Open, " (")?;
+        let condition = match name.as_str() {
+            "program" => {
+                let state = self.state("程序状态", &[
+                    ProgramState::Stopped, ProgramState::Starting, ProgramState::Running, ProgramState::Stopping,
+                ])?;
+                Condition::ProgramState(state)
+            }
+            "game" => {
+                let state = self.state("游戏状态", &[
+                    GameState::NotDetected, GameState::Detected, GameState::InBattle,
+                ])?;
+                Condition::GameState(state)
+            }
+            "vision" => Condition::Vision(self.text("识别名称")?),
+            "not" => Condition::Not(Box::new(self.condition()?)),
+            "all" | "any" => {
+                let mut conditions = Vec::new();
+                if self.peek() != Some(&TokenKind::Close) {
+                    conditions.push(self.condition()?);
+                    while self.peek() == Some(&TokenKind::Comma) {
+                        self.advance();
+                        conditions.push(self.condition()?);
+                    }
+                }
+                if name == "all" {
+                    Condition::All(conditions)
+                } else {
+                    Condition::Any(conditions)
+                }
+            }
+            _ => return Err(syntax_error(self.line.number, column, format!("未知的条件: {}", name))),
+        };
+        self.expect(TokenKind::Close, " )")?;
+        Ok(condition)
+    }
+
+    /// 读取状态名称（与 Debug 输出一致，不区分大小写）
+    fn state<T: Copy + fmt::Debug>(&mut self, what: &str, states: &[T]) -> ActionResult<T> {
+        let column = self.column();
+        let (name, _) = self.word(what)?;
+        states.iter()
+            .copied()
+            .find(|state| format!("{:?}", state).eq_ignore_ascii_case(&name))
+            .ok_or_else(|| syntax_error(self.line.number, column, format!("未知的{}: {}", what, name)))
+    }
+
+    /// 读取拟人化设置：`on|off [wait 时长] [position 像素] [drag 像素] [seed 种子]`
+    fn humanize(&mut self) -> ActionResult<HumanizeConfig> {
+        let mut config = HumanizeConfig {
+            enabled: match self.peek_word() {
+                Some("on") => true,
+                Some("off") => false,
+                _ => return Err(self.expected(" on 或 off")),
+            },
+            ..HumanizeConfig::default()
+        };
+        self.advance();
+
+        while !self.is_done() {
+            let (option, column) = self.word("拟人化选项")?;
+            match option.as_str() {
+                "wait" => config.wait_jitter = self.duration()?,
+                "position" => config.position_jitter = self.number("坐标偏移")?,
+                "drag" => config.drag_jitter = self.number("拖拽偏移")?,
+                "seed" => config.seed = Some(self.number("随机数种子")?),
+                _ => return Err(syntax_error(self.line.number, column, format!("未知的拟人化选项: {}", option))),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// 解析时长，如 `100ms`、`1.5s`、`3f`
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let split = text.find(|c: char| c.is_alphabetic())
+        .ok_or_else(|| format!("时长缺少单位: {}", text))?;
+    let (number, unit) = text.split_at(split);
+
+    let invalid = || format!("时长超出范围: {}", text);
+    let unit_nanos: u128 = match unit {
+        "ns" => 1,
+        "us" => 1_000,
+        "ms" => 1_000_000,
+        "s" => 1_000_000_000,
+        "f" => {
+            if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("帧数必须是非负整数: {}", text));
+            }
+            let count = number.parse::<u64>().map_err(|_| invalid())?;
+            let duration = frames(count);
+            // 与其他单位一致，不超过 u64 纳秒
+            return match u64::try_from(duration.as_nanos()) {
+                Ok(_) => Ok(duration),
+                Err(_) => Err(invalid()),
+            };
+        }
+        _ => return Err(format!("未知的时长单位: {}", unit)),
+    };
+
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let digits_only = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if whole.is_empty() || !digits_only(whole) || !digits_only(fraction) || (number.contains('.') && fraction.is_empty()) {
+        return Err(format!("无效的时长: {}", text));
+    }
+
+    let whole: u128 = whole.parse().map_err(|_| invalid())?;
+    let mut nanos = whole.checked_mul(unit_nanos).ok_or_else(invalid)?;
+    if !fraction.is_empty() {
+        let scale = 10u128.checked_pow(fraction.len() as u32).ok_or_else(invalid)?;
+        let fraction: u128 = fraction.parse().map_err(|_| invalid())?;
+        let scaled = fraction * unit_nanos;
+        if !scaled.is_multiple_of(scale) {
+            return Err(format!("时长精度超过 1 纳秒: {}", text));
+        }
+        nanos += scaled / scale;
+    }
+
+    u64::try_from(nanos)
+        .map(Duration::from_nanos)
+        .map_err(|_| invalid())
+}
+
+/// 在配置文件中以序列脚本文本保存操作序列表
+///
+/// 用于 `#[serde(with = "...")]`。读取时也接受结构化的形式；
+/// 脚本中没有 `@name` 时使用表中的名称
+pub mod script_map {
+    use super::ActionSequence;
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Entry {
+        Script(String),
+        Structured(ActionSequence),
+    }
+
+    pub fn serialize<S>(sequences: &HashMap<String, ActionSequence>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // 按名称排序，保存的配置文件保持稳定
+        let scripts: BTreeMap<&String, String> = sequences.iter()
+            .map(|(name, sequence)| (name, sequence.to_string()))
+            .collect();
+        scripts.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<String, ActionSequence>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let entries = HashMap::<String, Entry>::deserialize(deserializer)?;
+        entries.into_iter()
+            .map(|(name, entry)| {
+                let mut sequence = match entry {
+                    Entry::Script(script) => script.parse::<ActionSequence>()
+                        .map_err(|e| D::Error::custom(format!("序列 {}: {}", name, e)))?,
+                    Entry::Structured(sequence) => sequence,
+                };
+                if sequence.name.is_empty() {
+                    sequence.name = name.clone();
+                }
+                Ok((name, sequence))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 解析后重新输出再解析，结果应一致
+    fn assert_round_trip(sequence: &ActionSequence) {
+        let script = sequence.to_string();
+        let parsed: ActionSequence = script.parse()
+            .unwrap_or_else(|e| panic!("{}\n{}", e, script));
+        assert_eq!(&parsed, sequence, "{}", script);
+    }
+
+    fn syntax_position(script: &str) -> (usize, usize, String) {
+        match script.parse::<ActionSequence>() {
+            Err(ActionError::ScriptSyntax { line, column, message }) => (line, column, message),
+            other => panic!("应为语法错误: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let script = r#"
+            @name deploy   # 名称
+            key 1
+            wait 3f
+            drag (0.9,0.92)->(0.5,0.5) 180ms
+            click right (100,200)
+            repeat 3 {
+                hold "Ctrl+Shift+1" 1.5s
+            }
+            if all(game(InBattle), not(vision("skill ready"))) {
+                call skill
+            } else {
+                wait game 1s
+            }
+        "#;
+        let sequence: ActionSequence = script.parse().unwrap();
+
+        assert_eq!(sequence.name, "deploy");
+        assert_eq!(sequence.actions, vec![
+            ActionType::KeyPress("1".to_string()),
+            ActionType::Wait(Duration::from_millis(100)),
+            ActionType::MouseDragAt {
+                button: MouseButton::Left,
+                from: Position::normalized(0.9, 0.92),
+                to: Position::normalized(0.5, 0.5),
+                duration: Duration::from_millis(180),
+                steps: DEFAULT_DRAG_STEPS,
+                easing: Easing::Linear,
+            },
+            ActionType::MouseClick(MouseButton::Right, 100, 200),
+            ActionType::Repeat {
+                count: 3,
+                body: vec![ActionType::KeyHold("Ctrl+Shift+1".to_string(), Duration::from_millis(1500))],
+            },
+            ActionType::If {
+                condition: Condition::All(vec![
+                    Condition::GameState(GameState::InBattle),
+                    Condition::Not(Box::new(Condition::Vision("skill ready".to_string()))),
+                ]),
+                then: vec![ActionType::Call("skill".to_string())],
+                otherwise: vec![ActionType::GameWait(Duration::from_secs(1))],
+            },
+        ]);
+        assert_round_trip(&sequence);
+    }
+
+    #[test]
+    fn test_round_trip_all_actions() {
+        let mut sequence = ActionSequence::new("全部 操作".to_string());
+        sequence.description = Some("第一行\n\"第二行\"".to_string());
+        sequence.humanize = HumanizeConfig {
+            enabled: true,
+            wait_jitter: Duration::from_millis(20),
+            position_jitter: 3,
+            drag_jitter: 0,
+            seed: Some(42),
+        };
+        sequence.actions = vec![
+            ActionType::KeyPress(",".to_string()),
+            ActionType::KeyDown("Shift".to_string()),
+            ActionType::KeyUp("Shift".to_string()),
+            ActionType::KeyHold("-".to_string(), frames(1)),
+            ActionType::MouseMove(-10, 20),
+            ActionType::MouseMoveRelative(5, -5),
+            ActionType::MouseMoveTo(Position::window_client(10, 20)),
+            ActionType::MouseMoveTo(Position::screen(1, 2)),
+            ActionType::MouseClick(MouseButton::Left, 0, 0),
+            ActionType::MouseClickAt(MouseButton::Middle, Position::normalized(1.0, 0.0)),
+            ActionType::Scroll { dx: 0, dy: -3 },
+            ActionType::MouseDrag {
+                button: MouseButton::Right,
+                from: (0, 0),
+                to: (30, 40),
+                duration: Duration::from_nanos(1),
+                steps: 3,
+                easing: Easing::EaseInOut,
+            },
+            ActionType::MouseDragAt {
+                button: MouseButton::Left,
+                from: Position::screen(0, 0),
+                to: Position::normalized(0.25, 1e-7),
+                duration: Duration::ZERO,
+                steps: DEFAULT_DRAG_STEPS,
+                easing: Easing::EaseOut,
+            },
+            ActionType::Wait(Duration::from_micros(1500)),
+            ActionType::GameWait(frames(7)),
+            ActionType::Loop {
+                until: Condition::Any(vec![
+                    Condition::ProgramState(ProgramState::Stopping),
+                    Condition::All(Vec::new()),
+                ]),
+                max_iterations: 100,
+                body: vec![ActionType::Repeat { count: 0, body: Vec::new() }],
+            },
+            ActionType::If {
+                condition: Condition::Vision("#\\".to_string()),
+                then: Vec::new(),
+                otherwise: Vec::new(),
+            },
+            ActionType::Call("".to_string()),
+        ];
+        assert_round_trip(&sequence);
+
+        // 参数
+        let sequence = ActionSequence::new("deploy".to_string())
+            .with_param("slot", 3)
+            .with_param("offset", -1);
+        assert_eq!(sequence.to_string(), "@name deploy\n@param offset -1\n@param slot 3\n");
+        assert_round_trip(&sequence);
+
+        // 无名称、无操作
+        assert_round_trip(&ActionSequence::new(String::new()));
+        assert_eq!(ActionSequence::new(String::new()).to_string(), "");
+    }
+
+    #[test]
+    fn test_format_script() {
+        let mut sequence = ActionSequence::new("skill".to_string());
+        sequence.add_key_press("Space".to_string());
+        sequence.add_repeat(2, vec![ActionType::Wait(frames(1))]);
+        sequence.add_mouse_drag_at(
+            MouseButton::Left,
+            Position::normalized(0.9, 0.92),
+            Position::window_client(640, 360),
+            Duration::from_millis(180),
+            20,
+            Easing::EaseIn,
+        );
+
+        assert_eq!(sequence.to_string(), "\
+@name skill
+
+key Space
+repeat 2 {
+    wait 1f
+}
+drag (0.9,0.92)->client(640,360) 180ms steps 20 ease-in
+");
+    }
+
+    #[test]
+    fn test_durations() {
+        assert_eq!(parse_duration("3f"), Ok(Duration::from_millis(100)));
+        assert_eq!(parse_duration("1f"), Ok(Duration::from_nanos(33_333_333)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("0.25ms"), Ok(Duration::from_micros(250)));
+        assert!(parse_duration("100").is_err());
+        assert!(parse_duration("1.5f").is_err());
+        assert!(parse_duration("1.ms").is_err());
+        assert!(parse_duration("0.0000000001s").is_err());
+        assert!(parse_duration("5min").is_err());
+        // 大帧数不溢出，超出 u64 纳秒时报错
+        assert_eq!(frames(u64::MAX).as_secs(), u64::MAX / GAME_FRAME_RATE);
+        assert_eq!(parse_duration("553402322211f"), Ok(frames(553_402_322_211)));
+        assert_eq!(parse_duration("553402322212f"), Err("时长超出范围: 553402322212f".to_string()));
+        assert_eq!(parse_duration("18446744073709551616f"), Err("时长超出范围: 18446744073709551616f".to_string()));
+
+        assert_eq!(format_duration(Duration::ZERO), "0ms");
+        assert_eq!(format_duration(Duration::from_secs(2)), "2s");
+        assert_eq!(format_duration(frames(2)), "2f");
+        assert_eq!(format_duration(frames(3)), "100ms");
+        assert_eq!(format_duration(Duration::from_nanos(7)), "7ns");
+        assert_eq!(parse_duration(&format_duration(frames(553_402_322_211))), Ok(frames(553_402_322_211)));
+    }
+
+    #[test]
+    fn test_error_positions() {
+        assert_eq!(syntax_position("key 1\n  jump 3"), (2, 3, "未知的操作: jump".to_string()));
+        assert_eq!(syntax_position("wait 100"), (1, 6, "时长缺少单位: 100".to_string()));
+        assert_eq!(syntax_position("wait 99999999999999999999f"), (1, 6, "时长超出范围: 99999999999999999999f".to_string()));
+        assert_eq!(syntax_position("key"), (1, 4, "缺少按键".to_string()));
+        assert_eq!(syntax_position("key 1 2"), (1, 7, "多余的内容".to_string()));
+        assert_eq!(syntax_position("click (1,x)"), (1, 10, "无效的坐标: x".to_string()));
+        assert_eq!(syntax_position("drag (0,0) (1,1) 1s"), (1, 12, "应为 ->".to_string()));
+        assert_eq!(syntax_position("\nrepeat 2 {\n  key 1\n"), (2, 1, "块缺少对应的 }".to_string()));
+        assert_eq!(syntax_position("key 1\n}"), (2, 1, "多余的 }".to_string()));
+        assert_eq!(syntax_position("repeat 2 {\n} else {\n}"), (2, 1, "只有 if 块可以有 else".to_string()));
+        assert_eq!(syntax_position("if game(Paused) {\n}"), (1, 9, "未知的游戏状态: Paused".to_string()));
+        assert_eq!(syntax_position("key \"Ctrl"), (1, 5, "引号未闭合".to_string()));
+        assert_eq!(syntax_position("key 1\n@name x"), (2, 1, "未知的操作: @name".to_string()));
+        assert_eq!(syntax_position("@name a\n@name b"), (2, 1, "重复的指令: @name".to_string()));
+        assert_eq!(syntax_position("@param slot 1\n@param slot 2"), (2, 8, "重复的参数: slot".to_string()));
+        assert_eq!(syntax_position("@param $slot 1"), (1, 8, "无效的参数名称: $slot".to_string()));
+        assert_eq!(syntax_position("@param slot x"), (1, 13, "无效的参数值: x".to_string()));
+
+        let error = "wait 1x".parse::<ActionSequence>().unwrap_err();
+        assert_eq!(error.to_string(), "序列脚本第 1 行第 6 列: 未知的时长单位: x");
+    }
+}
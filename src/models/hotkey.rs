@@ -0,0 +1,194 @@
+//! 热键数据模型
+//!
+//! 热键由组合键和触发方式组成，配置中写作 `触发方式:组合键`，如 `hold:1`、`double:Ctrl+Q`；
+//! 不写触发方式时为按下即触发。不同触发方式的热键可以共用同一个按键
+
+use super::key::KeyCombo;
+use crate::utils::{ActionError, ActionResult};
+use std::fmt;
+use std::str::FromStr;
+
+/// 触发方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TriggerKind {
+    /// 按下时触发
+    #[default]
+    Press,
+    /// 短按后释放时触发（同一按键绑定了双击时，在双击间隔过后触发）
+    Tap,
+    /// 按住超过长按时间时触发
+    Hold,
+    /// 在双击间隔内再次按下时触发
+    DoubleTap,
+    /// 释放时触发
+    Release,
+}
+
+impl TriggerKind {
+    /// 所有触发方式
+    pub fn all() -> &'static [TriggerKind] {
+        &[TriggerKind::Press, TriggerKind::Tap, TriggerKind::Hold, TriggerKind::DoubleTap, TriggerKind::Release]
+    }
+
+    /// 配置中使用的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            TriggerKind::Press => "press",
+            TriggerKind::Tap => "tap",
+            TriggerKind::Hold => "hold",
+            TriggerKind::DoubleTap => "double",
+            TriggerKind::Release => "release",
+        }
+    }
+}
+
+impl fmt::Display for TriggerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for TriggerKind {
+    type Err = ActionError;
+
+    /// 解析触发方式（不区分大小写）
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        match name.as_str() {
+            "doubletap" => Ok(TriggerKind::DoubleTap),
+            _ => TriggerKind::all().iter()
+                .copied()
+                .find(|kind| kind.name() == name)
+                .ok_or_else(|| ActionError::InvalidParameter(format!("未知的触发方式: {}", s.trim()))),
+        }
+    }
+}
+
+/// 热键（组合键 + 触发方式）
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    combo: KeyCombo,
+    trigger: TriggerKind,
+}
+
+impl Hotkey {
+    /// 创建热键
+    pub fn new(combo: KeyCombo, trigger: TriggerKind) -> Self {
+        Self { combo, trigger }
+    }
+
+    /// 按下即触发的热键
+    pub fn press(combo: KeyCombo) -> Self {
+        Self::new(combo, TriggerKind::Press)
+    }
+
+    /// 组合键
+    pub fn combo(&self) -> &KeyCombo {
+        &self.combo
+    }
+
+    /// 触发方式
+    pub fn trigger(&self) -> TriggerKind {
+        self.trigger
+    }
+
+    /// 左右修饰键转换为通用修饰键后的热键
+    pub fn generic(&self) -> Hotkey {
+        Self::new(self.combo.generic(), self.trigger)
+    }
+
+    /// 判断两个热键是否匹配：触发方式相同且组合键匹配
+    pub fn matches(&self, other: &Hotkey) -> bool {
+        self.trigger == other.trigger && self.combo.matches(&other.combo)
+    }
+
+    /// 解析逗号分隔的热键列表，如 `1,hold:1,Ctrl+T`
+    pub fn parse_list(spec: &str) -> ActionResult<Vec<Hotkey>> {
+        spec.split(',')
+            .map(|entry| {
+                if entry.trim().is_empty() {
+                    return Err(ActionError::InvalidKey(spec.to_string()));
+                }
+                entry.parse()
+            })
+            .collect()
+    }
+
+    /// 将热键列表格式化为配置字符串
+    pub fn format_list(hotkeys: &[Hotkey]) -> String {
+        hotkeys.iter()
+            .map(|hotkey| hotkey.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl From<KeyCombo> for Hotkey {
+    fn from(combo: KeyCombo) -> Self {
+        Self::press(combo)
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.trigger != TriggerKind::Press {
+            write!(f, "{}:", self.trigger)?;
+        }
+        write!(f, "{}", self.combo)
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = ActionError;
+
+    /// 解析热键，`触发方式:` 前缀可以省略
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((trigger, combo)) => Ok(Self::new(combo.parse()?, trigger.parse()?)),
+            None => s.parse().map(Self::press),
+        }
+    }
+}
+
+/// 验证热键配置字符串
+///
+/// 配置是逗号分隔的多个热键，每项可以带触发方式前缀，如 `1,hold:1,double:Ctrl+T`
+pub fn validate_hotkey_spec(spec: &str) -> ActionResult<()> {
+    Hotkey::parse_list(spec).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Key;
+
+    #[test]
+    fn test_parse_and_format_hotkeys() {
+        let hotkeys = Hotkey::parse_list("1,hold:1, Double:shift+ctrl+t,release:Space,press:F1").unwrap();
+        assert_eq!(hotkeys.iter().map(Hotkey::trigger).collect::<Vec<_>>(), vec![
+            TriggerKind::Press,
+            TriggerKind::Hold,
+            TriggerKind::DoubleTap,
+            TriggerKind::Release,
+            TriggerKind::Press,
+        ]);
+        assert_eq!(Hotkey::format_list(&hotkeys), "1,hold:1,double:Ctrl+Shift+T,release:Space,F1");
+        assert_eq!("doubletap:Q".parse::<Hotkey>().unwrap().to_string(), "double:Q");
+
+        assert!(validate_hotkey_spec("tap:1,hold:=").is_ok());
+        assert!(validate_hotkey_spec("long:1").is_err());
+        assert!(validate_hotkey_spec("hold:").is_err());
+        assert!(validate_hotkey_spec("hold:1,").is_err());
+
+        // 逗号键以名称保存，可以放入热键列表
+        let hotkeys = vec![Hotkey::press(Key::Comma.into()), Hotkey::new("Ctrl+,".parse().unwrap(), TriggerKind::Tap)];
+        let spec = Hotkey::format_list(&hotkeys);
+        assert_eq!(spec, "Comma,tap:Ctrl+Comma");
+        assert_eq!(Hotkey::parse_list(&spec).unwrap(), hotkeys);
+
+        let hold: Hotkey = "hold:LeftCtrl+1".parse().unwrap();
+        assert!(hold.matches(&"hold:Ctrl+1".parse().unwrap()));
+        assert!(!hold.matches(&"tap:Ctrl+1".parse().unwrap()));
+        assert_eq!(hold.generic().to_string(), "hold:Ctrl+1");
+    }
+}
@@ -0,0 +1,531 @@
+//! 按键数据模型
+//!
+//! 定义所有可配置的按键名称及其对应的 Windows 虚拟键码
+
+use crate::utils::{ActionError, ActionResult};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 定义按键表：变体、虚拟键码、显示名称、别名（小写）
+macro_rules! define_keys {
+    ($($variant:ident => $vk:expr, $display:expr, [$($alias:expr),*];)*) => {
+        /// 按键
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum Key {
+            $($variant,)*
+        }
+
+        impl Key {
+            /// 所有按键
+            const ALL: &'static [Key] = &[$(Key::$variant,)*];
+
+            /// 获取 Windows 虚拟键码
+            pub fn vk_code(self) -> u16 {
+                match self {
+                    $(Key::$variant => $vk,)*
+                }
+            }
+
+            /// 获取显示名称
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Key::$variant => $display,)*
+                }
+            }
+
+            /// 获取别名（小写）
+            fn aliases(self) -> &'static [&'static str] {
+                match self {
+                    $(Key::$variant => &[$($alias),*],)*
+                }
+            }
+        }
+    };
+}
+
+define_keys! {
+    A => 0x41, "A", [];
+    B => 0x42, "B", [];
+    C => 0x43, "C", [];
+    D => 0x44, "D", [];
+    E => 0x45, "E", [];
+    F => 0x46, "F", [];
+    G => 0x47, "G", [];
+    H => 0x48, "H", [];
+    I => 0x49, "I", [];
+    J => 0x4A, "J", [];
+    K => 0x4B, "K", [];
+    L => 0x4C, "L", [];
+    M => 0x4D, "M", [];
+    N => 0x4E, "N", [];
+    O => 0x4F, "O", [];
+    P => 0x50, "P", [];
+    Q => 0x51, "Q", [];
+    R => 0x52, "R", [];
+    S => 0x53, "S", [];
+    T => 0x54, "T", [];
+    U => 0x55, "U", [];
+    V => 0x56, "V", [];
+    W => 0x57, "W", [];
+    X => 0x58, "X", [];
+    Y => 0x59, "Y", [];
+    Z => 0x5A, "Z", [];
+
+    Digit0 => 0x30, "0", ["digit0"];
+    Digit1 => 0x31, "1", ["digit1"];
+    Digit2 => 0x32, "2", ["digit2"];
+    Digit3 => 0x33, "3", ["digit3"];
+    Digit4 => 0x34, "4", ["digit4"];
+    Digit5 => 0x35, "5", ["digit5"];
+    Digit6 => 0x36, "6", ["digit6"];
+    Digit7 => 0x37, "7", ["digit7"];
+    Digit8 => 0x38, "8", ["digit8"];
+    Digit9 => 0x39, "9", ["digit9"];
+
+    F1 => 0x70, "F1", [];
+    F2 => 0x71, "F2", [];
+    F3 => 0x72, "F3", [];
+    F4 => 0x73, "F4", [];
+    F5 => 0x74, "F5", [];
+    F6 => 0x75, "F6", [];
+    F7 => 0x76, "F7", [];
+    F8 => 0x77, "F8", [];
+    F9 => 0x78, "F9", [];
+    F10 => 0x79, "F10", [];
+    F11 => 0x7A, "F11", [];
+    F12 => 0x7B, "F12", [];
+    F13 => 0x7C, "F13", [];
+    F14 => 0x7D, "F14", [];
+    F15 => 0x7E, "F15", [];
+    F16 => 0x7F, "F16", [];
+    F17 => 0x80, "F17", [];
+    F18 => 0x81, "F18", [];
+    F19 => 0x82, "F19", [];
+    F20 => 0x83, "F20", [];
+    F21 => 0x84, "F21", [];
+    F22 => 0x85, "F22", [];
+    F23 => 0x86, "F23", [];
+    F24 => 0x87, "F24", [];
+
+    Up => 0x26, "Up", ["arrowup", "uparrow"];
+    Down => 0x28, "Down", ["arrowdown", "downarrow"];
+    Left => 0x25, "Left", ["arrowleft", "leftarrow"];
+    Right => 0x27, "Right", ["arrowright", "rightarrow"];
+
+    Numpad0 => 0x60, "Numpad0", ["num0", "kp0"];
+    Numpad1 => 0x61, "Numpad1", ["num1", "kp1"];
+    Numpad2 => 0x62, "Numpad2", ["num2", "kp2"];
+    Numpad3 => 0x63, "Numpad3", ["num3", "kp3"];
+    Numpad4 => 0x64, "Numpad4", ["num4", "kp4"];
+    Numpad5 => 0x65, "Numpad5", ["num5", "kp5"];
+    Numpad6 => 0x66, "Numpad6", ["num6", "kp6"];
+    Numpad7 => 0x67, "Numpad7", ["num7", "kp7"];
+    Numpad8 => 0x68, "Numpad8", ["num8", "kp8"];
+    Numpad9 => 0x69, "Numpad9", ["num9", "kp9"];
+    NumpadMultiply => 0x6A, "NumpadMultiply", ["nummultiply", "multiply", "*"];
+    NumpadAdd => 0x6B, "NumpadAdd", ["numadd", "add", "numpadplus"];
+    NumpadSubtract => 0x6D, "NumpadSubtract", ["numsubtract", "subtract", "numpadminus"];
+    NumpadDecimal => 0x6E, "NumpadDecimal", ["numdecimal", "decimal"];
+    NumpadDivide => 0x6F, "NumpadDivide", ["numdivide", "divide"];
+
+    Minus => 0xBD, "-", ["minus", "dash", "hyphen"];
+    Equals => 0xBB, "=", ["equals", "equal"];
+    // 按键列表以逗号分隔，逗号键显示为名称
+    Comma => 0xBC, "Comma", [","];
+    Period => 0xBE, ".", ["period", "dot"];
+    Slash => 0xBF, "/", ["slash"];
+    Semicolon => 0xBA, ";", ["semicolon"];
+    Quote => 0xDE, "'", ["quote", "apostrophe"];
+    Backquote => 0xC0, "`", ["backquote", "grave", "backtick", "tilde"];
+    LeftBracket => 0xDB, "[", ["leftbracket", "bracketleft"];
+    RightBracket => 0xDD, "]", ["rightbracket", "bracketright"];
+    Backslash => 0xDC, "\\", ["backslash"];
+
+    Shift => 0x10, "Shift", [];
+    Ctrl => 0x11, "Ctrl", ["control", "ctl"];
+    Alt => 0x12, "Alt", ["menu"];
+    Win => 0x5B, "Win", ["windows", "super", "meta", "cmd", "lwin", "leftwin"];
+    LeftShift => 0xA0, "LeftShift", ["lshift", "shiftleft"];
+    RightShift => 0xA1, "RightShift", ["rshift", "shiftright"];
+    LeftCtrl => 0xA2, "LeftCtrl", ["lctrl", "leftcontrol", "controlleft"];
+    RightCtrl => 0xA3, "RightCtrl", ["rctrl", "rightcontrol", "controlright"];
+    LeftAlt => 0xA4, "LeftAlt", ["lalt", "altleft"];
+    RightAlt => 0xA5, "RightAlt", ["ralt", "altright", "altgr"];
+    RightWin => 0x5C, "RightWin", ["rwin"];
+
+    Space => 0x20, "Space", ["spacebar", " "];
+    Tab => 0x09, "Tab", [];
+    Enter => 0x0D, "Enter", ["return"];
+    Backspace => 0x08, "Backspace", ["back", "bksp"];
+    Escape => 0x1B, "Escape", ["esc"];
+    Delete => 0x2E, "Delete", ["del"];
+    Insert => 0x2D, "Insert", ["ins"];
+    Home => 0x24, "Home", [];
+    End => 0x23, "End", [];
+    PageUp => 0x21, "PageUp", ["pgup", "prior"];
+    PageDown => 0x22, "PageDown", ["pgdn", "next"];
+    CapsLock => 0x14, "CapsLock", ["caps"];
+    NumLock => 0x90, "NumLock", [];
+    ScrollLock => 0x91, "ScrollLock", [];
+    PrintScreen => 0x2C, "PrintScreen", ["prtsc", "printscr", "print"];
+    Pause => 0x13, "Pause", ["break"];
+    ContextMenu => 0x5D, "ContextMenu", ["apps"];
+
+    // 鼠标侧键，只用于热键触发
+    Mouse4 => 0x05, "Mouse4", ["xbutton1", "mousex1"];
+    Mouse5 => 0x06, "Mouse5", ["xbutton2", "mousex2"];
+}
+
+impl Key {
+    /// 获取所有按键
+    pub fn all() -> &'static [Key] {
+        Self::ALL
+    }
+
+    /// 根据虚拟键码查找按键
+    pub fn from_vk_code(vk_code: u16) -> Option<Key> {
+        Self::ALL.iter().copied().find(|key| key.vk_code() == vk_code)
+    }
+
+    /// 将左右修饰键转换为通用修饰键，其他按键保持不变
+    pub fn generic(self) -> Key {
+        match self {
+            Key::LeftShift | Key::RightShift => Key::Shift,
+            Key::LeftCtrl | Key::RightCtrl => Key::Ctrl,
+            Key::LeftAlt | Key::RightAlt => Key::Alt,
+            Key::RightWin => Key::Win,
+            other => other,
+        }
+    }
+
+    /// 是否为修饰键
+    pub fn is_modifier(self) -> bool {
+        matches!(
+            self,
+            Key::Shift | Key::Ctrl | Key::Alt | Key::Win
+                | Key::LeftShift | Key::RightShift
+                | Key::LeftCtrl | Key::RightCtrl
+                | Key::LeftAlt | Key::RightAlt
+                | Key::RightWin
+        )
+    }
+
+    /// 是否只能用于热键触发（鼠标侧键不能作为按键发送）
+    pub fn is_trigger_only(self) -> bool {
+        matches!(self, Key::Mouse4 | Key::Mouse5)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Key {
+    type Err = ActionError;
+
+    /// 解析按键名称（不区分大小写，支持别名）
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // 单独的空格也是合法的按键名称
+        let name = if s.trim().is_empty() { s } else { s.trim() };
+        let lower = name.to_lowercase();
+
+        Self::ALL.iter()
+            .copied()
+            .find(|key| key.name().to_lowercase() == lower || key.aliases().contains(&lower.as_str()))
+            .ok_or_else(|| ActionError::InvalidKey(name.to_string()))
+    }
+}
+
+/// 组合键（修饰键 + 按键）
+///
+/// 修饰键按 Ctrl、Alt、Shift、Win 的顺序保存，显示为 `Ctrl+Shift+T`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyCombo {
+    /// 修饰键（已排序、去重）
+    modifiers: Vec<Key>,
+    /// 主按键
+    key: Key,
+}
+
+impl KeyCombo {
+    /// 创建组合键，修饰键会被规范化
+    pub fn new(modifiers: impl IntoIterator<Item = Key>, key: Key) -> Self {
+        let mut modifiers: Vec<Key> = modifiers.into_iter()
+            .filter(|modifier| *modifier != key)
+            .collect();
+        modifiers.sort_by_key(|modifier| (modifier_rank(*modifier), *modifier));
+        modifiers.dedup();
+
+        Self { modifiers, key }
+    }
+
+    /// 创建不带修饰键的单个按键
+    pub fn single(key: Key) -> Self {
+        Self { modifiers: Vec::new(), key }
+    }
+
+    /// 获取修饰键
+    pub fn modifiers(&self) -> &[Key] {
+        &self.modifiers
+    }
+
+    /// 获取主按键
+    pub fn key(&self) -> Key {
+        self.key
+    }
+
+    /// 将左右修饰键转换为通用修饰键，如 `LeftCtrl+T` 转换为 `Ctrl+T`
+    pub fn generic(&self) -> KeyCombo {
+        Self::new(self.modifiers.iter().map(|modifier| modifier.generic()), self.key.generic())
+    }
+
+    /// 判断两个组合键是否匹配
+    ///
+    /// 左右修饰键视为同一个键，如 `LeftCtrl+T` 匹配 `Ctrl+T`
+    pub fn matches(&self, other: &KeyCombo) -> bool {
+        if self.key.generic() != other.key.generic() {
+            return false;
+        }
+
+        let mut ours: Vec<Key> = self.modifiers.iter().map(|key| key.generic()).collect();
+        let mut theirs: Vec<Key> = other.modifiers.iter().map(|key| key.generic()).collect();
+        ours.dedup();
+        theirs.dedup();
+        ours == theirs
+    }
+
+    /// 解析逗号分隔的组合键列表，如 `1,2,Ctrl+T`
+    pub fn parse_list(spec: &str) -> ActionResult<Vec<KeyCombo>> {
+        spec.split(',')
+            .map(|entry| {
+                if entry.trim().is_empty() {
+                    return Err(ActionError::InvalidKey(spec.to_string()));
+                }
+                entry.parse()
+            })
+            .collect()
+    }
+
+    /// 将组合键列表格式化为配置字符串
+    pub fn format_list(combos: &[KeyCombo]) -> String {
+        combos.iter()
+            .map(|combo| combo.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl From<Key> for KeyCombo {
+    fn from(key: Key) -> Self {
+        Self::single(key)
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{}+", modifier)?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = ActionError;
+
+    /// 解析组合键，修饰键可以出现在任意位置，但最多只能有一个普通按键
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // 整体是一个按键名称（如单独的空格）
+        if let Ok(key) = s.parse::<Key>() {
+            return Ok(Self::single(key));
+        }
+
+        let keys = s.split('+')
+            .map(|part| part.parse::<Key>())
+            .collect::<ActionResult<Vec<Key>>>()
+            .map_err(|_| ActionError::InvalidKey(s.trim().to_string()))?;
+
+        let mut regular = keys.iter().copied().filter(|key| !key.is_modifier());
+        let key = match (regular.next(), regular.next()) {
+            (Some(key), None) => key,
+            (None, _) => *keys.last().ok_or_else(|| ActionError::InvalidKey(s.to_string()))?,
+            (Some(_), Some(_)) => return Err(ActionError::InvalidKey(s.trim().to_string())),
+        };
+
+        Ok(Self::new(keys.into_iter().filter(|modifier| modifier.is_modifier()), key))
+    }
+}
+
+impl TryFrom<String> for KeyCombo {
+    type Error = ActionError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<KeyCombo> for String {
+    fn from(combo: KeyCombo) -> Self {
+        combo.to_string()
+    }
+}
+
+/// 修饰键排序：Ctrl、Alt、Shift、Win
+fn modifier_rank(key: Key) -> u8 {
+    match key.generic() {
+        Key::Ctrl => 0,
+        Key::Alt => 1,
+        Key::Shift => 2,
+        Key::Win => 3,
+        _ => 4,
+    }
+}
+
+/// 验证按键配置字符串
+///
+/// 配置可以是逗号分隔的多个按键，每项可以用 `+` 连接成组合键，如 `1,2,Ctrl+T`。
+/// 配置的按键需要能够发送，只能用于热键触发的按键无效
+pub fn validate_key_spec(spec: &str) -> ActionResult<()> {
+    for combo in KeyCombo::parse_list(spec)? {
+        if combo.key().is_trigger_only() {
+            return Err(ActionError::InvalidKey(format!("{} 只能用于热键触发", combo)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_case_insensitive_and_aliases() {
+        assert_eq!("q".parse::<Key>().unwrap(), Key::Q);
+        assert_eq!("Q".parse::<Key>().unwrap(), Key::Q);
+        assert_eq!("esc".parse::<Key>().unwrap(), Key::Escape);
+        assert_eq!("ESCAPE".parse::<Key>().unwrap(), Key::Escape);
+        assert_eq!("Del".parse::<Key>().unwrap(), Key::Delete);
+        assert_eq!("f12".parse::<Key>().unwrap(), Key::F12);
+        assert_eq!("F24".parse::<Key>().unwrap(), Key::F24);
+        assert_eq!("-".parse::<Key>().unwrap(), Key::Minus);
+        assert_eq!("=".parse::<Key>().unwrap(), Key::Equals);
+        assert_eq!("control".parse::<Key>().unwrap(), Key::Ctrl);
+        assert_eq!(" Space ".parse::<Key>().unwrap(), Key::Space);
+        assert_eq!("kp5".parse::<Key>().unwrap(), Key::Numpad5);
+        assert!(matches!("NoSuchKey".parse::<Key>(), Err(ActionError::InvalidKey(_))));
+        assert!("".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for key in Key::all() {
+            let name = key.to_string();
+            assert_eq!(name.parse::<Key>().unwrap(), *key, "按键 {} 无法往返解析", name);
+        }
+    }
+
+    #[test]
+    fn test_names_and_aliases_are_unique() {
+        let mut seen = std::collections::HashSet::new();
+        for key in Key::all() {
+            assert!(seen.insert(key.name().to_lowercase()), "重复的名称: {}", key.name());
+            for alias in key.aliases() {
+                assert_eq!(*alias, alias.to_lowercase());
+                assert!(seen.insert(alias.to_string()), "重复的别名: {}", alias);
+            }
+        }
+    }
+
+    #[test]
+    fn test_vk_codes() {
+        assert_eq!(Key::Space.vk_code(), 0x20);
+        assert_eq!(Key::Digit0.vk_code(), 0x30);
+        assert_eq!(Key::A.vk_code(), 0x41);
+        assert_eq!(Key::F13.vk_code(), 0x7C);
+        assert_eq!(Key::Equals.vk_code(), 0xBB);
+        assert_eq!(Key::from_vk_code(0x1B), Some(Key::Escape));
+        assert_eq!(Key::from_vk_code(0xFF), None);
+    }
+
+    #[test]
+    fn test_ui_default_key_lists_are_valid() {
+        assert!(validate_key_spec("1,2,3,4,5,6,7,8,9,0,-,=").is_ok());
+        assert!(validate_key_spec("Q,W,E,R,T,Y,U,I,O,P").is_ok());
+        assert!(validate_key_spec("A,S,D,F,G,H,J,K,L").is_ok());
+        assert!(validate_key_spec("Ctrl+T").is_ok());
+        assert!(validate_key_spec("1,,2").is_err());
+        assert!(validate_key_spec("1,Foo").is_err());
+        assert!(validate_key_spec("Ctrl+").is_err());
+        assert!(validate_key_spec("1,Mouse4").is_err());
+        assert!(validate_key_spec("Ctrl+XButton2").is_err());
+    }
+
+    #[test]
+    fn test_comma_key_list_round_trip() {
+        let combos = vec![KeyCombo::single(Key::Comma), KeyCombo::new([Key::Ctrl], Key::Comma)];
+        let spec = KeyCombo::format_list(&combos);
+        assert_eq!(spec, "Comma,Ctrl+Comma");
+        assert_eq!(KeyCombo::parse_list(&spec).unwrap(), combos);
+        assert!(validate_key_spec(&spec).is_ok());
+        assert_eq!(",".parse::<Key>().unwrap(), Key::Comma);
+    }
+
+    #[test]
+    fn test_key_combo_parse_and_normalize() {
+        let combo: KeyCombo = "shift+ctrl+t".parse().unwrap();
+        assert_eq!(combo.modifiers(), &[Key::Ctrl, Key::Shift]);
+        assert_eq!(combo.key(), Key::T);
+        assert_eq!(combo.to_string(), "Ctrl+Shift+T");
+
+        // 修饰键位置和重复不影响结果
+        assert_eq!("T+Ctrl".parse::<KeyCombo>().unwrap().to_string(), "Ctrl+T");
+        assert_eq!("Ctrl+Ctrl+T".parse::<KeyCombo>().unwrap().to_string(), "Ctrl+T");
+        assert_eq!("Win+Alt+Esc".parse::<KeyCombo>().unwrap().to_string(), "Alt+Win+Escape");
+
+        // 单独的修饰键作为主按键
+        let combo: KeyCombo = "Ctrl+Shift".parse().unwrap();
+        assert_eq!(combo.key(), Key::Shift);
+        assert_eq!(combo.to_string(), "Ctrl+Shift");
+
+        assert_eq!("Space".parse::<KeyCombo>().unwrap(), KeyCombo::single(Key::Space));
+        assert!("Ctrl+A+B".parse::<KeyCombo>().is_err());
+        assert!("Ctrl+".parse::<KeyCombo>().is_err());
+        assert!("Ctrl+Foo".parse::<KeyCombo>().is_err());
+    }
+
+    #[test]
+    fn test_key_combo_round_trip_and_serde() {
+        for spec in ["Ctrl+T", "Ctrl+Alt+Shift+Win+F5", "-", "LeftCtrl+=", "Space"] {
+            let combo: KeyCombo = spec.parse().unwrap();
+            assert_eq!(combo.to_string().parse::<KeyCombo>().unwrap(), combo);
+        }
+
+        let combo: KeyCombo = "alt+f4".parse().unwrap();
+        let json = serde_json::to_string(&combo).unwrap();
+        assert_eq!(json, "\"Alt+F4\"");
+        assert_eq!(serde_json::from_str::<KeyCombo>(&json).unwrap(), combo);
+        assert!(serde_json::from_str::<KeyCombo>("\"Alt+Nope\"").is_err());
+    }
+
+    #[test]
+    fn test_key_combo_matching() {
+        let hotkey: KeyCombo = "Ctrl+T".parse().unwrap();
+        assert!(hotkey.matches(&"LeftCtrl+T".parse().unwrap()));
+        assert!(hotkey.matches(&"RightCtrl+t".parse().unwrap()));
+        assert!(!hotkey.matches(&"T".parse().unwrap()));
+        assert!(!hotkey.matches(&"Ctrl+Shift+T".parse().unwrap()));
+        assert!(!hotkey.matches(&"Ctrl+Y".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_key_combo_list() {
+        let combos = KeyCombo::parse_list("1, ctrl+2 ,Shift+=").unwrap();
+        assert_eq!(combos.len(), 3);
+        assert_eq!(KeyCombo::format_list(&combos), "1,Ctrl+2,Shift+=");
+        assert!(KeyCombo::parse_list("").is_err());
+    }
+}
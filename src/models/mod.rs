@@ -3,11 +3,19 @@
 //! 包含应用程序的所有数据结构和类型定义
 
 pub mod config;
+pub mod conflict;
+pub mod dsl;
+pub mod hotkey;
+pub mod key;
 pub mod operation;
 pub mod window;
 pub mod state;
 
 pub use config::*;
+pub use conflict::*;
+pub use dsl::*;
+pub use hotkey::*;
+pub use key::*;
 pub use operation::*;
 pub use window::*;
 pub use state::*;
\ No newline at end of file
@@ -1,23 +1,273 @@
 //! 操作相关数据模型
 
+use super::key::KeyCombo;
+use super::state::{GameState, ProgramState};
+use super::window::WindowInfo;
+use crate::utils::{ActionError, ActionResult};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 /// 操作类型
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ActionType {
-    /// 按键操作
+    /// 按键操作（按下并释放）
     KeyPress(String),
+    /// 按下按键，直到 KeyUp 才释放
+    KeyDown(String),
+    /// 释放按键
+    KeyUp(String),
+    /// 按住按键指定时间后释放
+    KeyHold(String, Duration),
     /// 鼠标移动
     MouseMove(i32, i32),
+    /// 鼠标相对移动 (dx, dy)
+    MouseMoveRelative(i32, i32),
     /// 鼠标点击
     MouseClick(MouseButton, i32, i32),
-    /// 等待
+    /// 鼠标移动到指定坐标系中的位置
+    MouseMoveTo(Position),
+    /// 在指定坐标系中的位置点击
+    MouseClickAt(MouseButton, Position),
+    /// 鼠标滚轮（单位为格，正值向上/向右）
+    Scroll {
+        /// 水平滚动格数
+        dx: i32,
+        /// 垂直滚动格数
+        dy: i32,
+    },
+    /// 鼠标拖拽：按下、沿插值路径移动、释放
+    MouseDrag {
+        /// 鼠标按钮
+        button: MouseButton,
+        /// 起点
+        from: (i32, i32),
+        /// 终点
+        to: (i32, i32),
+        /// 拖拽总时长
+        duration: Duration,
+        /// 插值步数（不含起点）
+        steps: u32,
+        /// 缓动曲线
+        easing: Easing,
+    },
+    /// 在指定坐标系中拖拽
+    MouseDragAt {
+        /// 鼠标按钮
+        button: MouseButton,
+        /// 起点
+        from: Position,
+        /// 终点
+        to: Position,
+        /// 拖拽总时长
+        duration: Duration,
+        /// 插值步数（不含起点）
+        steps: u32,
+        /// 缓动曲线
+        easing: Easing,
+    },
+    /// 等待（现实时间）
     Wait(Duration),
+    /// 等待游戏内时间，按当前战斗速度换算为现实时间（2 倍速时减半）
+    GameWait(Duration),
+    /// 重复执行固定次数
+    Repeat {
+        /// 重复次数
+        count: u32,
+        /// 循环体
+        body: Vec<ActionType>,
+    },
+    /// 循环执行直到条件成立（每次执行循环体前检查）
+    Loop {
+        /// 结束条件
+        until: Condition,
+        /// 最大循环次数，超过后报错
+        max_iterations: u32,
+        /// 循环体
+        body: Vec<ActionType>,
+    },
+    /// 调用已注册的操作序列
+    Call(String),
+    /// 条件分支
+    If {
+        /// 条件
+        condition: Condition,
+        /// 条件成立时执行
+        then: Vec<ActionType>,
+        /// 条件不成立时执行
+        #[serde(rename = "else", default)]
+        otherwise: Vec<ActionType>,
+    },
 }
 
-/// 鼠标按钮
+/// 序列执行条件
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Condition {
+    /// 程序处于指定状态
+    ProgramState(ProgramState),
+    /// 游戏处于指定状态
+    GameState(GameState),
+    /// 命名的图像识别判断结果为真
+    Vision(String),
+    /// 取反
+    Not(Box<Condition>),
+    /// 全部成立
+    All(Vec<Condition>),
+    /// 任意一个成立
+    Any(Vec<Condition>),
+}
+
+/// 缓动曲线
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// 匀速
+    #[default]
+    Linear,
+    /// 先慢后快
+    EaseIn,
+    /// 先快后慢
+    EaseOut,
+    /// 两端慢中间快
+    EaseInOut,
+}
+
+impl Easing {
+    /// 将进度 t (0.0-1.0) 映射为位移比例
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    -1.0 + (4.0 - 2.0 * t) * t
+                }
+            }
+        }
+    }
+}
+
+/// 计算拖拽路径上的点（不含起点，最后一个点总是终点）
+///
+/// 结果只取决于参数，可以直接用于断言
+pub fn drag_path(from: (i32, i32), to: (i32, i32), steps: u32, easing: Easing) -> Vec<(i32, i32)> {
+    let steps = steps.max(1);
+    (1..=steps)
+        .map(|step| {
+            let progress = easing.apply(step as f64 / steps as f64);
+            let x = from.0 as f64 + (to.0 - from.0) as f64 * progress;
+            let y = from.1 as f64 + (to.1 - from.1) as f64 * progress;
+            (x.round() as i32, y.round() as i32)
+        })
+        .collect()
+}
+
+/// 坐标系
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum CoordinateSpace {
+    /// 屏幕像素坐标
+    #[default]
+    Screen,
+    /// 目标窗口内的像素坐标
+    WindowClient,
+    /// 目标窗口内的比例坐标（0.0-1.0）
+    Normalized,
+}
+
+/// 带坐标系的位置
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Position {
+    /// X 坐标
+    pub x: f64,
+    /// Y 坐标
+    pub y: f64,
+    /// 坐标系
+    #[serde(default)]
+    pub space: CoordinateSpace,
+}
+
+impl Position {
+    /// 屏幕像素坐标
+    pub fn screen(x: i32, y: i32) -> Self {
+        Self { x: x as f64, y: y as f64, space: CoordinateSpace::Screen }
+    }
+
+    /// 目标窗口内的像素坐标
+    pub fn window_client(x: i32, y: i32) -> Self {
+        Self { x: x as f64, y: y as f64, space: CoordinateSpace::WindowClient }
+    }
+
+    /// 目标窗口内的比例坐标
+    pub fn normalized(x: f64, y: f64) -> Self {
+        Self { x, y, space: CoordinateSpace::Normalized }
+    }
+
+    /// 将屏幕坐标转换为指定坐标系中的位置
+    ///
+    /// 窗口坐标系需要目标窗口，且坐标必须在窗口内，否则返回 `CoordinateError`
+    pub fn from_screen(
+        (x, y): (i32, i32),
+        space: CoordinateSpace,
+        window: Option<&WindowInfo>,
+    ) -> ActionResult<Self> {
+        if space == CoordinateSpace::Screen {
+            return Ok(Self::screen(x, y));
+        }
+
+        let window = window.ok_or(ActionError::CoordinateError)?;
+        let (client_x, client_y) = window.screen_to_window_coords(x, y)
+            .ok_or(ActionError::CoordinateError)?;
+
+        match space {
+            CoordinateSpace::WindowClient => Ok(Self::window_client(client_x, client_y)),
+            _ => {
+                let (width, height) = window.size;
+                Ok(Self::normalized(client_x as f64 / width as f64, client_y as f64 / height as f64))
+            }
+        }
+    }
+
+    /// 转换为屏幕坐标
+    ///
+    /// 窗口坐标系需要已锁定的目标窗口，否则返回 `CoordinateError`
+    pub fn to_screen(&self, window: Option<&WindowInfo>) -> ActionResult<(i32, i32)> {
+        if !self.x.is_finite() || !self.y.is_finite() {
+            return Err(ActionError::InvalidParameter(format!("无效的坐标: ({}, {})", self.x, self.y)));
+        }
+
+        match self.space {
+            CoordinateSpace::Screen => Ok((self.x.round() as i32, self.y.round() as i32)),
+            CoordinateSpace::WindowClient => {
+                let window = window.ok_or(ActionError::CoordinateError)?;
+                Ok(window.window_to_screen_coords(self.x.round() as i32, self.y.round() as i32))
+            }
+            CoordinateSpace::Normalized => {
+                if !(0.0..=1.0).contains(&self.x) || !(0.0..=1.0).contains(&self.y) {
+                    return Err(ActionError::InvalidParameter(format!(
+                        "比例坐标超出范围 0.0-1.0: ({}, {})", self.x, self.y
+                    )));
+                }
+                let window = window.ok_or(ActionError::CoordinateError)?;
+                let (width, height) = window.size;
+                if width == 0 || height == 0 {
+                    return Err(ActionError::CoordinateError);
+                }
+
+                // 1.0 对应最后一个像素
+                let x = ((self.x * width as f64).round() as i32).min(width as i32 - 1);
+                let y = ((self.y * height as f64).round() as i32).min(height as i32 - 1);
+                Ok(window.window_to_screen_coords(x, y))
+            }
+        }
+    }
+}
+
+/// 鼠标按钮
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum MouseButton {
     /// 左键
     Left,
@@ -27,6 +277,42 @@ pub enum MouseButton {
     Middle,
 }
 
+/// 等待时长随机偏移的上限
+pub const MAX_WAIT_JITTER: Duration = Duration::from_millis(500);
+
+/// 坐标随机偏移的上限（像素）
+pub const MAX_POSITION_JITTER: u32 = 50;
+
+/// 拟人化设置
+///
+/// 默认关闭，零帧操作等对时间要求严格的序列不应开启
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct HumanizeConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 等待时长的最大随机偏移（正负）
+    pub wait_jitter: Duration,
+    /// 点击位置的最大随机偏移（像素，正负）
+    pub position_jitter: u32,
+    /// 拖拽路径中段的最大随机偏移（像素，正负）
+    pub drag_jitter: u32,
+    /// 随机数种子，为空时每次执行使用不同的随机序列
+    pub seed: Option<u64>,
+}
+
+impl HumanizeConfig {
+    /// 将偏移量限制在上限以内
+    pub fn clamped(self) -> Self {
+        Self {
+            wait_jitter: self.wait_jitter.min(MAX_WAIT_JITTER),
+            position_jitter: self.position_jitter.min(MAX_POSITION_JITTER),
+            drag_jitter: self.drag_jitter.min(MAX_POSITION_JITTER),
+            ..self
+        }
+    }
+}
+
 /// 操作序列
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ActionSequence {
@@ -36,6 +322,12 @@ pub struct ActionSequence {
     pub name: String,
     /// 描述
     pub description: Option<String>,
+    /// 拟人化设置
+    #[serde(default)]
+    pub humanize: HumanizeConfig,
+    /// 参数（名称 -> 值），按键名称和调用的序列名称中的 `$名称` 在执行时替换为参数值
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, i64>,
 }
 
 impl ActionSequence {
@@ -45,9 +337,23 @@ impl ActionSequence {
             actions: Vec::new(),
             name,
             description: None,
+            humanize: HumanizeConfig::default(),
+            params: BTreeMap::new(),
         }
     }
     
+    /// 设置参数
+    pub fn with_param(mut self, name: impl Into<String>, value: i64) -> Self {
+        self.params.insert(name.into(), value);
+        self
+    }
+    
+    /// 将按键中的 `$slot_key` 替换为槽位在游戏内的按键（包括循环和分支中的按键）
+    pub fn with_slot_key(mut self, key: &str) -> Self {
+        replace_slot_key(&mut self.actions, &format!("${}", SLOT_KEY_PARAM), key);
+        self
+    }
+    
     /// 添加操作
     pub fn add_action(&mut self, action: ActionType) {
         self.actions.push(action);
@@ -58,16 +364,82 @@ impl ActionSequence {
         self.actions.push(ActionType::KeyPress(key));
     }
     
+    /// 添加按下按键
+    pub fn add_key_down(&mut self, key: String) {
+        self.actions.push(ActionType::KeyDown(key));
+    }
+    
+    /// 添加释放按键
+    pub fn add_key_up(&mut self, key: String) {
+        self.actions.push(ActionType::KeyUp(key));
+    }
+    
+    /// 添加按住按键
+    pub fn add_key_hold(&mut self, key: String, duration: Duration) {
+        self.actions.push(ActionType::KeyHold(key, duration));
+    }
+    
     /// 添加鼠标点击
     pub fn add_mouse_click(&mut self, button: MouseButton, x: i32, y: i32) {
         self.actions.push(ActionType::MouseClick(button, x, y));
     }
     
+    /// 添加鼠标拖拽
+    pub fn add_mouse_drag(
+        &mut self,
+        button: MouseButton,
+        from: (i32, i32),
+        to: (i32, i32),
+        duration: Duration,
+        steps: u32,
+        easing: Easing,
+    ) {
+        self.actions.push(ActionType::MouseDrag { button, from, to, duration, steps, easing });
+    }
+    
+    /// 添加滚轮操作
+    pub fn add_scroll(&mut self, dx: i32, dy: i32) {
+        self.actions.push(ActionType::Scroll { dx, dy });
+    }
+    
+    /// 添加重复执行
+    pub fn add_repeat(&mut self, count: u32, body: Vec<ActionType>) {
+        self.actions.push(ActionType::Repeat { count, body });
+    }
+    
+    /// 添加序列调用
+    pub fn add_call(&mut self, name: String) {
+        self.actions.push(ActionType::Call(name));
+    }
+    
+    /// 添加指定坐标系中的鼠标点击
+    pub fn add_mouse_click_at(&mut self, button: MouseButton, position: Position) {
+        self.actions.push(ActionType::MouseClickAt(button, position));
+    }
+    
+    /// 添加指定坐标系中的鼠标拖拽
+    pub fn add_mouse_drag_at(
+        &mut self,
+        button: MouseButton,
+        from: Position,
+        to: Position,
+        duration: Duration,
+        steps: u32,
+        easing: Easing,
+    ) {
+        self.actions.push(ActionType::MouseDragAt { button, from, to, duration, steps, easing });
+    }
+    
     /// 添加等待
     pub fn add_wait(&mut self, duration: Duration) {
         self.actions.push(ActionType::Wait(duration));
     }
     
+    /// 添加游戏内时间等待
+    pub fn add_game_wait(&mut self, duration: Duration) {
+        self.actions.push(ActionType::GameWait(duration));
+    }
+    
     /// 获取操作数量
     pub fn len(&self) -> usize {
         self.actions.len()
@@ -79,6 +451,90 @@ impl ActionSequence {
     }
 }
 
+fn replace_slot_key(actions: &mut [ActionType], placeholder: &str, key: &str) {
+    for action in actions {
+        match action {
+            ActionType::KeyPress(text) | ActionType::KeyDown(text) | ActionType::KeyUp(text) | ActionType::KeyHold(text, _) => {
+                *text = text.replace(placeholder, key);
+            }
+            ActionType::Repeat { body, .. } | ActionType::Loop { body, .. } => replace_slot_key(body, placeholder, key),
+            ActionType::If { then, otherwise, .. } => {
+                replace_slot_key(then, placeholder, key);
+                replace_slot_key(otherwise, placeholder, key);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 是否为有效的序列参数名（字母、数字和下划线）
+pub fn is_param_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(is_param_char)
+}
+
+fn is_param_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// 将文本中的 `$名称` 替换为参数值
+///
+/// 引用未定义的参数时返回错误
+pub fn expand_params<'a>(text: &'a str, params: &BTreeMap<String, i64>) -> ActionResult<Cow<'a, str>> {
+    if !text.contains('$') {
+        return Ok(Cow::Borrowed(text));
+    }
+
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after.find(|c: char| !is_param_char(c)).unwrap_or(after.len());
+        let name = &after[..end];
+        if name.is_empty() {
+            return Err(ActionError::InvalidParameter(format!("无效的参数引用: {}", text)));
+        }
+        let value = params.get(name)
+            .ok_or_else(|| ActionError::InvalidParameter(format!("未定义的序列参数: {}", name)))?;
+        expanded.push_str(&value.to_string());
+        rest = &after[end..];
+    }
+    expanded.push_str(rest);
+    Ok(Cow::Owned(expanded))
+}
+
+/// 槽位编号在序列参数中的名称（从 1 开始）
+pub const SLOT_PARAM: &str = "slot";
+
+/// 槽位在游戏内的按键在序列按键中的引用名称，如 `key $slot_key`
+pub const SLOT_KEY_PARAM: &str = "slot_key";
+
+/// 按槽位区分的操作，热键列表中的第 n 个按键触发 `操作名[n]`
+pub const SLOT_OPERATIONS: [&str; 3] = ["deploy_operator", "activate_skill", "retreat_operator"];
+
+/// 是否为按槽位区分的操作
+pub fn is_slot_operation(name: &str) -> bool {
+    SLOT_OPERATIONS.contains(&name)
+}
+
+/// 槽位操作的名称，如 `deploy_operator[3]`
+pub fn slot_operation_name(operation: &str, slot: usize) -> String {
+    format!("{}[{}]", operation, slot)
+}
+
+/// 解析槽位操作的名称，返回操作名和槽位编号
+pub fn parse_slot_operation(name: &str) -> Option<(&str, usize)> {
+    let (operation, rest) = name.split_once('[')?;
+    let slot = rest.strip_suffix(']')?.parse().ok()?;
+    (is_slot_operation(operation) && slot > 0).then_some((operation, slot))
+}
+
+/// 槽位在游戏内的按键：逗号分隔的按键列表中的第 n 个（从 1 开始），列表无效或槽位超出列表时为 None
+pub fn slot_key(keys: &str, slot: usize) -> Option<String> {
+    let keys = KeyCombo::parse_list(keys).ok()?;
+    keys.get(slot.checked_sub(1)?).map(ToString::to_string)
+}
+
 /// 游戏操作定义
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct GameOperation {
@@ -92,6 +548,26 @@ pub struct GameOperation {
     pub sequence: ActionSequence,
     /// 是否启用
     pub enabled: bool,
+    /// 优先级（数值越大越优先）
+    #[serde(default)]
+    pub priority: u8,
+    /// 执行器忙碌时的处理方式
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+}
+
+/// 执行器忙碌时新操作的处理方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    /// 排队等待
+    #[default]
+    Queue,
+    /// 忙碌时丢弃
+    DropIfBusy,
+    /// 优先级高于正在执行的操作时打断它，否则排队
+    PreemptLower,
+    /// 同名操作正在执行或排队时合并为一次，否则排队
+    Coalesce,
 }
 
 impl GameOperation {
@@ -103,8 +579,24 @@ impl GameOperation {
             game_key,
             sequence: ActionSequence::new(name),
             enabled: true,
+            priority: 0,
+            conflict_policy: ConflictPolicy::Queue,
         }
     }
+    
+    /// 指定槽位的操作：名称为 `操作名[槽位]`，槽位编号作为序列参数 `slot` 传入
+    pub fn for_slot(&self, slot: usize) -> GameOperation {
+        let mut operation = self.clone();
+        operation.name = slot_operation_name(&self.name, slot);
+        operation.sequence.params.insert(SLOT_PARAM.to_string(), slot as i64);
+        operation
+    }
+    
+    /// 将序列按键中的 `$slot_key` 替换为槽位在游戏内的按键
+    pub fn with_slot_key(mut self, key: &str) -> Self {
+        self.sequence = self.sequence.with_slot_key(key);
+        self
+    }
 }
 
 /// 预定义的游戏操作
@@ -130,8 +622,8 @@ impl DefaultOperations {
             "1".to_string(),
         );
         
-        // 添加默认的拖出干员操作序列
-        operation.sequence.add_key_press("1".to_string());
+        // 添加默认的拖出干员操作序列：按槽位在游戏内的按键选择干员
+        operation.sequence.add_key_press(format!("${}", SLOT_KEY_PARAM));
         operation.sequence.add_wait(Duration::from_millis(100));
         
         operation
@@ -145,7 +637,8 @@ impl DefaultOperations {
             "Space".to_string(),
         );
         
-        operation.sequence.add_key_press("Space".to_string());
+        // 按槽位在游戏内的技能按键开启技能
+        operation.sequence.add_key_press(format!("${}", SLOT_KEY_PARAM));
         operation.sequence.add_wait(Duration::from_millis(50));
         
         operation
@@ -159,7 +652,7 @@ impl DefaultOperations {
             "Delete".to_string(),
         );
         
-        operation.sequence.add_key_press("Delete".to_string());
+        operation.sequence.add_key_press(format!("${}", SLOT_KEY_PARAM));
         operation.sequence.add_wait(Duration::from_millis(100));
         
         operation
@@ -189,7 +682,129 @@ impl DefaultOperations {
         
         operation.sequence.add_key_press("Escape".to_string());
         operation.sequence.add_wait(Duration::from_millis(100));
+        // 暂停需要立即响应
+        operation.priority = 100;
+        operation.conflict_policy = ConflictPolicy::PreemptLower;
         
         operation
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_to_screen() {
+        let window = WindowInfo::test_window((100, 50), (1280, 720));
+
+        assert_eq!(Position::screen(10, 20).to_screen(None).unwrap(), (10, 20));
+        assert_eq!(Position::window_client(10, 20).to_screen(Some(&window)).unwrap(), (110, 70));
+        assert_eq!(Position::normalized(0.5, 0.5).to_screen(Some(&window)).unwrap(), (740, 410));
+        assert_eq!(Position::normalized(1.0, 1.0).to_screen(Some(&window)).unwrap(), (1379, 769));
+
+        assert!(matches!(
+            Position::window_client(10, 20).to_screen(None),
+            Err(ActionError::CoordinateError)
+        ));
+        assert!(matches!(
+            Position::normalized(1.5, 0.0).to_screen(Some(&window)),
+            Err(ActionError::InvalidParameter(_))
+        ));
+
+        // 屏幕坐标转换回窗口坐标系
+        for screen in [(110, 70), (740, 410), (1379, 769)] {
+            for space in [CoordinateSpace::Screen, CoordinateSpace::WindowClient, CoordinateSpace::Normalized] {
+                let position = Position::from_screen(screen, space, Some(&window)).unwrap();
+                assert_eq!(position.space, space);
+                assert_eq!(position.to_screen(Some(&window)).unwrap(), screen);
+            }
+        }
+        assert!(matches!(
+            Position::from_screen((0, 0), CoordinateSpace::WindowClient, Some(&window)),
+            Err(ActionError::CoordinateError)
+        ));
+        assert!(matches!(
+            Position::from_screen((110, 70), CoordinateSpace::Normalized, None),
+            Err(ActionError::CoordinateError)
+        ));
+    }
+
+    #[test]
+    fn test_humanize_disabled_by_default() {
+        let mut sequence = ActionSequence::new("zero_frame".to_string());
+        sequence.add_key_press("1".to_string());
+        assert!(!sequence.humanize.enabled);
+
+        // 旧配置中没有拟人化设置
+        let mut value = serde_json::to_value(&sequence).unwrap();
+        value.as_object_mut().unwrap().remove("humanize");
+        let restored: ActionSequence = serde_json::from_value(value).unwrap();
+        assert_eq!(restored, sequence);
+    }
+
+    #[test]
+    fn test_easing_endpoints() {
+        for easing in [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+        }
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert!(Easing::EaseIn.apply(0.25) < 0.25);
+        assert!(Easing::EaseOut.apply(0.25) > 0.25);
+    }
+
+    #[test]
+    fn test_drag_path() {
+        assert_eq!(
+            drag_path((0, 0), (100, -100), 4, Easing::Linear),
+            vec![(25, -25), (50, -50), (75, -75), (100, -100)]
+        );
+        assert_eq!(
+            drag_path((0, 0), (100, 0), 4, Easing::EaseIn),
+            vec![(6, 0), (25, 0), (56, 0), (100, 0)]
+        );
+
+        // 步数为 0 时直接到达终点
+        assert_eq!(drag_path((10, 10), (20, 30), 0, Easing::EaseOut), vec![(20, 30)]);
+    }
+
+    #[test]
+    fn test_params_and_slots() {
+        let params: BTreeMap<String, i64> = [("slot".to_string(), 3), ("n".to_string(), -1)].into();
+        assert_eq!(expand_params("F1", &params).unwrap(), "F1");
+        assert_eq!(expand_params("$slot", &params).unwrap(), "3");
+        assert_eq!(expand_params("skill_$slot+$n", &params).unwrap(), "skill_3+-1");
+        assert!(expand_params("$missing", &params).is_err());
+        assert!(expand_params("$", &params).is_err());
+
+        assert_eq!(slot_operation_name("deploy_operator", 12), "deploy_operator[12]");
+        assert_eq!(parse_slot_operation("retreat_operator[2]"), Some(("retreat_operator", 2)));
+        assert_eq!(parse_slot_operation("retreat_operator[0]"), None);
+        assert_eq!(parse_slot_operation("focus_view[1]"), None);
+        assert_eq!(parse_slot_operation("deploy_operator"), None);
+
+        let operation = GameOperation::new("activate_skill".to_string(), "Q".to_string(), "E".to_string());
+        let slotted = operation.for_slot(5);
+        assert_eq!(slotted.name, "activate_skill[5]");
+        assert_eq!(slotted.sequence.params.get(SLOT_PARAM), Some(&5));
+        assert!(operation.sequence.params.is_empty());
+
+        // 槽位按键取自按键列表
+        assert_eq!(slot_key("1,2,0,-,=", 5).as_deref(), Some("="));
+        assert_eq!(slot_key("1,2,Ctrl+Comma", 3).as_deref(), Some("Ctrl+Comma"));
+        assert_eq!(slot_key("1,2", 3), None);
+        assert_eq!(slot_key("1,2", 0), None);
+        assert_eq!(slot_key("1,,2", 1), None);
+
+        let sequence: ActionSequence = "key Shift+$slot_key\nrepeat 2 {\n    key $slot_key\n}\nkey $slot".parse().unwrap();
+        let operation = GameOperation { sequence, ..operation };
+        let slotted = operation.for_slot(10).with_slot_key("-");
+        assert_eq!(slotted.sequence.actions[0], ActionType::KeyPress("Shift+-".to_string()));
+        assert_eq!(slotted.sequence.actions[1], ActionType::Repeat {
+            count: 2,
+            body: vec![ActionType::KeyPress("-".to_string())],
+        });
+        assert_eq!(slotted.sequence.actions[2], ActionType::KeyPress("$slot".to_string()));
+    }
+}
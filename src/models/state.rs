@@ -47,6 +47,48 @@ impl std::fmt::Display for GameState {
     }
 }
 
+/// 战斗速度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GameSpeed {
+    /// 1 倍速
+    #[default]
+    Normal,
+    /// 2 倍速
+    Double,
+}
+
+impl GameSpeed {
+    /// 游戏时间相对现实时间的倍率
+    pub fn factor(self) -> f64 {
+        match self {
+            GameSpeed::Normal => 1.0,
+            GameSpeed::Double => 2.0,
+        }
+    }
+
+    /// 切换后的速度
+    pub fn toggled(self) -> Self {
+        match self {
+            GameSpeed::Normal => GameSpeed::Double,
+            GameSpeed::Double => GameSpeed::Normal,
+        }
+    }
+
+    /// 将游戏内时长换算为现实时长
+    pub fn to_real(self, game_time: std::time::Duration) -> std::time::Duration {
+        game_time.div_f64(self.factor())
+    }
+}
+
+impl std::fmt::Display for GameSpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameSpeed::Normal => write!(f, "1x"),
+            GameSpeed::Double => write!(f, "2x"),
+        }
+    }
+}
+
 /// 应用程序整体状态
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AppState {
@@ -77,6 +77,23 @@ impl WindowInfo {
         }
     }
     
+    /// 测试用的前台游戏窗口
+    #[cfg(test)]
+    pub(crate) fn test_window(position: (i32, i32), size: (u32, u32)) -> Self {
+        Self {
+            #[cfg(windows)]
+            handle: WindowHandle::from(std::ptr::null_mut()),
+            #[cfg(not(windows))]
+            handle: 0,
+            position,
+            size,
+            title: "明日方舟".to_string(),
+            process_id: 0,
+            is_visible: true,
+            is_foreground: true,
+        }
+    }
+    
     /// 获取窗口中心点坐标
     pub fn center(&self) -> (i32, i32) {
         (
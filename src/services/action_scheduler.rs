@@ -0,0 +1,337 @@
+//! 高精度操作调度器
+//!
+//! `tokio::time::sleep` 的精度为毫秒级且有数毫秒的抖动，不足以支撑帧级别
+//! （30 fps 约 33 ms 一帧）的操作。调度器在独立线程上按绝对时间点等待：
+//! 距离时间点较远时休眠，进入自旋阈值后自旋到时间点再唤醒等待方。
+
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// 默认自旋阈值
+///
+/// 距离时间点小于该值时不再休眠，改为自旋等待
+pub const DEFAULT_SPIN_THRESHOLD: Duration = Duration::from_millis(2);
+
+/// 唤醒请求
+struct WakeRequest {
+    /// 目标时间点
+    deadline: Instant,
+    /// 请求序号，相同时间点按提交顺序唤醒
+    id: u64,
+    /// 唤醒通道，发送实际唤醒时间
+    waker: oneshot::Sender<Instant>,
+}
+
+impl PartialEq for WakeRequest {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline && self.id == other.id
+    }
+}
+
+impl Eq for WakeRequest {}
+
+impl PartialOrd for WakeRequest {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for WakeRequest {
+    /// 反向比较，使 `BinaryHeap` 顶部为最早的时间点
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.deadline.cmp(&self.deadline)
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+/// 高精度调度器
+///
+/// 所有等待请求由同一个调度线程处理，调度器释放后线程在处理完剩余请求后退出
+pub struct PrecisionScheduler {
+    /// 请求通道
+    sender: Sender<WakeRequest>,
+    /// 下一个请求序号
+    next_id: AtomicU64,
+    /// 自旋阈值
+    spin_threshold: Duration,
+}
+
+impl PrecisionScheduler {
+    /// 使用默认自旋阈值创建调度器
+    pub fn new() -> Self {
+        Self::with_spin_threshold(DEFAULT_SPIN_THRESHOLD)
+    }
+
+    /// 使用指定自旋阈值创建调度器
+    pub fn with_spin_threshold(spin_threshold: Duration) -> Self {
+        let (sender, receiver) = unbounded();
+
+        let spawned = std::thread::Builder::new()
+            .name("action-scheduler".to_string())
+            .spawn(move || run_scheduler(receiver, spin_threshold));
+        if let Err(e) = spawned {
+            // 请求发送失败时 `wait_until` 会退回到 tokio 定时器
+            log::error!("创建调度线程失败: {}", e);
+        }
+
+        Self {
+            sender,
+            next_id: AtomicU64::new(0),
+            spin_threshold,
+        }
+    }
+
+    /// 进程共享的调度器
+    pub fn shared() -> Arc<Self> {
+        static SHARED: OnceLock<Arc<PrecisionScheduler>> = OnceLock::new();
+        Arc::clone(SHARED.get_or_init(|| Arc::new(Self::new())))
+    }
+
+    /// 自旋阈值
+    pub fn spin_threshold(&self) -> Duration {
+        self.spin_threshold
+    }
+
+    /// 等待到指定时间点，返回实际唤醒时间
+    ///
+    /// 时间点已过时立即返回
+    pub async fn wait_until(&self, deadline: Instant) -> Instant {
+        let now = Instant::now();
+        if now >= deadline {
+            return now;
+        }
+
+        let (waker, woken) = oneshot::channel();
+        let request = WakeRequest {
+            deadline,
+            id: self.next_id.fetch_add(1, AtomicOrdering::Relaxed),
+            waker,
+        };
+
+        if self.sender.send(request).is_err() {
+            log::warn!("调度线程不可用，使用普通定时器");
+            tokio::time::sleep_until(tokio::time::Instant::from_std(deadline)).await;
+            return Instant::now();
+        }
+
+        woken.await.unwrap_or_else(|_| Instant::now())
+    }
+}
+
+impl Default for PrecisionScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for PrecisionScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrecisionScheduler")
+            .field("spin_threshold", &self.spin_threshold)
+            .finish()
+    }
+}
+
+/// 调度线程主循环
+fn run_scheduler(receiver: Receiver<WakeRequest>, spin_threshold: Duration) {
+    let _timer_resolution = TimerResolution::acquire();
+    let mut pending: BinaryHeap<WakeRequest> = BinaryHeap::new();
+    let mut connected = true;
+
+    loop {
+        // 收取新请求，不阻塞
+        while connected {
+            match receiver.try_recv() {
+                Ok(request) => pending.push(request),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => connected = false,
+            }
+        }
+
+        // 丢弃等待方已放弃的请求（例如序列被取消）
+        while pending.peek().is_some_and(|request| request.waker.is_closed()) {
+            pending.pop();
+        }
+
+        let Some(next) = pending.peek() else {
+            if !connected {
+                return;
+            }
+            match receiver.recv() {
+                Ok(request) => pending.push(request),
+                Err(_) => return,
+            }
+            continue;
+        };
+
+        let now = Instant::now();
+        if now >= next.deadline {
+            if let Some(request) = pending.pop() {
+                let _ = request.waker.send(now);
+            }
+            continue;
+        }
+
+        let remaining = next.deadline - now;
+        if remaining > spin_threshold && connected {
+            // 休眠到自旋阈值前，期间有新请求到达时提前醒来
+            match receiver.recv_timeout(remaining - spin_threshold) {
+                Ok(request) => pending.push(request),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => connected = false,
+            }
+        } else if remaining > spin_threshold {
+            std::thread::sleep(remaining - spin_threshold);
+        } else {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+/// 调度线程运行期间提高系统定时器精度
+///
+/// Windows 默认定时器精度约 15.6 ms，休眠阶段会严重超时
+struct TimerResolution;
+
+impl TimerResolution {
+    fn acquire() -> Self {
+        #[cfg(windows)]
+        unsafe {
+            winapi::um::timeapi::timeBeginPeriod(1);
+        }
+        Self
+    }
+}
+
+impl Drop for TimerResolution {
+    fn drop(&mut self) {
+        #[cfg(windows)]
+        unsafe {
+            winapi::um::timeapi::timeEndPeriod(1);
+        }
+    }
+}
+
+/// 单次输出的计划时间和实际时间（相对序列开始）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimingSample {
+    /// 计划时间
+    pub scheduled: Duration,
+    /// 实际时间
+    pub actual: Duration,
+}
+
+impl TimingSample {
+    /// 偏差（实际晚于计划的时长）
+    pub fn drift(&self) -> Duration {
+        self.actual.saturating_sub(self.scheduled)
+    }
+}
+
+/// 时间偏差统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimingStats {
+    /// 样本数量
+    pub samples: usize,
+    /// 平均偏差
+    pub mean_drift: Duration,
+    /// 95 分位偏差
+    pub p95_drift: Duration,
+    /// 最大偏差
+    pub max_drift: Duration,
+}
+
+impl TimingStats {
+    /// 根据样本计算统计
+    pub fn from_samples(samples: &[TimingSample]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+
+        let mut drifts: Vec<Duration> = samples.iter().map(TimingSample::drift).collect();
+        drifts.sort();
+
+        let total: Duration = drifts.iter().sum();
+        let p95_index = (drifts.len() * 95).div_ceil(100).saturating_sub(1);
+
+        Self {
+            samples: drifts.len(),
+            mean_drift: total / drifts.len() as u32,
+            p95_drift: drifts[p95_index],
+            max_drift: drifts[drifts.len() - 1],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_until_is_not_early() {
+        let scheduler = PrecisionScheduler::new();
+
+        let start = Instant::now();
+        let deadlines: Vec<Instant> = (1..=5)
+            .map(|i| start + Duration::from_millis(3 * i))
+            .collect();
+
+        for deadline in deadlines {
+            let woken = scheduler.wait_until(deadline).await;
+            assert!(woken >= deadline);
+            // 调度线程本身的唤醒误差应远小于一帧
+            assert!(woken - deadline < Duration::from_millis(20));
+        }
+
+        // 已过去的时间点立即返回
+        let past = start;
+        assert!(scheduler.wait_until(past).await >= past);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_waits_wake_in_order() {
+        let scheduler = Arc::new(PrecisionScheduler::new());
+        let start = Instant::now();
+
+        let late = {
+            let scheduler = Arc::clone(&scheduler);
+            tokio::spawn(async move { scheduler.wait_until(start + Duration::from_millis(20)).await })
+        };
+        let early = scheduler.wait_until(start + Duration::from_millis(5)).await;
+        let late = late.await.unwrap();
+
+        assert!(early >= start + Duration::from_millis(5));
+        assert!(late >= start + Duration::from_millis(20));
+        assert!(early < late);
+    }
+
+    #[test]
+    fn test_timing_stats() {
+        assert_eq!(TimingStats::from_samples(&[]), TimingStats::default());
+
+        let samples: Vec<TimingSample> = (0..20)
+            .map(|i| TimingSample {
+                scheduled: Duration::from_millis(i * 10),
+                actual: Duration::from_millis(i * 10) + Duration::from_micros(i * 100),
+            })
+            .collect();
+
+        let stats = TimingStats::from_samples(&samples);
+        assert_eq!(stats.samples, 20);
+        assert_eq!(stats.max_drift, Duration::from_micros(1900));
+        assert_eq!(stats.p95_drift, Duration::from_micros(1800));
+        assert_eq!(stats.mean_drift, Duration::from_micros(950));
+
+        // 提前输出不计为负偏差
+        let early = TimingSample {
+            scheduled: Duration::from_millis(10),
+            actual: Duration::from_millis(9),
+        };
+        assert_eq!(early.drift(), Duration::ZERO);
+    }
+}
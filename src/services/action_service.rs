@@ -2,264 +2,2318 @@
 //! 
 //! 负责执行各种操作，包括键盘按键、鼠标移动和点击等
 
-use crate::models::{ActionType, ActionSequence, MouseButton};
-use crate::utils::{ActionError, ActionResult};
-use std::sync::Arc;
-use std::collections::HashMap;
-
-#[cfg(windows)]
-use winapi::um::winuser::{
-    SendInput, INPUT, INPUT_KEYBOARD, INPUT_MOUSE, KEYBDINPUT, MOUSEINPUT,
-    KEYEVENTF_KEYUP, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
-    MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_MIDDLEDOWN,
-    MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_MOVE, MOUSEEVENTF_ABSOLUTE,
-    VK_SPACE, VK_DELETE, VK_ESCAPE, VK_F1, VK_F2, VK_F3, VK_F4,
-    GetCursorPos, SetCursorPos,
+use crate::models::{
+    drag_path, expand_params, ActionType, ActionSequence, Condition, Easing, GameSpeed, HumanizeConfig, Key,
+    KeyCombo, MouseButton, Position,
+};
+use crate::services::action_scheduler::{PrecisionScheduler, TimingSample, TimingStats};
+use crate::services::execution_tracer::{ExecutionTracer, TraceEntry};
+use crate::services::game_speed::GameSpeedTracker;
+use crate::services::global_input::InjectedKeys;
+use crate::services::humanizer::Humanizer;
+use crate::services::input_backend::{InputBackend, InputEvent, PlatformInputBackend};
+use crate::services::safety_guard::{SafetyGuard, SafetyViolation};
+use crate::services::sequence_flow::{
+    ConditionSource, SequenceResolver, DEFAULT_MAX_CALL_DEPTH, MAX_LOOP_ITERATIONS,
 };
+use crate::services::sequence_registry::{CancellationToken, SequenceRegistry};
+use crate::services::window_service::TargetWindow;
+use crate::utils::{ActionError, ActionResult};
+use chrono::Local;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError, TryLockError};
+use std::collections::{BTreeMap, HashMap};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// 滚轮一格对应的滚动量
+const WHEEL_DELTA: i32 = 120;
+
+/// 拖拽时长上限
+const MAX_DRAG_DURATION: Duration = Duration::from_secs(3600);
+
+/// 序列执行报告
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SequenceReport {
+    /// 序列名称
+    pub name: String,
+    /// 已执行的操作数量（不含控制流操作本身）
+    pub actions_executed: usize,
+    /// 序列结束时仍被按住的按键（虚拟键码，按按下顺序）
+    pub held_keys: Vec<u16>,
+    /// 序列结束时仍被按住的鼠标按钮
+    pub held_buttons: Vec<MouseButton>,
+    /// 执行耗时
+    pub elapsed: Duration,
+    /// 各次输出的计划时间和实际时间
+    pub timeline: Vec<TimingSample>,
+    /// 时间偏差统计
+    pub timing: TimingStats,
+}
+
+/// 模拟执行时间线条目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimelineEntry {
+    /// 相对序列开始的时间
+    pub offset: Duration,
+    /// 输入事件
+    pub event: InputEvent,
+}
+
+impl std::fmt::Display for TimelineEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "+{:>9.3}ms {:?}", self.offset.as_secs_f64() * 1000.0, self.event)
+    }
+}
+
+/// 序列时钟
+#[derive(Debug)]
+enum Clock {
+    /// 实际执行，由调度器等待
+    Real(Arc<PrecisionScheduler>),
+    /// 模拟执行，等待立即完成，事件记录到时间线而不发送
+    Virtual(Vec<TimelineEntry>),
+}
+
+/// 序列执行上下文
+///
+/// 等待按时间线上的绝对时间点进行：每次等待从上一次的计划时间点开始计算，
+/// 执行本身的耗时不会累积到后续操作上
+#[derive(Debug)]
+struct SequenceContext {
+    /// 时钟
+    clock: Clock,
+    /// 当前序列名称
+    sequence: String,
+    /// 执行编号（用于追踪）
+    run_id: u64,
+    /// 当前操作已发送的事件，仅在追踪时记录
+    trace_events: Option<Vec<InputEvent>>,
+    /// 序列开始时间
+    origin: Instant,
+    /// 时间线当前的计划时间点
+    cursor: Instant,
+    /// 输出时间记录
+    samples: Vec<TimingSample>,
+    /// 拟人化随机偏移
+    humanizer: Humanizer,
+    /// 战斗速度（实际执行时以跟踪器为准，模拟执行时按发出的变速键切换）
+    game_speed: GameSpeed,
+    /// 本序列按下且尚未释放的按键
+    held_keys: Vec<u16>,
+    /// 本序列按下且尚未释放的鼠标按钮
+    held_buttons: Vec<MouseButton>,
+    /// 取消令牌
+    cancel: CancellationToken,
+    /// 超时时间点
+    deadline: Option<tokio::time::Instant>,
+    /// 超时时间点来自安全保护的执行时间上限时，记录该上限
+    duration_limit: Option<Duration>,
+    /// 当前序列调用深度
+    call_depth: usize,
+    /// 当前序列的参数
+    params: BTreeMap<String, i64>,
+    /// 已执行的操作数量
+    executed: usize,
+}
+
+impl SequenceContext {
+    /// 创建带取消令牌和超时的上下文
+    fn new(
+        clock: Clock,
+        sequence: &str,
+        humanize: HumanizeConfig,
+        cancel: CancellationToken,
+        timeout: Option<Duration>,
+    ) -> Self {
+        let origin = Instant::now();
+        Self {
+            clock,
+            sequence: sequence.to_string(),
+            run_id: 0,
+            trace_events: None,
+            origin,
+            cursor: origin,
+            samples: Vec::new(),
+            humanizer: Humanizer::new(humanize),
+            game_speed: GameSpeed::Normal,
+            held_keys: Vec::new(),
+            held_buttons: Vec::new(),
+            cancel,
+            deadline: timeout.map(|timeout| tokio::time::Instant::from_std(origin + timeout)),
+            duration_limit: None,
+            call_depth: 0,
+            params: BTreeMap::new(),
+            executed: 0,
+        }
+    }
+
+    /// 应用执行时间上限，比现有超时更早时生效
+    fn limit_duration(&mut self, limit: Duration) {
+        let deadline = tokio::time::Instant::from_std(self.origin + limit);
+        if self.deadline.is_none_or(|current| deadline < current) {
+            self.deadline = Some(deadline);
+            self.duration_limit = Some(limit);
+        }
+    }
+
+    /// 到达超时时间点时的错误
+    fn deadline_error(&self) -> ActionError {
+        match self.duration_limit {
+            Some(limit) => ActionError::SequenceDurationExceeded(limit),
+            None => ActionError::Timeout,
+        }
+    }
+
+    /// 是否为模拟执行
+    fn is_virtual(&self) -> bool {
+        matches!(self.clock, Clock::Virtual(_))
+    }
+
+    /// 记录一次输出的计划时间和实际时间
+    fn record(&mut self) {
+        if self.is_virtual() {
+            return;
+        }
+        self.samples.push(TimingSample {
+            scheduled: self.cursor - self.origin,
+            actual: self.origin.elapsed(),
+        });
+    }
+
+    /// 将时间线推进到当前时刻
+    ///
+    /// 用于依赖运行时状态的分支之后，避免为追赶时间线而连续输出
+    fn resync(&mut self) {
+        if !self.is_virtual() {
+            self.cursor = self.cursor.max(Instant::now());
+        }
+    }
+
+    /// 检查是否已取消或超时
+    fn check(&self) -> ActionResult<()> {
+        if self.cancel.is_cancelled() {
+            return Err(ActionError::Cancelled);
+        }
+        if matches!(self.deadline, Some(deadline) if tokio::time::Instant::now() >= deadline) {
+            return Err(self.deadline_error());
+        }
+        Ok(())
+    }
+
+    /// 等待到时间线上的指定时间点，期间可被取消或超时打断
+    async fn sleep_until(&mut self, target: Instant) -> ActionResult<()> {
+        let scheduler = match &self.clock {
+            Clock::Real(scheduler) => scheduler,
+            Clock::Virtual(_) => {
+                self.check()?;
+                self.cursor = self.cursor.max(target);
+                return Ok(());
+            }
+        };
+
+        let timeout = async {
+            match self.deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            biased;
+            _ = self.cancel.cancelled() => return Err(ActionError::Cancelled),
+            _ = timeout => return Err(self.deadline_error()),
+            _ = scheduler.wait_until(target) => {}
+        }
+
+        self.cursor = self.cursor.max(target);
+        Ok(())
+    }
+
+    /// 从当前计划时间点起等待指定时长，期间可被取消或超时打断
+    async fn sleep(&mut self, duration: Duration) -> ActionResult<()> {
+        self.sleep_until(self.cursor + duration).await
+    }
+}
+
+/// 序列上下文守卫
+///
+/// 执行中途出错、被取消或 Future 被丢弃（任务被中止、panic 展开）时，
+/// 释放本序列仍按住的按键和鼠标按钮
+struct ContextGuard<'a, B: InputBackend> {
+    /// 操作服务
+    service: &'a ActionService<B>,
+    /// 序列上下文，正常结束时被取出
+    context: Option<SequenceContext>,
+}
+
+impl<'a, B: InputBackend> ContextGuard<'a, B> {
+    fn new(service: &'a ActionService<B>, context: SequenceContext) -> Self {
+        Self {
+            service,
+            context: Some(context),
+        }
+    }
+
+    /// 正常结束，取出上下文，按住的输入保持不变
+    fn finish(mut self) -> SequenceContext {
+        self.context.take().expect("序列上下文只能取出一次")
+    }
+}
+
+impl<B: InputBackend> Deref for ContextGuard<'_, B> {
+    type Target = SequenceContext;
+
+    fn deref(&self) -> &SequenceContext {
+        self.context.as_ref().expect("序列上下文已被取出")
+    }
+}
+
+impl<B: InputBackend> DerefMut for ContextGuard<'_, B> {
+    fn deref_mut(&mut self) -> &mut SequenceContext {
+        self.context.as_mut().expect("序列上下文已被取出")
+    }
+}
+
+impl<B: InputBackend> Drop for ContextGuard<'_, B> {
+    fn drop(&mut self) {
+        if let Some(mut context) = self.context.take() {
+            self.service.release_context(&mut context);
+        }
+    }
+}
+
+/// 后台执行的序列句柄
+///
+/// 可以直接 `.await` 获取执行结果，或调用 `cancel()` 取消执行
+pub struct SequenceHandle {
+    /// 序列 ID
+    id: u64,
+    /// 取消令牌
+    cancel: CancellationToken,
+    /// 执行任务
+    task: tokio::task::JoinHandle<ActionResult<SequenceReport>>,
+}
+
+impl SequenceHandle {
+    /// 序列 ID
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// 取消执行，已按住的按键和鼠标按钮会被释放
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// 是否已经结束
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Future for SequenceHandle {
+    type Output = ActionResult<SequenceReport>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.task).poll(cx).map(|result| {
+            result.unwrap_or_else(|e| Err(ActionError::SequenceError(format!("序列任务异常结束: {}", e))))
+        })
+    }
+}
 
 /// 操作执行服务
-pub struct ActionService {
-    /// 按键映射表
+///
+/// 实际的输入注入由 `InputBackend` 完成，默认使用当前平台的后端
+pub struct ActionService<B: InputBackend = PlatformInputBackend> {
+    /// 输入后端
+    backend: Arc<B>,
+    /// 自定义按键映射表（优先于内置按键表）
     key_map: HashMap<String, u16>,
+    /// 当前被按住的按键（所有序列）
+    held_keys: Mutex<Vec<u16>>,
+    /// 当前被按住的鼠标按钮（所有序列）
+    held_buttons: Mutex<Vec<MouseButton>>,
+    /// 正在执行的序列
+    registry: Arc<SequenceRegistry>,
+    /// `Call` 使用的序列解析器
+    resolver: Option<Arc<dyn SequenceResolver>>,
+    /// `Loop` / `If` 使用的条件来源
+    conditions: Option<Arc<dyn ConditionSource>>,
+    /// 最大序列调用深度
+    max_call_depth: usize,
+    /// 等待使用的调度器
+    scheduler: Arc<PrecisionScheduler>,
+    /// 窗口坐标使用的目标窗口
+    window: Option<Arc<dyn TargetWindow>>,
+    /// 执行追踪
+    tracer: Option<Arc<ExecutionTracer>>,
+    /// 安全保护
+    safety: Option<Arc<SafetyGuard>>,
+    /// 战斗速度跟踪
+    game_speed: Option<Arc<GameSpeedTracker>>,
+    /// 注入按键记录
+    injected: Option<Arc<InjectedKeys>>,
 }
 
 impl ActionService {
     /// 创建新的操作服务
     pub fn new() -> Self {
-        let mut key_map = HashMap::new();
-        
-        // 初始化按键映射
-        key_map.insert("Space".to_string(), VK_SPACE as u16);
-        key_map.insert("Delete".to_string(), VK_DELETE as u16);
-        key_map.insert("Escape".to_string(), VK_ESCAPE as u16);
-        key_map.insert("F".to_string(), 0x46); // F key
-        key_map.insert("1".to_string(), 0x31); // 1 key
-        key_map.insert("2".to_string(), 0x32); // 2 key
-        key_map.insert("3".to_string(), 0x33); // 3 key
-        key_map.insert("4".to_string(), 0x34); // 4 key
-        
-        Self { key_map }
+        Self::with_backend(PlatformInputBackend::default())
+    }
+}
+
+impl Default for ActionService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: InputBackend> ActionService<B> {
+    /// 使用指定的输入后端创建操作服务
+    pub fn with_backend(backend: B) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            key_map: HashMap::new(),
+            held_keys: Mutex::new(Vec::new()),
+            held_buttons: Mutex::new(Vec::new()),
+            registry: Arc::new(SequenceRegistry::new()),
+            resolver: None,
+            conditions: None,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            scheduler: PrecisionScheduler::shared(),
+            window: None,
+            tracer: None,
+            safety: None,
+            game_speed: None,
+            injected: None,
+        }
+    }
+    
+    /// 使用共享的序列注册表（通常与 `StateManager` 共享）
+    pub fn with_registry(mut self, registry: Arc<SequenceRegistry>) -> Self {
+        self.registry = registry;
+        self
+    }
+    
+    /// 设置 `Call` 使用的序列解析器（通常是 `ModeManager`）
+    pub fn with_resolver(mut self, resolver: Arc<dyn SequenceResolver>) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+    
+    /// 设置 `Loop` / `If` 使用的条件来源
+    pub fn with_conditions(mut self, conditions: Arc<dyn ConditionSource>) -> Self {
+        self.conditions = Some(conditions);
+        self
+    }
+    
+    /// 设置最大序列调用深度
+    pub fn with_max_call_depth(mut self, depth: usize) -> Self {
+        self.max_call_depth = depth;
+        self
+    }
+    
+    /// 使用指定的调度器（默认使用进程共享的调度器）
+    pub fn with_scheduler(mut self, scheduler: Arc<PrecisionScheduler>) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+    
+    /// 设置目标窗口（通常是 `WindowService::shared_target_window`）
+    pub fn with_target_window(mut self, window: Arc<dyn TargetWindow>) -> Self {
+        self.window = Some(window);
+        self
+    }
+    
+    /// 启用执行追踪
+    pub fn with_tracer(mut self, tracer: Arc<ExecutionTracer>) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+    
+    /// 获取执行追踪器
+    pub fn tracer(&self) -> Option<Arc<ExecutionTracer>> {
+        self.tracer.clone()
+    }
+    
+    /// 启用安全保护（输入速率限制、执行时间上限和紧急停止）
+    pub fn with_safety_guard(mut self, guard: Arc<SafetyGuard>) -> Self {
+        self.safety = Some(guard);
+        self
+    }
+    
+    /// 获取安全保护
+    pub fn safety_guard(&self) -> Option<Arc<SafetyGuard>> {
+        self.safety.clone()
+    }
+    
+    /// 启用战斗速度跟踪：发送变速键时切换速度，`GameWait` 按当前速度换算
+    pub fn with_game_speed(mut self, tracker: Arc<GameSpeedTracker>) -> Self {
+        self.game_speed = Some(tracker);
+        self
+    }
+    
+    /// 获取战斗速度跟踪器
+    pub fn game_speed(&self) -> Option<Arc<GameSpeedTracker>> {
+        self.game_speed.clone()
+    }
+    
+    /// 发送按键前登记到注入按键记录（通常是 `InjectedKeys::shared`），全局监听据此忽略本程序发送的按键
+    pub fn with_injected_keys(mut self, injected: Arc<InjectedKeys>) -> Self {
+        self.injected = Some(injected);
+        self
+    }
+    
+    /// 当前战斗速度，未启用跟踪时为 1 倍速
+    pub fn current_game_speed(&self) -> GameSpeed {
+        self.game_speed.as_ref()
+            .map(|tracker| tracker.speed())
+            .unwrap_or_default()
+    }
+    
+    /// 获取序列注册表
+    pub fn registry(&self) -> Arc<SequenceRegistry> {
+        Arc::clone(&self.registry)
+    }
+    
+    /// 获取输入后端
+    pub fn backend(&self) -> &B {
+        &self.backend
     }
     
     /// 执行单个操作
+    ///
+    /// 执行失败时释放该操作已按住的输入
     pub async fn execute_action(&self, action: &ActionType) -> ActionResult<()> {
-        match action {
-            ActionType::KeyPress(key) => {
-                self.send_key_press(key).await
-            }
-            ActionType::MouseMove(x, y) => {
-                self.send_mouse_move(*x, *y).await
-            }
-            ActionType::MouseClick(button, x, y) => {
-                self.send_mouse_click(*button, *x, *y).await
-            }
-            ActionType::Wait(duration) => {
-                log::info!("等待 {:?}", duration);
-                tokio::time::sleep(*duration).await;
-                Ok(())
-            }
-        }
+        let context = SequenceContext::new(
+            Clock::Real(Arc::clone(&self.scheduler)),
+            "",
+            HumanizeConfig::default(),
+            CancellationToken::new(),
+            None,
+        );
+        let mut context = ContextGuard::new(self, context);
+        context.game_speed = self.current_game_speed();
+        self.execute_in(action, &mut context).await?;
+        context.finish();
+        Ok(())
     }
     
     /// 执行操作序列
     pub async fn execute_sequence(&self, sequence: &ActionSequence) -> ActionResult<()> {
-        log::info!("开始执行操作序列: {}", sequence.name);
-        
-        for (index, action) in sequence.actions.iter().enumerate() {
-            match self.execute_action(action).await {
-                Ok(()) => {
-                    log::debug!("操作 {} 执行成功", index + 1);
-                }
-                Err(e) => {
-                    log::error!("操作 {} 执行失败: {}", index + 1, e);
-                    return Err(e);
-                }
-            }
+        let report = self.run_sequence(sequence).await?;
+
+        if !report.held_keys.is_empty() {
+            log::warn!("操作序列 {} 结束时仍按住按键: {:?}", sequence.name, report.held_keys);
         }
-        
-        log::info!("操作序列执行完成: {}", sequence.name);
+
         Ok(())
     }
     
-    /// 发送按键操作
-    #[cfg(windows)]
-    async fn send_key_press(&self, key: &str) -> ActionResult<()> {
-        let vk_code = self.key_map.get(key)
-            .ok_or_else(|| ActionError::InvalidKey(key.to_string()))?;
-        
-        log::info!("执行按键操作: {} (VK: 0x{:02X})", key, vk_code);
-        
-        unsafe {
-            // 按下按键
-            let mut input_down = INPUT {
-                type_: INPUT_KEYBOARD,
-                u: std::mem::zeroed(),
-            };
-            *input_down.u.ki_mut() = KEYBDINPUT {
-                wVk: *vk_code,
-                wScan: 0,
-                dwFlags: 0,
-                time: 0,
-                dwExtraInfo: 0,
-            };
-            
-            // 释放按键
-            let mut input_up = INPUT {
-                type_: INPUT_KEYBOARD,
-                u: std::mem::zeroed(),
-            };
-            *input_up.u.ki_mut() = KEYBDINPUT {
-                wVk: *vk_code,
-                wScan: 0,
-                dwFlags: KEYEVENTF_KEYUP,
-                time: 0,
-                dwExtraInfo: 0,
+    /// 执行操作序列并返回执行报告
+    pub async fn run_sequence(&self, sequence: &ActionSequence) -> ActionResult<SequenceReport> {
+        let registration = self.registry.register(&sequence.name);
+        self.run_with_cancel(sequence, registration.token().clone(), None).await
+    }
+    
+    /// 在取消令牌和超时控制下执行序列
+    ///
+    /// 执行失败（包括被取消、超时和被安全保护中止）或执行中途被丢弃时，
+    /// 释放本序列仍按住的按键和鼠标按钮；正常结束时按住的输入保持不变并记录在报告中
+    async fn run_with_cancel(
+        &self,
+        sequence: &ActionSequence,
+        cancel: CancellationToken,
+        timeout: Option<Duration>,
+    ) -> ActionResult<SequenceReport> {
+        log::info!("开始执行操作序列: {}", sequence.name);
+
+        let clock = Clock::Real(Arc::clone(&self.scheduler));
+        let context = SequenceContext::new(clock, &sequence.name, sequence.humanize, cancel, timeout);
+        let mut context = ContextGuard::new(self, context);
+        if let Some(tracer) = &self.tracer {
+            context.run_id = tracer.next_run_id();
+        }
+        context.params = sequence.params.clone();
+        context.game_speed = self.current_game_speed();
+        if let Some(limit) = self.safety.as_ref().and_then(|guard| guard.max_sequence_duration()) {
+            context.limit_duration(limit);
+        }
+
+        if let Err(e) = self.execute_block(&sequence.actions, &mut context).await {
+            let e = match e {
+                ActionError::Cancelled if self.safety.as_ref().is_some_and(|guard| guard.is_engaged()) => {
+                    ActionError::EmergencyStop
+                }
+                e => e,
             };
-            
-            let inputs = [input_down, input_up];
-            let result = SendInput(
-                inputs.len() as u32,
-                inputs.as_ptr() as *mut INPUT,
-                std::mem::size_of::<INPUT>() as i32,
-            );
-            
-            if result != inputs.len() as u32 {
-                return Err(ActionError::SystemCall(format!(
-                    "SendInput failed for key: {}", key
-                )));
-            }
+            log::error!("操作序列 {} 执行失败: {}", sequence.name, e);
+            drop(context);
+            self.report_violation(&sequence.name, &e);
+            return Err(e);
         }
-        
-        Ok(())
+
+        let context = context.finish();
+        let timing = TimingStats::from_samples(&context.samples);
+        log::info!(
+            "操作序列执行完成: {} (平均偏差 {:?}, 最大偏差 {:?})",
+            sequence.name, timing.mean_drift, timing.max_drift
+        );
+        Ok(SequenceReport {
+            name: sequence.name.clone(),
+            actions_executed: context.executed,
+            held_keys: context.held_keys,
+            held_buttons: context.held_buttons,
+            elapsed: context.origin.elapsed(),
+            timeline: context.samples,
+            timing,
+        })
     }
     
-    /// 发送鼠标移动操作
-    #[cfg(windows)]
-    async fn send_mouse_move(&self, x: i32, y: i32) -> ActionResult<()> {
-        log::info!("执行鼠标移动: ({}, {})", x, y);
-        
-        unsafe {
-            let result = SetCursorPos(x, y);
-            if result == 0 {
-                return Err(ActionError::SystemCall(format!(
-                    "SetCursorPos failed for position: ({}, {})", x, y
-                )));
+    /// 模拟执行操作序列
+    ///
+    /// 使用虚拟时钟，不发送任何输入，返回按时间排列的事件（拖拽、重复和调用均已展开）。
+    /// `Loop` / `If` 的条件仍从已配置的条件来源读取；战斗速度从当前速度开始，
+    /// 只在模拟中随变速键切换，不影响跟踪器
+    pub async fn simulate(&self, sequence: &ActionSequence) -> ActionResult<Vec<TimelineEntry>> {
+        let mut context = SequenceContext::new(
+            Clock::Virtual(Vec::new()),
+            &sequence.name,
+            sequence.humanize,
+            CancellationToken::new(),
+            None,
+        );
+        context.game_speed = self.current_game_speed();
+        context.params = sequence.params.clone();
+
+        self.execute_block(&sequence.actions, &mut context).await?;
+
+        match context.clock {
+            Clock::Virtual(timeline) => Ok(timeline),
+            Clock::Real(_) => unreachable!("模拟执行使用虚拟时钟"),
+        }
+    }
+    
+    /// 依次执行一组操作
+    ///
+    /// 控制流操作会递归调用此方法，因此返回装箱的 Future
+    fn execute_block<'a>(
+        &'a self,
+        actions: &'a [ActionType],
+        context: &'a mut SequenceContext,
+    ) -> Pin<Box<dyn Future<Output = ActionResult<()>> + Send + 'a>> {
+        Box::pin(async move {
+            for (index, action) in actions.iter().enumerate() {
+                context.check()?;
+                self.execute_in(action, context).await.inspect_err(|e| {
+                    log::debug!("操作 {} 执行失败: {}", index + 1, e);
+                })?;
+            }
+            Ok(())
+        })
+    }
+    
+    /// 计算条件
+    async fn evaluate(&self, condition: &Condition) -> ActionResult<bool> {
+        let conditions = self.conditions.as_ref()
+            .ok_or_else(|| ActionError::SequenceError("未配置条件来源".to_string()))?;
+        conditions.evaluate_async(condition).await
+    }
+    
+    /// 在序列上下文中执行单个操作
+    async fn execute_in(&self, action: &ActionType, context: &mut SequenceContext) -> ActionResult<()> {
+        match action {
+            ActionType::Repeat { count, body } => {
+                if *count > MAX_LOOP_ITERATIONS {
+                    return Err(ActionError::InvalidParameter(format!(
+                        "重复次数超过上限 {}: {}", MAX_LOOP_ITERATIONS, count
+                    )));
+                }
+                for _ in 0..*count {
+                    context.check()?;
+                    self.execute_block(body, context).await?;
+                    tokio::task::yield_now().await;
+                }
+                return Ok(());
+            }
+            ActionType::Loop { until, max_iterations, body } => {
+                if *max_iterations == 0 {
+                    return Err(ActionError::InvalidParameter("循环最大次数不能为 0".to_string()));
+                }
+                let limit = (*max_iterations).min(MAX_LOOP_ITERATIONS);
+                for _ in 0..limit {
+                    context.check()?;
+                    let done = self.evaluate(until).await?;
+                    context.resync();
+                    if done {
+                        return Ok(());
+                    }
+                    self.execute_block(body, context).await?;
+                    // 让出执行权，空循环体也能及时响应取消
+                    tokio::task::yield_now().await;
+                }
+                if self.evaluate(until).await? {
+                    return Ok(());
+                }
+                return Err(ActionError::SequenceError(format!("循环超过最大次数: {}", limit)));
             }
+            ActionType::Call(name) => {
+                if context.call_depth >= self.max_call_depth {
+                    return Err(ActionError::SequenceError(format!(
+                        "序列调用深度超过限制 {}: {}", self.max_call_depth, name
+                    )));
+                }
+                let resolver = self.resolver.as_ref()
+                    .ok_or_else(|| ActionError::SequenceError("未配置序列解析器".to_string()))?;
+                let name = expand_params(name, &context.params)?;
+                let sequence = resolver.resolve_sequence_async(&name).await?;
+
+                log::debug!("调用序列: {}", name);
+                // 被调用的序列使用自己的拟人化设置和参数默认值，调用方的同名参数优先
+                let humanize = context.humanizer.replace_config(sequence.humanize);
+                let mut params = sequence.params.clone();
+                params.extend(context.params.iter().map(|(name, value)| (name.clone(), *value)));
+                let caller_params = std::mem::replace(&mut context.params, params);
+                let caller = std::mem::replace(&mut context.sequence, sequence.name.clone());
+                context.call_depth += 1;
+                let result = self.execute_block(&sequence.actions, context).await;
+                context.call_depth -= 1;
+                context.sequence = caller;
+                context.params = caller_params;
+                context.humanizer.replace_config(humanize);
+                return result;
+            }
+            ActionType::If { condition, then, otherwise } => {
+                let branch = if self.evaluate(condition).await? { then } else { otherwise };
+                context.resync();
+                return self.execute_block(branch, context).await;
+            }
+            _ => {}
         }
-        
+
+        // 模拟执行不写入追踪
+        let tracer = self.tracer.as_ref().filter(|_| !context.is_virtual());
+        let scheduled = context.cursor - context.origin;
+        let actual = context.origin.elapsed();
+        let timestamp = tracer.map(|_| Local::now());
+        if tracer.is_some() {
+            context.trace_events = Some(Vec::new());
+        }
+        if !matches!(action, ActionType::Wait(_) | ActionType::GameWait(_)) {
+            context.record();
+        }
+
+        let result = self.execute_leaf(action, context).await;
+
+        if let (Some(tracer), Some(timestamp)) = (tracer, timestamp) {
+            tracer.record(TraceEntry {
+                run_id: context.run_id,
+                sequence: context.sequence.clone(),
+                index: context.executed,
+                action: action.clone(),
+                scheduled_us: scheduled.as_micros() as u64,
+                actual_us: actual.as_micros() as u64,
+                timestamp,
+                events: context.trace_events.take().unwrap_or_default(),
+                error: result.as_ref().err().map(|e| e.to_string()),
+            });
+        }
+
+        result?;
+        context.executed += 1;
         Ok(())
     }
     
-    /// 发送鼠标点击操作
-    #[cfg(windows)]
-    async fn send_mouse_click(&self, button: MouseButton, x: i32, y: i32) -> ActionResult<()> {
-        log::info!("执行鼠标点击: {:?} at ({}, {})", button, x, y);
-        
-        // 先移动鼠标到指定位置
-        self.send_mouse_move(x, y).await?;
-        
-        // 等待一小段时间确保鼠标移动完成
-        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
-        
-        let (down_flag, up_flag) = match button {
-            MouseButton::Left => (MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP),
-            MouseButton::Right => (MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP),
-            MouseButton::Middle => (MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP),
-        };
-        
-        unsafe {
-            // 按下鼠标按钮
-            let mut input_down = INPUT {
-                type_: INPUT_MOUSE,
-                u: std::mem::zeroed(),
-            };
-            *input_down.u.mi_mut() = MOUSEINPUT {
-                dx: 0,
-                dy: 0,
-                mouseData: 0,
-                dwFlags: down_flag,
-                time: 0,
-                dwExtraInfo: 0,
-            };
-            
-            // 释放鼠标按钮
-            let mut input_up = INPUT {
-                type_: INPUT_MOUSE,
-                u: std::mem::zeroed(),
-            };
-            *input_up.u.mi_mut() = MOUSEINPUT {
-                dx: 0,
-                dy: 0,
-                mouseData: 0,
-                dwFlags: up_flag,
-                time: 0,
-                dwExtraInfo: 0,
-            };
-            
-            let inputs = [input_down, input_up];
-            let result = SendInput(
-                inputs.len() as u32,
-                inputs.as_ptr() as *mut INPUT,
-                std::mem::size_of::<INPUT>() as i32,
-            );
-            
-            if result != inputs.len() as u32 {
-                return Err(ActionError::SystemCall(format!(
-                    "SendInput failed for mouse click: {:?}", button
-                )));
+    /// 执行不含控制流的单个操作
+    async fn execute_leaf(&self, action: &ActionType, context: &mut SequenceContext) -> ActionResult<()> {
+        match action {
+            ActionType::KeyPress(key) => {
+                let key = expand_params(key, &context.params)?;
+                self.send_key_press(&key, context).await
+            }
+            ActionType::KeyDown(key) => {
+                let key = expand_params(key, &context.params)?;
+                self.send_key_down(&key, context).await
+            }
+            ActionType::KeyUp(key) => {
+                let key = expand_params(key, &context.params)?;
+                self.send_key_up(&key, context).await
+            }
+            ActionType::KeyHold(key, duration) => {
+                let key = expand_params(key, &context.params)?;
+                self.send_key_down(&key, context).await?;
+                let duration = context.humanizer.wait(*duration);
+                context.sleep(duration).await?;
+                context.record();
+                self.send_key_up(&key, context).await
+            }
+            ActionType::MouseMove(x, y) => {
+                self.send_mouse_move(*x, *y, context).await
+            }
+            ActionType::MouseMoveRelative(dx, dy) => {
+                log::info!("执行鼠标相对移动: ({}, {})", dx, dy);
+                self.emit(&[InputEvent::MouseMoveRelative(*dx, *dy)], context)
+            }
+            ActionType::MouseClick(button, x, y) => {
+                let (x, y) = context.humanizer.position((*x, *y));
+                self.send_mouse_click(*button, x, y, context).await
+            }
+            ActionType::MouseMoveTo(position) => {
+                let (x, y) = self.resolve_position(position)?;
+                self.send_mouse_move(x, y, context).await
+            }
+            ActionType::MouseClickAt(button, position) => {
+                let (x, y) = context.humanizer.position(self.resolve_position(position)?);
+                self.send_mouse_click(*button, x, y, context).await
+            }
+            ActionType::Scroll { dx, dy } => {
+                log::info!("执行滚轮操作: ({}, {})", dx, dy);
+                // 脚本中的滚动格数不受限制，超出范围时取极值
+                let delta = (dx.saturating_mul(WHEEL_DELTA), dy.saturating_mul(WHEEL_DELTA));
+                self.emit(&[InputEvent::MouseWheel(delta.0, delta.1)], context)
+            }
+            ActionType::MouseDrag { button, from, to, duration, steps, easing } => {
+                self.send_mouse_drag(*button, *from, *to, *duration, *steps, *easing, context).await
+            }
+            ActionType::MouseDragAt { button, from, to, duration, steps, easing } => {
+                let from = self.resolve_position(from)?;
+                let to = self.resolve_position(to)?;
+                self.send_mouse_drag(*button, from, to, *duration, *steps, *easing, context).await
             }
+            ActionType::Wait(duration) => {
+                let duration = context.humanizer.wait(*duration);
+                log::info!("等待 {:?}", duration);
+                context.sleep(duration).await
+            }
+            ActionType::GameWait(duration) => {
+                let speed = match &self.game_speed {
+                    Some(tracker) if !context.is_virtual() => tracker.speed(),
+                    _ => context.game_speed,
+                };
+                let real = context.humanizer.wait(speed.to_real(*duration));
+                log::info!("等待游戏时间 {:?}（{}，实际 {:?}）", duration, speed, real);
+                context.sleep(real).await
+            }
+            ActionType::Repeat { .. }
+            | ActionType::Loop { .. }
+            | ActionType::Call(_)
+            | ActionType::If { .. } => unreachable!("控制流操作由 execute_in 处理"),
         }
-        
-        Ok(())
     }
     
-    /// 非Windows平台的按键操作实现（占位符）
-    #[cfg(not(windows))]
-    async fn send_key_press(&self, key: &str) -> ActionResult<()> {
-        log::warn!("按键操作在非Windows平台上不支持: {}", key);
-        Err(ActionError::UnsupportedPlatform("Key press not supported on this platform".to_string()))
+    /// 发送按键操作
+    ///
+    /// 支持组合键：按顺序按下修饰键，释放时顺序相反
+    async fn send_key_press(&self, key: &str, context: &mut SequenceContext) -> ActionResult<()> {
+        let (modifiers, vk_code) = self.resolve_combo(key)?;
+
+        log::info!("执行按键操作: {} (VK: 0x{:02X})", key, vk_code);
+
+        let mut events = Vec::with_capacity(modifiers.len() * 2 + 2);
+        events.extend(modifiers.iter().map(|vk| InputEvent::KeyDown(*vk)));
+        events.push(InputEvent::KeyDown(vk_code));
+        events.push(InputEvent::KeyUp(vk_code));
+        events.extend(modifiers.iter().rev().map(|vk| InputEvent::KeyUp(*vk)));
+
+        // 按下和释放在同一批次中发送
+        self.send_tracked(&events, context)
     }
     
-    /// 非Windows平台的鼠标移动实现（占位符）
-    #[cfg(not(windows))]
-    async fn send_mouse_move(&self, x: i32, y: i32) -> ActionResult<()> {
-        log::warn!("鼠标移动在非Windows平台上不支持: ({}, {})", x, y);
-        Err(ActionError::UnsupportedPlatform("Mouse move not supported on this platform".to_string()))
+    /// 按下按键（组合键先按修饰键）
+    async fn send_key_down(&self, key: &str, context: &mut SequenceContext) -> ActionResult<()> {
+        let (modifiers, vk_code) = self.resolve_combo(key)?;
+
+        log::info!("按下按键: {} (VK: 0x{:02X})", key, vk_code);
+
+        let events: Vec<InputEvent> = modifiers.iter()
+            .chain(std::iter::once(&vk_code))
+            .map(|vk| InputEvent::KeyDown(*vk))
+            .collect();
+        self.send_tracked(&events, context)
     }
     
-    /// 非Windows平台的鼠标点击实现（占位符）
-    #[cfg(not(windows))]
-    async fn send_mouse_click(&self, button: MouseButton, x: i32, y: i32) -> ActionResult<()> {
-        log::warn!("鼠标点击在非Windows平台上不支持: {:?} at ({}, {})", button, x, y);
-        Err(ActionError::UnsupportedPlatform("Mouse click not supported on this platform".to_string()))
+    /// 释放按键（组合键最后释放修饰键）
+    async fn send_key_up(&self, key: &str, context: &mut SequenceContext) -> ActionResult<()> {
+        let (modifiers, vk_code) = self.resolve_combo(key)?;
+
+        log::info!("释放按键: {} (VK: 0x{:02X})", key, vk_code);
+
+        let events: Vec<InputEvent> = std::iter::once(&vk_code)
+            .chain(modifiers.iter().rev())
+            .map(|vk| InputEvent::KeyUp(*vk))
+            .collect();
+        self.send_tracked(&events, context)
     }
     
-    /// 获取当前鼠标位置
-    #[cfg(windows)]
-    pub fn get_cursor_position(&self) -> ActionResult<(i32, i32)> {
-        unsafe {
-            let mut point = winapi::shared::windef::POINT { x: 0, y: 0 };
-            let result = GetCursorPos(&mut point);
-            if result == 0 {
-                return Err(ActionError::SystemCall("GetCursorPos failed".to_string()));
+    /// 发送事件，模拟执行时只记录到时间线
+    fn emit(&self, events: &[InputEvent], context: &mut SequenceContext) -> ActionResult<()> {
+        if let Clock::Virtual(timeline) = &mut context.clock {
+            let offset = context.cursor - context.origin;
+            timeline.extend(events.iter().map(|event| TimelineEntry { offset, event: *event }));
+            if let Some(tracker) = &self.game_speed {
+                if tracker.toggles_in(events) % 2 == 1 {
+                    context.game_speed = context.game_speed.toggled();
+                }
             }
-            Ok((point.x, point.y))
+            return Ok(());
         }
+
+        if let Some(guard) = &self.safety {
+            guard.admit(events)?;
+        }
+        if let Some(injected) = &self.injected {
+            injected.record(events);
+        }
+        self.backend.send_events(events)?;
+        if let Some(trace) = context.trace_events.as_mut() {
+            trace.extend_from_slice(events);
+        }
+        if let Some(tracker) = &self.game_speed {
+            tracker.observe(events);
+            context.game_speed = tracker.speed();
+        }
+        Ok(())
     }
     
-    /// 非Windows平台获取鼠标位置（占位符）
-    #[cfg(not(windows))]
-    pub fn get_cursor_position(&self) -> ActionResult<(i32, i32)> {
-        Err(ActionError::UnsupportedPlatform("Get cursor position not supported on this platform".to_string()))
-    }
-    
-    /// 添加自定义按键映射
-    pub fn add_key_mapping(&mut self, key: String, vk_code: u16) {
-        self.key_map.insert(key, vk_code);
+    /// 发送事件并更新按住的按键和鼠标按钮记录
+    ///
+    /// 按下在发送前就记录，批量发送中途失败时已发出的按下也能被释放（多余的释放是无害的）；
+    /// 释放在发送成功后才记录。模拟执行时只更新本序列的记录
+    fn send_tracked(&self, events: &[InputEvent], context: &mut SequenceContext) -> ActionResult<()> {
+        self.track(events, context, true);
+        self.emit(events, context)?;
+        self.track(events, context, false);
+        Ok(())
     }
     
-    /// 获取支持的按键列表
+    /// 按事件顺序更新按住的按键和鼠标按钮记录，`downs_only` 时只记录按下
+    fn track(&self, events: &[InputEvent], context: &mut SequenceContext, downs_only: bool) {
+        let (mut global_keys, mut global_buttons) = if context.is_virtual() {
+            (None, None)
+        } else {
+            (self.held_keys.lock().ok(), self.held_buttons.lock().ok())
+        };
+        for event in events {
+            match event {
+                InputEvent::KeyDown(vk) => {
+                    track_down(&mut context.held_keys, *vk);
+                    if let Some(global) = global_keys.as_mut() {
+                        track_down(global, *vk);
+                    }
+                }
+                InputEvent::KeyUp(vk) if !downs_only => {
+                    context.held_keys.retain(|held| held != vk);
+                    if let Some(global) = global_keys.as_mut() {
+                        global.retain(|held| held != vk);
+                    }
+                }
+                InputEvent::MouseDown(button) => {
+                    track_down(&mut context.held_buttons, *button);
+                    if let Some(global) = global_buttons.as_mut() {
+                        track_down(global, *button);
+                    }
+                }
+                InputEvent::MouseUp(button) if !downs_only => {
+                    context.held_buttons.retain(|held| held != button);
+                    if let Some(global) = global_buttons.as_mut() {
+                        global.retain(|held| held != button);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    
+    /// 获取当前被按住的按键（虚拟键码，按按下顺序）
+    pub fn held_keys(&self) -> Vec<u16> {
+        self.held_keys.lock()
+            .map(|held| held.clone())
+            .unwrap_or_default()
+    }
+    
+    /// 获取当前被按住的鼠标按钮
+    pub fn held_buttons(&self) -> Vec<MouseButton> {
+        self.held_buttons.lock()
+            .map(|held| held.clone())
+            .unwrap_or_default()
+    }
+    
+    /// 释放序列上下文中仍按住的按键（逆序）和鼠标按钮
+    fn release_context(&self, context: &mut SequenceContext) {
+        if context.held_keys.is_empty() && context.held_buttons.is_empty() {
+            return;
+        }
+
+        log::info!("释放序列仍按住的输入: 按键 {:?}, 鼠标按钮 {:?}", context.held_keys, context.held_buttons);
+
+        let events: Vec<InputEvent> = context.held_buttons.iter()
+            .map(|button| InputEvent::MouseUp(*button))
+            .chain(context.held_keys.iter().rev().map(|vk| InputEvent::KeyUp(*vk)))
+            .collect();
+
+        if let Err(e) = self.send_tracked(&events, context) {
+            // 批量发送失败时逐个重试，尽量释放每一个输入
+            log::error!("释放按住的输入失败: {}，逐个重试", e);
+            for event in &events {
+                if let Err(e) = self.send_tracked(std::slice::from_ref(event), context) {
+                    log::error!("释放 {:?} 失败: {}", event, e);
+                }
+            }
+        }
+    }
+    
+    /// 按相反顺序释放所有被按住的按键
+    pub fn release_held_keys(&self) -> ActionResult<()> {
+        let held = take_held(&self.held_keys, true);
+        if held.is_empty() {
+            return Ok(());
+        }
+
+        log::info!("释放 {} 个被按住的按键", held.len());
+        let events: Vec<InputEvent> = held.iter().rev().map(|vk| InputEvent::KeyUp(*vk)).collect();
+        self.send_release(&events)
+    }
+    
+    /// 释放所有被按住的鼠标按钮和按键（按键按相反顺序）
+    pub fn release_all_held(&self) -> ActionResult<()> {
+        self.release_held(true)
+    }
+    
+    /// 释放所有被按住的输入
+    ///
+    /// 非阻塞时不等待记录的锁（用于 panic 钩子，panic 的线程可能正持有锁）
+    fn release_held(&self, blocking: bool) -> ActionResult<()> {
+        let buttons = take_held(&self.held_buttons, blocking);
+        let keys = take_held(&self.held_keys, blocking);
+        if buttons.is_empty() && keys.is_empty() {
+            return Ok(());
+        }
+
+        log::info!("释放所有按住的输入: 按键 {:?}, 鼠标按钮 {:?}", keys, buttons);
+        let events: Vec<InputEvent> = buttons.iter()
+            .map(|button| InputEvent::MouseUp(*button))
+            .chain(keys.iter().rev().map(|vk| InputEvent::KeyUp(*vk)))
+            .collect();
+        self.send_release(&events)
+    }
+    
+    /// 发送释放事件，批量发送失败时逐个重试，返回第一个错误
+    fn send_release(&self, events: &[InputEvent]) -> ActionResult<()> {
+        if let Some(injected) = &self.injected {
+            injected.record(events);
+        }
+        let Err(batch_error) = self.backend.send_events(events) else {
+            return Ok(());
+        };
+
+        log::error!("释放按住的输入失败: {}，逐个重试", batch_error);
+        let mut first_error = None;
+        for event in events {
+            if let Err(e) = self.backend.send_event(*event) {
+                log::error!("释放 {:?} 失败: {}", event, e);
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+    
+    /// 紧急停止
+    ///
+    /// 取消所有正在执行的序列并释放所有按住的输入。启用安全保护时进入紧急停止状态，
+    /// 在 `SafetyGuard::reset` 之前拒绝新的输入。返回被取消的序列数量
+    pub fn emergency_stop(&self) -> usize {
+        if let Some(guard) = &self.safety {
+            guard.engage();
+        }
+
+        let cancelled = self.registry.cancel_all();
+        if let Err(e) = self.release_all_held() {
+            log::error!("紧急停止时释放按住的输入失败: {}", e);
+        }
+
+        if let Some(guard) = &self.safety {
+            guard.report(SafetyViolation::EmergencyStop);
+        }
+        log::warn!("紧急停止: 已取消 {} 个序列", cancelled);
+        cancelled
+    }
+    
+    /// 将安全保护中止序列的错误发布为违规通知
+    fn report_violation(&self, sequence: &str, error: &ActionError) {
+        let Some(guard) = &self.safety else {
+            return;
+        };
+        let violation = match error {
+            ActionError::RateLimitExceeded(limit) => SafetyViolation::RateLimit {
+                sequence: sequence.to_string(),
+                limit: *limit,
+            },
+            ActionError::SequenceDurationExceeded(limit) => SafetyViolation::DurationExceeded {
+                sequence: sequence.to_string(),
+                limit: *limit,
+            },
+            _ => return,
+        };
+        guard.report(violation);
+    }
+    
+    /// 发送鼠标移动操作
+    async fn send_mouse_move(&self, x: i32, y: i32, context: &mut SequenceContext) -> ActionResult<()> {
+        log::info!("执行鼠标移动: ({}, {})", x, y);
+        self.emit(&[InputEvent::MouseMove(x, y)], context)
+    }
+    
+    /// 发送鼠标点击操作
+    async fn send_mouse_click(
+        &self,
+        button: MouseButton,
+        x: i32,
+        y: i32,
+        context: &mut SequenceContext,
+    ) -> ActionResult<()> {
+        log::info!("执行鼠标点击: {:?} at ({}, {})", button, x, y);
+        
+        // 先移动鼠标到指定位置
+        self.send_mouse_move(x, y, context).await?;
+
+        // 等待一小段时间确保鼠标移动完成
+        context.sleep(Duration::from_millis(10)).await?;
+
+        // 按下和释放在同一批次中发送，释放失败时按钮会被松开
+        self.send_tracked(&[
+            InputEvent::MouseDown(button),
+            InputEvent::MouseUp(button),
+        ], context)
+    }
+    
+    /// 发送鼠标拖拽操作
+    ///
+    /// 每一步按绝对时间点调度，避免多步累积误差
+    #[allow(clippy::too_many_arguments)]
+    async fn send_mouse_drag(
+        &self,
+        button: MouseButton,
+        from: (i32, i32),
+        to: (i32, i32),
+        duration: Duration,
+        steps: u32,
+        easing: Easing,
+        context: &mut SequenceContext,
+    ) -> ActionResult<()> {
+        log::info!("执行鼠标拖拽: {:?} {:?} -> {:?} ({:?}, {} 步)", button, from, to, duration, steps);
+        if duration > MAX_DRAG_DURATION {
+            return Err(ActionError::InvalidParameter(format!(
+                "拖拽时长超过上限 {:?}: {:?}", MAX_DRAG_DURATION, duration
+            )));
+        }
+
+        let path = context.humanizer.drag_path(drag_path(from, to, steps, easing));
+
+        self.send_mouse_move(from.0, from.1, context).await?;
+        self.send_tracked(&[InputEvent::MouseDown(button)], context)?;
+
+        // 拖拽时长从按钮实际按下时开始计算
+        context.resync();
+        let start = context.cursor;
+        let step_count = path.len();
+        for (index, (x, y)) in path.into_iter().enumerate() {
+            let deadline = start + duration.mul_f64((index + 1) as f64 / step_count as f64);
+            context.sleep_until(deadline).await?;
+            context.record();
+            self.emit(&[InputEvent::MouseMove(x, y)], context)?;
+        }
+
+        self.send_tracked(&[InputEvent::MouseUp(button)], context)
+    }
+    
+    /// 将按键名称解析为虚拟键码
+    ///
+    /// 先查找自定义映射，再查找内置按键表（不区分大小写）。只能用于热键触发的按键（鼠标侧键）无效
+    pub fn resolve_key(&self, key: &str) -> ActionResult<u16> {
+        if let Some(vk_code) = self.key_map.get(key) {
+            return Ok(*vk_code);
+        }
+        let parsed: Key = key.parse()?;
+        if parsed.is_trigger_only() {
+            return Err(ActionError::InvalidKey(format!("{} 只能用于热键触发", key)));
+        }
+        Ok(parsed.vk_code())
+    }
+    
+    /// 将按键或组合键解析为（修饰键虚拟键码列表, 主按键虚拟键码）
+    pub fn resolve_combo(&self, key: &str) -> ActionResult<(Vec<u16>, u16)> {
+        if let Some(vk_code) = self.key_map.get(key) {
+            return Ok((Vec::new(), *vk_code));
+        }
+
+        let combo: KeyCombo = key.parse()?;
+        if combo.key().is_trigger_only() {
+            return Err(ActionError::InvalidKey(format!("{} 只能用于热键触发", key)));
+        }
+        let modifiers = combo.modifiers().iter().map(|modifier| modifier.vk_code()).collect();
+        Ok((modifiers, combo.key().vk_code()))
+    }
+    
+    /// 将位置转换为屏幕坐标
+    ///
+    /// 窗口坐标系在未设置或未锁定目标窗口时返回 `CoordinateError`
+    pub fn resolve_position(&self, position: &Position) -> ActionResult<(i32, i32)> {
+        let window = self.window.as_ref().and_then(|window| window.target_window());
+        position.to_screen(window.as_ref())
+    }
+    
+    /// 获取当前鼠标位置
+    pub fn get_cursor_position(&self) -> ActionResult<(i32, i32)> {
+        self.backend.cursor_position()
+    }
+    
+    /// 添加自定义按键映射
+    pub fn add_key_mapping(&mut self, key: String, vk_code: u16) {
+        self.key_map.insert(key, vk_code);
+    }
+    
+    /// 获取支持的按键列表
     pub fn get_supported_keys(&self) -> Vec<String> {
-        self.key_map.keys().cloned().collect()
+        Key::all().iter()
+            .map(|key| key.to_string())
+            .chain(self.key_map.keys().cloned())
+            .collect()
+    }
+}
+
+impl<B: InputBackend> Drop for ActionService<B> {
+    /// 服务释放时松开所有仍被按住的输入
+    fn drop(&mut self) {
+        if let Err(e) = self.release_held(true) {
+            log::error!("释放按住的输入失败: {}", e);
+        }
+    }
+}
+
+impl<B: InputBackend + 'static> ActionService<B> {
+    /// 安装 panic 钩子：任意线程 panic 时先释放所有被按住的输入，再调用之前的钩子
+    ///
+    /// 钩子只持有弱引用，不会延长服务的生命周期
+    pub fn install_panic_hook(self: &Arc<Self>) {
+        let service = Arc::downgrade(self);
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if let Some(service) = service.upgrade() {
+                if let Err(e) = service.release_held(false) {
+                    log::error!("panic 时释放按住的输入失败: {}", e);
+                }
+            }
+            previous(info);
+        }));
+    }
+    
+    /// 在后台执行操作序列
+    ///
+    /// 返回的句柄可以取消执行或等待结果；`timeout` 为整个序列的最长执行时间
+    pub fn spawn_sequence(self: &Arc<Self>, sequence: ActionSequence, timeout: Option<Duration>) -> SequenceHandle {
+        let registration = self.registry.register(&sequence.name);
+        let id = registration.id();
+        let cancel = registration.token().clone();
+
+        let service = Arc::clone(self);
+        let task_cancel = cancel.clone();
+        let task = tokio::spawn(async move {
+            // 任务结束时注销
+            let _registration = registration;
+            service.run_with_cancel(&sequence, task_cancel, timeout).await
+        });
+
+        SequenceHandle { id, cancel, task }
+    }
+}
+
+/// 取出并清空按住的输入记录，锁中毒时仍然取出；非阻塞且锁被占用时返回空
+fn take_held<T>(held: &Mutex<Vec<T>>, blocking: bool) -> Vec<T> {
+    let guard = if blocking {
+        held.lock()
+    } else {
+        match held.try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(TryLockError::Poisoned(poisoned)) => Err(poisoned),
+            Err(TryLockError::WouldBlock) => return Vec::new(),
+        }
+    };
+    std::mem::take(&mut *guard.unwrap_or_else(PoisonError::into_inner))
+}
+
+/// 记录按下的按键或按钮，重复按下不重复记录
+fn track_down<T: PartialEq>(held: &mut Vec<T>, input: T) {
+    if !held.contains(&input) {
+        held.push(input);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GameState, WindowInfo};
+    use crate::services::input_backend::RecordingInputBackend;
+    use crate::services::mode_manager::ModeManager;
+    use crate::services::sequence_flow::ConditionSnapshot;
+
+    fn recording_service() -> ActionService<RecordingInputBackend> {
+        ActionService::with_backend(RecordingInputBackend::new())
+    }
+
+    #[tokio::test]
+    async fn test_execute_sequence_event_stream() {
+        let service = recording_service();
+
+        let mut sequence = ActionSequence::new("test".to_string());
+        sequence.add_key_press("1".to_string());
+        sequence.add_wait(Duration::from_millis(5));
+        sequence.add_mouse_click(MouseButton::Left, 100, 200);
+        sequence.add_action(ActionType::MouseMove(300, 400));
+
+        service.execute_sequence(&sequence).await.unwrap();
+
+        assert_eq!(service.backend().events(), vec![
+            InputEvent::KeyDown(0x31),
+            InputEvent::KeyUp(0x31),
+            InputEvent::MouseMove(100, 200),
+            InputEvent::MouseDown(MouseButton::Left),
+            InputEvent::MouseUp(MouseButton::Left),
+            InputEvent::MouseMove(300, 400),
+        ]);
+        assert_eq!(service.get_cursor_position().unwrap(), (300, 400));
+    }
+
+    #[tokio::test]
+    async fn test_wait_is_reflected_in_timestamps() {
+        let service = recording_service();
+
+        let mut sequence = ActionSequence::new("wait".to_string());
+        sequence.add_key_press("Space".to_string());
+        sequence.add_wait(Duration::from_millis(20));
+        sequence.add_key_press("Escape".to_string());
+
+        service.execute_sequence(&sequence).await.unwrap();
+
+        let recorded = service.backend().recorded_events();
+        assert_eq!(recorded.len(), 4);
+        assert!(recorded[2].timestamp - recorded[1].timestamp >= Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn test_waits_follow_absolute_timeline() {
+        let service = recording_service();
+        let frame = Duration::from_nanos(1_000_000_000 / 30);
+
+        let mut sequence = ActionSequence::new("frames".to_string());
+        for _ in 0..3 {
+            sequence.add_key_press("A".to_string());
+            sequence.add_wait(frame);
+        }
+        sequence.add_key_press("B".to_string());
+
+        let report = service.run_sequence(&sequence).await.unwrap();
+
+        assert_eq!(report.timeline.len(), 4);
+        for (index, sample) in report.timeline.iter().enumerate() {
+            // 计划时间不受执行耗时影响
+            assert_eq!(sample.scheduled, frame * index as u32);
+            assert!(sample.actual >= sample.scheduled);
+        }
+        assert_eq!(report.timing.samples, 4);
+        assert!(report.timing.max_drift < Duration::from_millis(30));
+    }
+
+    #[tokio::test]
+    async fn test_humanized_sequence_is_reproducible() {
+        let mut sequence = ActionSequence::new("humanized".to_string());
+        sequence.humanize = HumanizeConfig {
+            enabled: true,
+            wait_jitter: Duration::from_millis(2),
+            position_jitter: 5,
+            drag_jitter: 0,
+            seed: Some(2024),
+        };
+        for _ in 0..5 {
+            sequence.add_mouse_click(MouseButton::Left, 100, 100);
+            sequence.add_wait(Duration::from_millis(3));
+        }
+
+        let first = recording_service();
+        let report = first.run_sequence(&sequence).await.unwrap();
+        let second = recording_service();
+        second.run_sequence(&sequence).await.unwrap();
+
+        assert_eq!(first.backend().events(), second.backend().events());
+
+        let positions: Vec<InputEvent> = first.backend().events().into_iter()
+            .filter(|event| matches!(event, InputEvent::MouseMove(..)))
+            .collect();
+        assert_eq!(positions.len(), 5);
+        assert!(positions.iter().all(|event| matches!(
+            event,
+            InputEvent::MouseMove(x, y) if (95..=105).contains(x) && (95..=105).contains(y)
+        )));
+        assert!(positions.iter().any(|event| *event != InputEvent::MouseMove(100, 100)));
+
+        // 点击后的 10 ms 等待加上 5 次有界偏移的等待
+        let scheduled = report.timeline.last().unwrap().scheduled;
+        assert!(scheduled >= Duration::from_millis(4 * 11));
+        assert!(scheduled <= Duration::from_millis(4 * 15));
+    }
+
+    #[tokio::test]
+    async fn test_simulate_expands_timeline_without_input() {
+        let mut skill = ActionSequence::new("skill".to_string());
+        skill.add_key_press("E".to_string());
+
+        let mut sequences = HashMap::new();
+        sequences.insert("skill".to_string(), skill);
+        let service = recording_service().with_resolver(Arc::new(sequences));
+
+        let mut sequence = ActionSequence::new("preview".to_string());
+        sequence.add_repeat(2, vec![
+            ActionType::KeyPress("Q".to_string()),
+            ActionType::Wait(Duration::from_secs(1)),
+        ]);
+        sequence.add_mouse_drag(MouseButton::Left, (0, 0), (30, 0), Duration::from_millis(300), 3, Easing::Linear);
+        sequence.add_call("skill".to_string());
+        sequence.add_key_down("Shift".to_string());
+
+        let start = Instant::now();
+        let timeline = service.simulate(&sequence).await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(500));
+        assert!(service.backend().events().is_empty());
+        assert!(service.held_keys().is_empty());
+
+        let ms = Duration::from_millis;
+        let expected = vec![
+            (ms(0), InputEvent::KeyDown(0x51)),
+            (ms(0), InputEvent::KeyUp(0x51)),
+            (ms(1000), InputEvent::KeyDown(0x51)),
+            (ms(1000), InputEvent::KeyUp(0x51)),
+            (ms(2000), InputEvent::MouseMove(0, 0)),
+            (ms(2000), InputEvent::MouseDown(MouseButton::Left)),
+            (ms(2100), InputEvent::MouseMove(10, 0)),
+            (ms(2200), InputEvent::MouseMove(20, 0)),
+            (ms(2300), InputEvent::MouseMove(30, 0)),
+            (ms(2300), InputEvent::MouseUp(MouseButton::Left)),
+            (ms(2300), InputEvent::KeyDown(0x45)),
+            (ms(2300), InputEvent::KeyUp(0x45)),
+            (ms(2300), InputEvent::KeyDown(0x10)),
+        ];
+        let actual: Vec<(Duration, InputEvent)> = timeline.iter()
+            .map(|entry| (entry.offset, entry.event))
+            .collect();
+        assert_eq!(actual, expected);
+        assert_eq!(timeline[2].to_string(), "+ 1000.000ms KeyDown(81)");
+    }
+
+    #[tokio::test]
+    async fn test_game_wait_scaled_by_battle_speed() {
+        let tracker = Arc::new(GameSpeedTracker::new(Some("2".parse().unwrap())));
+        let service = recording_service().with_game_speed(Arc::clone(&tracker));
+
+        let mut sequence = ActionSequence::new("speed".to_string());
+        sequence.add_game_wait(Duration::from_secs(1));
+        sequence.add_key_press("2".to_string());
+        sequence.add_game_wait(Duration::from_secs(1));
+        sequence.add_wait(Duration::from_secs(1));
+        sequence.add_key_press("Q".to_string());
+
+        // 模拟执行中切换速度，不影响跟踪器
+        let timeline = service.simulate(&sequence).await.unwrap();
+        let offsets: Vec<(Duration, InputEvent)> = timeline.iter()
+            .filter(|entry| matches!(entry.event, InputEvent::KeyDown(_)))
+            .map(|entry| (entry.offset, entry.event))
+            .collect();
+        assert_eq!(offsets, vec![
+            (Duration::from_millis(1000), InputEvent::KeyDown(0x32)),
+            (Duration::from_millis(2500), InputEvent::KeyDown(0x51)),
+        ]);
+        assert_eq!(tracker.speed(), GameSpeed::Normal);
+
+        // 实际发送变速键后跟踪器切换
+        service.execute_action(&ActionType::KeyPress("2".to_string())).await.unwrap();
+        assert_eq!(tracker.speed(), GameSpeed::Double);
+
+        let timeline = service.simulate(&sequence).await.unwrap();
+        assert_eq!(timeline[0].offset, Duration::from_millis(500));
+        assert_eq!(timeline.last().unwrap().offset, Duration::from_millis(2500));
+    }
+
+    #[tokio::test]
+    async fn test_sent_keys_recorded_as_injected() {
+        let injected = Arc::new(InjectedKeys::new());
+        let service = recording_service().with_injected_keys(Arc::clone(&injected));
+
+        service.execute_action(&ActionType::KeyPress("2".to_string())).await.unwrap();
+        assert!(injected.take(&rdev::EventType::KeyPress(rdev::Key::Num2)));
+        assert!(injected.take(&rdev::EventType::KeyRelease(rdev::Key::Num2)));
+        assert!(!injected.take(&rdev::EventType::KeyPress(rdev::Key::Num2)));
+
+        // 模拟执行不发送按键
+        let mut sequence = ActionSequence::new("simulate".to_string());
+        sequence.add_key_press("Q".to_string());
+        service.simulate(&sequence).await.unwrap();
+        assert!(!injected.take(&rdev::EventType::KeyPress(rdev::Key::KeyQ)));
+    }
+
+    #[tokio::test]
+    async fn test_window_relative_coordinates() {
+        let target = Arc::new(std::sync::Mutex::new(None));
+        let service = recording_service().with_target_window(target.clone());
+
+        let mut sequence = ActionSequence::new("deploy".to_string());
+        sequence.add_action(ActionType::MouseMoveTo(Position::window_client(10, 20)));
+        sequence.add_mouse_click_at(MouseButton::Left, Position::normalized(0.5, 0.25));
+        sequence.add_mouse_drag_at(
+            MouseButton::Right,
+            Position::window_client(0, 0),
+            Position::normalized(0.5, 0.5),
+            Duration::ZERO,
+            1,
+            Easing::Linear,
+        );
+
+        // 未锁定目标窗口
+        assert!(matches!(
+            service.execute_sequence(&sequence).await,
+            Err(ActionError::CoordinateError)
+        ));
+        assert!(service.backend().events().is_empty());
+
+        *target.lock().unwrap() = Some(WindowInfo::test_window((200, 100), (800, 400)));
+
+        service.execute_sequence(&sequence).await.unwrap();
+        assert_eq!(service.backend().events(), vec![
+            InputEvent::MouseMove(210, 120),
+            InputEvent::MouseMove(600, 200),
+            InputEvent::MouseDown(MouseButton::Left),
+            InputEvent::MouseUp(MouseButton::Left),
+            InputEvent::MouseMove(200, 100),
+            InputEvent::MouseDown(MouseButton::Right),
+            InputEvent::MouseMove(600, 300),
+            InputEvent::MouseUp(MouseButton::Right),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_tracer_records_every_action() {
+        let tracer = Arc::new(ExecutionTracer::default());
+
+        let mut skill = ActionSequence::new("skill".to_string());
+        skill.add_key_press("Ctrl+E".to_string());
+        let mut sequences = HashMap::new();
+        sequences.insert("skill".to_string(), skill);
+
+        let service = recording_service()
+            .with_resolver(Arc::new(sequences))
+            .with_tracer(tracer.clone());
+
+        let mut sequence = ActionSequence::new("deploy".to_string());
+        sequence.add_mouse_click(MouseButton::Left, 10, 20);
+        sequence.add_wait(Duration::from_millis(5));
+        sequence.add_call("skill".to_string());
+        sequence.add_key_press("NotAKey".to_string());
+
+        assert!(service.execute_sequence(&sequence).await.is_err());
+
+        let entries = tracer.entries();
+        assert_eq!(entries.len(), 4);
+        assert!(entries.iter().all(|entry| entry.run_id == entries[0].run_id));
+
+        assert_eq!(entries[0].sequence, "deploy");
+        assert_eq!(entries[0].index, 0);
+        assert_eq!(entries[0].events, vec![
+            InputEvent::MouseMove(10, 20),
+            InputEvent::MouseDown(MouseButton::Left),
+            InputEvent::MouseUp(MouseButton::Left),
+        ]);
+        assert!(entries[0].is_ok());
+
+        assert_eq!(entries[1].action, ActionType::Wait(Duration::from_millis(5)));
+        assert!(entries[1].events.is_empty());
+
+        assert_eq!(entries[2].sequence, "skill");
+        assert_eq!(entries[2].scheduled_us, 15_000);
+        assert!(entries[2].actual_us >= entries[2].scheduled_us);
+        assert_eq!(entries[2].events, vec![
+            InputEvent::KeyDown(0x11),
+            InputEvent::KeyDown(0x45),
+            InputEvent::KeyUp(0x45),
+            InputEvent::KeyUp(0x11),
+        ]);
+
+        assert_eq!(entries[3].sequence, "deploy");
+        assert_eq!(entries[3].index, 3);
+        assert!(entries[3].error.is_some());
+        assert_eq!(tracer.errors().len(), 1);
+
+        // 模拟执行不写入追踪
+        service.simulate(&sequence).await.ok();
+        assert_eq!(tracer.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_key_stops_sequence() {
+        let service = recording_service();
+
+        let mut sequence = ActionSequence::new("invalid".to_string());
+        sequence.add_key_press("NoSuchKey".to_string());
+        sequence.add_key_press("1".to_string());
+
+        let result = service.execute_sequence(&sequence).await;
+        assert!(matches!(result, Err(ActionError::InvalidKey(_))));
+        assert!(service.backend().events().is_empty());
+
+        // 鼠标侧键只能用于热键触发
+        for key in ["Mouse4", "Ctrl+Mouse5"] {
+            let result = service.execute_action(&ActionType::KeyPress(key.to_string())).await;
+            assert!(matches!(result, Err(ActionError::InvalidKey(_))));
+        }
+        assert!(service.resolve_key("XButton1").is_err());
+        assert!(service.backend().events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_configured_default_keys_resolve() {
+        let service = recording_service();
+
+        let mut sequence = ActionSequence::new("keys".to_string());
+        for key in ["0", "-", "=", "q", "P", "a", "L", "F12", "esc", "Del", "Tab", "Enter", "Backspace", "Up"] {
+            sequence.add_key_press(key.to_string());
+        }
+
+        service.execute_sequence(&sequence).await.unwrap();
+
+        let pressed: Vec<u16> = service.backend().events().into_iter()
+            .filter_map(|event| match event {
+                InputEvent::KeyDown(vk_code) => Some(vk_code),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(pressed, vec![
+            0x30, 0xBD, 0xBB, 0x51, 0x50, 0x41, 0x4C, 0x7B, 0x1B, 0x2E, 0x09, 0x0D, 0x08, 0x26,
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_key_combo_press_order() {
+        let service = recording_service();
+
+        service.execute_action(&ActionType::KeyPress("shift+ctrl+t".to_string())).await.unwrap();
+
+        assert_eq!(service.backend().events(), vec![
+            InputEvent::KeyDown(0x11),
+            InputEvent::KeyDown(0x10),
+            InputEvent::KeyDown(0x54),
+            InputEvent::KeyUp(0x54),
+            InputEvent::KeyUp(0x10),
+            InputEvent::KeyUp(0x11),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_modifier_held_across_click() {
+        let service = recording_service();
+
+        let mut sequence = ActionSequence::new("shift_click".to_string());
+        sequence.add_key_down("Shift".to_string());
+        sequence.add_mouse_click(MouseButton::Left, 10, 20);
+        sequence.add_key_up("Shift".to_string());
+
+        let report = service.run_sequence(&sequence).await.unwrap();
+
+        assert_eq!(service.backend().events(), vec![
+            InputEvent::KeyDown(0x10),
+            InputEvent::MouseMove(10, 20),
+            InputEvent::MouseDown(MouseButton::Left),
+            InputEvent::MouseUp(MouseButton::Left),
+            InputEvent::KeyUp(0x10),
+        ]);
+        assert!(report.held_keys.is_empty());
+        assert_eq!(report.actions_executed, 3);
+        assert!(service.held_keys().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_held_keys_reported_and_released() {
+        let service = recording_service();
+
+        let mut sequence = ActionSequence::new("hold".to_string());
+        sequence.add_key_down("Ctrl+A".to_string());
+        sequence.add_key_down("W".to_string());
+        sequence.add_key_press("W".to_string());
+
+        let report = service.run_sequence(&sequence).await.unwrap();
+        assert_eq!(report.held_keys, vec![0x11, 0x41]);
+        assert_eq!(service.held_keys(), vec![0x11, 0x41]);
+
+        service.backend().clear();
+        service.release_held_keys().unwrap();
+        assert_eq!(service.backend().events(), vec![
+            InputEvent::KeyUp(0x41),
+            InputEvent::KeyUp(0x11),
+        ]);
+        assert!(service.held_keys().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_key_hold_duration() {
+        let service = recording_service();
+
+        let mut sequence = ActionSequence::new("key_hold".to_string());
+        sequence.add_key_hold("Ctrl+F".to_string(), Duration::from_millis(20));
+
+        let report = service.run_sequence(&sequence).await.unwrap();
+        assert!(report.held_keys.is_empty());
+
+        let recorded = service.backend().recorded_events();
+        let events: Vec<InputEvent> = recorded.iter().map(|r| r.event).collect();
+        assert_eq!(events, vec![
+            InputEvent::KeyDown(0x11),
+            InputEvent::KeyDown(0x46),
+            InputEvent::KeyUp(0x46),
+            InputEvent::KeyUp(0x11),
+        ]);
+        assert!(recorded[2].timestamp - recorded[1].timestamp >= Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn test_mouse_drag_path_and_timing() {
+        let service = recording_service();
+
+        let mut sequence = ActionSequence::new("deploy".to_string());
+        sequence.add_key_down("Shift".to_string());
+        sequence.add_mouse_drag(
+            MouseButton::Left, (100, 900), (500, 500), Duration::from_millis(40), 4, Easing::Linear,
+        );
+        sequence.add_key_up("Shift".to_string());
+
+        let report = service.run_sequence(&sequence).await.unwrap();
+        assert!(report.held_buttons.is_empty());
+
+        let recorded = service.backend().recorded_events();
+        let events: Vec<InputEvent> = recorded.iter().map(|r| r.event).collect();
+        assert_eq!(events, vec![
+            InputEvent::KeyDown(0x10),
+            InputEvent::MouseMove(100, 900),
+            InputEvent::MouseDown(MouseButton::Left),
+            InputEvent::MouseMove(200, 800),
+            InputEvent::MouseMove(300, 700),
+            InputEvent::MouseMove(400, 600),
+            InputEvent::MouseMove(500, 500),
+            InputEvent::MouseUp(MouseButton::Left),
+            InputEvent::KeyUp(0x10),
+        ]);
+
+        // 按下到最后一次移动至少经过拖拽时长
+        assert!(recorded[6].timestamp - recorded[2].timestamp >= Duration::from_millis(40));
+    }
+
+    #[tokio::test]
+    async fn test_scroll_and_relative_move() {
+        let service = recording_service();
+
+        let mut sequence = ActionSequence::new("map_view".to_string());
+        sequence.add_action(ActionType::MouseMove(500, 500));
+        sequence.add_scroll(0, -2);
+        sequence.add_action(ActionType::MouseMoveRelative(15, -10));
+        sequence.add_scroll(1, 0);
+
+        service.execute_sequence(&sequence).await.unwrap();
+
+        assert_eq!(service.backend().events(), vec![
+            InputEvent::MouseMove(500, 500),
+            InputEvent::MouseWheel(0, -240),
+            InputEvent::MouseMoveRelative(15, -10),
+            InputEvent::MouseWheel(120, 0),
+        ]);
+        assert_eq!(service.get_cursor_position().unwrap(), (515, 490));
+
+        service.execute_action(&ActionType::Scroll { dx: i32::MAX, dy: i32::MIN / 2 }).await.unwrap();
+        assert_eq!(service.backend().events().last(), Some(&InputEvent::MouseWheel(i32::MAX, i32::MIN)));
+    }
+
+    #[tokio::test]
+    async fn test_spawned_sequence_cancel_releases_inputs() {
+        let service = Arc::new(recording_service());
+
+        let mut sequence = ActionSequence::new("long".to_string());
+        sequence.add_key_down("Shift".to_string());
+        sequence.add_action(ActionType::MouseDrag {
+            button: MouseButton::Left,
+            from: (0, 0),
+            to: (100, 0),
+            duration: Duration::from_secs(10),
+            steps: 10,
+            easing: Easing::Linear,
+        });
+
+        let handle = service.spawn_sequence(sequence, None);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(service.registry().active_count(), 1);
+
+        handle.cancel();
+        let result = tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap();
+        assert!(matches!(result, Err(ActionError::Cancelled)));
+
+        let events = service.backend().events();
+        assert_eq!(&events[events.len() - 2..], &[
+            InputEvent::MouseUp(MouseButton::Left),
+            InputEvent::KeyUp(0x10),
+        ]);
+        assert!(service.held_keys().is_empty());
+        assert!(service.held_buttons().is_empty());
+        assert_eq!(service.registry().active_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_spawned_sequence_timeout() {
+        let service = Arc::new(recording_service());
+
+        let mut sequence = ActionSequence::new("slow".to_string());
+        sequence.add_key_hold("W".to_string(), Duration::from_secs(10));
+        sequence.add_key_press("E".to_string());
+
+        let start = Instant::now();
+        let result = service.spawn_sequence(sequence, Some(Duration::from_millis(30))).await;
+        assert!(matches!(result, Err(ActionError::Timeout)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        assert_eq!(service.backend().events(), vec![
+            InputEvent::KeyDown(0x57),
+            InputEvent::KeyUp(0x57),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_registry_cancel_all_stops_running_sequences() {
+        let service = Arc::new(recording_service());
+
+        let mut sequence = ActionSequence::new("wait".to_string());
+        sequence.add_wait(Duration::from_secs(10));
+
+        let first = service.spawn_sequence(sequence.clone(), None);
+        let second = service.spawn_sequence(sequence, None);
+        assert_eq!(service.registry().cancel_all(), 2);
+
+        assert!(matches!(first.await, Err(ActionError::Cancelled)));
+        assert!(matches!(second.await, Err(ActionError::Cancelled)));
+    }
+
+    /// 断言每个按下都有对应的释放
+    fn assert_balanced(events: &[InputEvent]) {
+        let mut keys = Vec::new();
+        let mut buttons = Vec::new();
+        for event in events {
+            match event {
+                InputEvent::KeyDown(vk) => track_down(&mut keys, *vk),
+                InputEvent::KeyUp(vk) => keys.retain(|held| held != vk),
+                InputEvent::MouseDown(button) => track_down(&mut buttons, *button),
+                InputEvent::MouseUp(button) => buttons.retain(|held| held != button),
+                _ => {}
+            }
+        }
+        assert!(keys.is_empty() && buttons.is_empty(), "未释放的按键 {:?}, 鼠标按钮 {:?}", keys, buttons);
+    }
+
+    #[tokio::test]
+    async fn test_send_failure_releases_held_inputs() {
+        let service = recording_service();
+        // 组合键批量发送到一半失败：Ctrl 已按下，A 失败
+        service.backend().fail_on(InputEvent::KeyDown(0x41));
+
+        let mut sequence = ActionSequence::new("fail".to_string());
+        sequence.add_key_down("Shift".to_string());
+        sequence.add_key_press("Ctrl+A".to_string());
+        sequence.add_key_press("1".to_string());
+
+        let result = service.execute_sequence(&sequence).await;
+        assert!(matches!(result, Err(ActionError::SystemCall(_))));
+
+        let events = service.backend().events();
+        assert!(!events.contains(&InputEvent::KeyDown(0x31)));
+        assert_balanced(&events);
+        assert!(service.held_keys().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_drag_failure_releases_button() {
+        let service = recording_service();
+        service.backend().fail_on(InputEvent::MouseMove(300, 700));
+
+        let mut sequence = ActionSequence::new("drag".to_string());
+        sequence.add_mouse_drag(
+            MouseButton::Left, (100, 900), (500, 500), Duration::from_millis(20), 4, Easing::Linear,
+        );
+
+        assert!(service.run_sequence(&sequence).await.is_err());
+
+        let events = service.backend().events();
+        assert_eq!(events.last(), Some(&InputEvent::MouseUp(MouseButton::Left)));
+        assert_balanced(&events);
+        assert!(service.held_buttons().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_click_failure_releases_button() {
+        let service = recording_service();
+        service.backend().fail_on(InputEvent::MouseUp(MouseButton::Left));
+
+        let result = service.execute_action(&ActionType::MouseClick(MouseButton::Left, 10, 10)).await;
+        assert!(result.is_err());
+        assert_eq!(service.backend().events().last(), Some(&InputEvent::MouseUp(MouseButton::Left)));
+        assert_balanced(&service.backend().events());
+        assert!(service.held_buttons().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_drag_duration_out_of_range() {
+        let service = recording_service();
+
+        let result = service.execute_action(&ActionType::MouseDrag {
+            button: MouseButton::Left,
+            from: (0, 0),
+            to: (100, 0),
+            duration: Duration::MAX,
+            steps: u32::MAX,
+            easing: Easing::Linear,
+        }).await;
+        assert!(matches!(result, Err(ActionError::InvalidParameter(_))));
+        assert!(service.backend().events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_action_failure_releases() {
+        let service = recording_service();
+        service.backend().fail_on(InputEvent::KeyDown(0x42));
+
+        let result = service.execute_action(&ActionType::KeyPress("Alt+B".to_string())).await;
+        assert!(result.is_err());
+        assert_balanced(&service.backend().events());
+        assert!(service.held_keys().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dropped_future_releases_held_inputs() {
+        let service = recording_service();
+
+        let mut sequence = ActionSequence::new("dropped".to_string());
+        sequence.add_key_down("W".to_string());
+        sequence.add_wait(Duration::from_secs(10));
+
+        let result = tokio::time::timeout(Duration::from_millis(20), service.run_sequence(&sequence)).await;
+        assert!(result.is_err());
+
+        let events = service.backend().events();
+        assert_eq!(events, vec![InputEvent::KeyDown(0x57), InputEvent::KeyUp(0x57)]);
+        assert!(service.held_keys().is_empty());
+        assert_eq!(service.registry().active_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_service_drop_releases_held_inputs() {
+        let backend = Arc::new(RecordingInputBackend::new());
+        let service = ActionService::with_backend(Arc::clone(&backend));
+
+        // 正常结束时按住的输入保持不变
+        let mut sequence = ActionSequence::new("hold".to_string());
+        sequence.add_key_down("Shift".to_string());
+        sequence.add_mouse_drag(
+            MouseButton::Right, (0, 0), (10, 10), Duration::ZERO, 1, Easing::Linear,
+        );
+        sequence.add_key_down("Ctrl".to_string());
+        let report = service.run_sequence(&sequence).await.unwrap();
+        assert_eq!(report.held_keys, vec![0x10, 0x11]);
+
+        drop(service);
+        let events = backend.events();
+        assert_eq!(&events[events.len() - 2..], &[InputEvent::KeyUp(0x11), InputEvent::KeyUp(0x10)]);
+        assert_balanced(&events);
+    }
+
+    #[tokio::test]
+    async fn test_panic_hook_releases_held_inputs() {
+        let backend = Arc::new(RecordingInputBackend::new());
+        let service = Arc::new(ActionService::with_backend(Arc::clone(&backend)));
+
+        // 保存之前的钩子，测试结束时恢复
+        let previous: Arc<dyn Fn(&std::panic::PanicHookInfo<'_>) + Send + Sync> = Arc::from(std::panic::take_hook());
+        let chained = Arc::clone(&previous);
+        std::panic::set_hook(Box::new(move |info| chained(info)));
+        service.install_panic_hook();
+
+        let mut sequence = ActionSequence::new("hold".to_string());
+        sequence.add_key_down("Alt".to_string());
+        service.run_sequence(&sequence).await.unwrap();
+        assert_eq!(service.held_keys(), vec![0x12]);
+
+        // panic 的线程持有记录的锁时不等待，也不释放
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _held = service.held_keys.lock().unwrap();
+            panic!("测试 panic");
+        }));
+        assert!(result.is_err());
+        assert_eq!(backend.events(), vec![InputEvent::KeyDown(0x12)]);
+        service.held_keys.clear_poison();
+        assert_eq!(service.held_keys(), vec![0x12]);
+
+        let result = std::panic::catch_unwind(|| panic!("测试 panic"));
+        drop(std::panic::take_hook());
+        std::panic::set_hook(Box::new(move |info| previous(info)));
+        assert!(result.is_err());
+
+        assert_eq!(backend.events(), vec![InputEvent::KeyDown(0x12), InputEvent::KeyUp(0x12)]);
+        assert!(service.held_keys().is_empty());
+    }
+
+    fn guarded_service(settings: crate::models::SafetySettings) -> (Arc<ActionService<RecordingInputBackend>>, Arc<SafetyGuard>) {
+        let guard = Arc::new(SafetyGuard::new(settings));
+        let service = recording_service().with_safety_guard(Arc::clone(&guard));
+        (Arc::new(service), guard)
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_stops_sequence() {
+        let (service, guard) = guarded_service(crate::models::SafetySettings {
+            max_events_per_second: 5,
+            ..Default::default()
+        });
+        let mut violations = guard.subscribe();
+
+        let mut sequence = ActionSequence::new("spam".to_string());
+        sequence.add_key_down("Shift".to_string());
+        sequence.add_repeat(10, vec![ActionType::KeyPress("A".to_string())]);
+
+        let result = service.run_sequence(&sequence).await;
+        assert!(matches!(result, Err(ActionError::RateLimitExceeded(5))));
+
+        // Shift + 4 次 A 按下，之后按住的 Shift 被释放
+        let events = service.backend().events();
+        assert_eq!(events.iter().filter(|event| matches!(event, InputEvent::KeyDown(_))).count(), 5);
+        assert_eq!(events.last(), Some(&InputEvent::KeyUp(0x10)));
+        assert!(service.held_keys().is_empty());
+        assert!(matches!(violations.try_recv(), Ok(SafetyViolation::RateLimit { limit: 5, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_sequence_duration_cap() {
+        let (service, _guard) = guarded_service(crate::models::SafetySettings {
+            max_sequence_duration_ms: 30,
+            ..Default::default()
+        });
+
+        let mut sequence = ActionSequence::new("slow".to_string());
+        sequence.add_key_hold("W".to_string(), Duration::from_secs(10));
+
+        let start = Instant::now();
+        let result = service.spawn_sequence(sequence.clone(), None).await;
+        assert!(matches!(result, Err(ActionError::SequenceDurationExceeded(limit)) if limit == Duration::from_millis(30)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(service.held_keys().is_empty());
+
+        // 更短的超时优先
+        let result = service.spawn_sequence(sequence, Some(Duration::from_millis(10))).await;
+        assert!(matches!(result, Err(ActionError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn test_emergency_stop_cancels_and_blocks_input() {
+        let (service, guard) = guarded_service(Default::default());
+
+        let mut sequence = ActionSequence::new("hold".to_string());
+        sequence.add_key_down("Ctrl".to_string());
+        sequence.add_mouse_click(MouseButton::Left, 10, 10);
+        sequence.add_wait(Duration::from_secs(10));
+
+        let handle = service.spawn_sequence(sequence.clone(), None);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(service.held_keys(), vec![0x11]);
+
+        assert_eq!(service.emergency_stop(), 1);
+        let result = tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap();
+        assert!(matches!(result, Err(ActionError::EmergencyStop)));
+        assert!(service.held_keys().is_empty());
+        assert_eq!(service.backend().events().last(), Some(&InputEvent::KeyUp(0x11)));
+
+        // 解除前拒绝新的输入
+        assert!(matches!(service.run_sequence(&sequence).await, Err(ActionError::EmergencyStop)));
+        guard.reset();
+        service.backend().clear();
+        let mut quick = ActionSequence::new("quick".to_string());
+        quick.add_key_press("1".to_string());
+        service.run_sequence(&quick).await.unwrap();
+        assert_eq!(service.backend().events().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_spawned_sequence_completes() {
+        let service = Arc::new(recording_service());
+
+        let mut sequence = ActionSequence::new("quick".to_string());
+        sequence.add_key_press("1".to_string());
+
+        let report = service.spawn_sequence(sequence, Some(Duration::from_secs(1))).await.unwrap();
+        assert_eq!(report.actions_executed, 1);
+        assert_eq!(service.registry().active_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_custom_key_mapping() {
+        let mut service = recording_service();
+        service.add_key_mapping("Custom".to_string(), 0x7B);
+
+        service.execute_action(&ActionType::KeyPress("Custom".to_string())).await.unwrap();
+
+        assert_eq!(service.backend().events(), vec![
+            InputEvent::KeyDown(0x7B),
+            InputEvent::KeyUp(0x7B),
+        ]);
+        assert!(service.get_supported_keys().contains(&"Custom".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_repeat_counts_leaf_actions() {
+        let service = recording_service();
+
+        let mut sequence = ActionSequence::new("triple".to_string());
+        sequence.add_repeat(3, vec![ActionType::KeyPress("Q".to_string())]);
+
+        let report = service.run_sequence(&sequence).await.unwrap();
+        assert_eq!(report.actions_executed, 3);
+        assert_eq!(service.backend().events().len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_loop_until_condition() {
+        let conditions = Arc::new(std::sync::RwLock::new(ConditionSnapshot::default()));
+        let service = recording_service().with_conditions(conditions.clone());
+
+        let mut sequence = ActionSequence::new("wait_battle".to_string());
+        sequence.add_action(ActionType::Loop {
+            until: Condition::GameState(GameState::InBattle),
+            max_iterations: 1000,
+            body: vec![ActionType::Wait(Duration::from_millis(2))],
+        });
+        sequence.add_key_press("Space".to_string());
+
+        let flip = {
+            let conditions = conditions.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(15)).await;
+                conditions.write().unwrap().game_state = GameState::InBattle;
+            })
+        };
+
+        tokio::time::timeout(Duration::from_secs(1), service.run_sequence(&sequence))
+            .await
+            .unwrap()
+            .unwrap();
+        flip.await.unwrap();
+        assert_eq!(service.backend().events(), vec![
+            InputEvent::KeyDown(0x20),
+            InputEvent::KeyUp(0x20),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_loop_max_iterations_exceeded() {
+        let conditions = Arc::new(ConditionSnapshot::default());
+        let service = recording_service().with_conditions(conditions);
+
+        let mut sequence = ActionSequence::new("never".to_string());
+        sequence.add_action(ActionType::Loop {
+            until: Condition::GameState(GameState::InBattle),
+            max_iterations: 5,
+            body: vec![ActionType::KeyPress("E".to_string())],
+        });
+
+        let result = service.execute_sequence(&sequence).await;
+        assert!(matches!(result, Err(ActionError::SequenceError(_))));
+        assert_eq!(service.backend().events().len(), 10);
+
+        let mut invalid = ActionSequence::new("zero".to_string());
+        invalid.add_action(ActionType::Loop {
+            until: Condition::GameState(GameState::InBattle),
+            max_iterations: 0,
+            body: vec![],
+        });
+        assert!(matches!(
+            service.execute_sequence(&invalid).await,
+            Err(ActionError::InvalidParameter(_))
+        ));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_call_resolves_named_sequences() {
+        let mut skill = ActionSequence::new("skill".to_string());
+        skill.add_key_press("E".to_string());
+
+        let mut sequences = HashMap::new();
+        sequences.insert("skill".to_string(), skill);
+        let service = recording_service().with_resolver(Arc::new(sequences));
+
+        let mut sequence = ActionSequence::new("combo".to_string());
+        sequence.add_call("skill".to_string());
+        sequence.add_key_press("Q".to_string());
+
+        let report = service.run_sequence(&sequence).await.unwrap();
+        assert_eq!(report.actions_executed, 2);
+        assert_eq!(service.backend().events(), vec![
+            InputEvent::KeyDown(0x45),
+            InputEvent::KeyUp(0x45),
+            InputEvent::KeyDown(0x51),
+            InputEvent::KeyUp(0x51),
+        ]);
+
+        let mut missing = ActionSequence::new("missing".to_string());
+        missing.add_call("unknown".to_string());
+        assert!(matches!(
+            service.execute_sequence(&missing).await,
+            Err(ActionError::SequenceError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_flow_waits_for_concurrent_writers() {
+        let mut skill = ActionSequence::new("skill".to_string());
+        skill.add_key_press("E".to_string());
+        let sequences = Arc::new(tokio::sync::RwLock::new(HashMap::from([("skill".to_string(), skill)])));
+        let state = Arc::new(tokio::sync::RwLock::new(crate::models::AppState::default()));
+        let service = Arc::new(recording_service()
+            .with_resolver(Arc::clone(&sequences) as Arc<dyn SequenceResolver>)
+            .with_conditions(Arc::clone(&state) as Arc<dyn ConditionSource>));
+
+        let sequence: ActionSequence = "if game(NotDetected) {\n    call skill\n}".parse().unwrap();
+        let writer = sequences.write().await;
+        let state_writer = state.write().await;
+        let running = tokio::spawn({
+            let service = Arc::clone(&service);
+            async move { service.execute_sequence(&sequence).await }
+        });
+
+        // 写锁释放后继续执行，而不是报错
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!running.is_finished());
+        drop(state_writer);
+        drop(writer);
+        running.await.unwrap().unwrap();
+        assert_eq!(service.backend().events(), vec![InputEvent::KeyDown(0x45), InputEvent::KeyUp(0x45)]);
+
+        let mut repeat = ActionSequence::new("repeat".to_string());
+        repeat.add_repeat(MAX_LOOP_ITERATIONS + 1, vec![ActionType::KeyPress("E".to_string())]);
+        assert!(matches!(service.execute_sequence(&repeat).await, Err(ActionError::InvalidParameter(_))));
+        assert_eq!(service.backend().events().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sequence_params() {
+        // 被调用的序列有自己的默认参数，调用方的同名参数优先
+        let skill: ActionSequence = "@param slot 9\n@param key 4\nkey $slot\nkey $key".parse().unwrap();
+        let mut sequences = HashMap::new();
+        sequences.insert("skill_2".to_string(), skill);
+        let service = recording_service().with_resolver(Arc::new(sequences));
+
+        let sequence: ActionSequence = "@param slot 2\nhold $slot 1ms\ncall skill_$slot\nkey $slot".parse().unwrap();
+        service.execute_sequence(&sequence).await.unwrap();
+        assert_eq!(service.backend().events(), vec![
+            InputEvent::KeyDown(0x32),
+            InputEvent::KeyUp(0x32),
+            InputEvent::KeyDown(0x32),
+            InputEvent::KeyUp(0x32),
+            InputEvent::KeyDown(0x34),
+            InputEvent::KeyUp(0x34),
+            InputEvent::KeyDown(0x32),
+            InputEvent::KeyUp(0x32),
+        ]);
+
+        let missing: ActionSequence = "key $slot".parse().unwrap();
+        assert!(matches!(
+            service.execute_sequence(&missing).await,
+            Err(ActionError::InvalidParameter(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_default_slot_operations() {
+        // 默认配置下槽位操作按下槽位按键配置中该槽位的按键
+        let mut manager = ModeManager::new();
+        manager.update_hotkey("deploy_operator".to_string(), "1,2,3,4,5,6,7,8,9,0,-,=,F5".to_string()).unwrap();
+        manager.update_hotkey("activate_skill".to_string(), "Q,W,E,R,T,Y,U,I,O,P".to_string()).unwrap();
+        manager.update_hotkey("retreat_operator".to_string(), "A,S,D,F,G,H,J,K,L".to_string()).unwrap();
+        let bindings = manager.current_bindings();
+        let operation = |name: &str| {
+            bindings.iter()
+                .find(|(_, operation)| operation.name == name)
+                .map(|(_, operation)| operation.sequence.clone())
+                .unwrap()
+        };
+
+        for (name, key) in [
+            ("deploy_operator[1]", 0x31),
+            ("deploy_operator[10]", 0x30),
+            ("deploy_operator[11]", 0xBD),
+            ("deploy_operator[12]", 0xBB),
+            ("activate_skill[10]", 0x50),
+            ("retreat_operator[9]", 0x4C),
+        ] {
+            let service = recording_service();
+            service.execute_sequence(&operation(name)).await.unwrap();
+            assert_eq!(service.backend().events(), vec![InputEvent::KeyDown(key), InputEvent::KeyUp(key)], "{}", name);
+        }
+
+        // 槽位按键配置中没有的槽位无法执行
+        let service = recording_service();
+        assert!(matches!(
+            service.execute_sequence(&operation("deploy_operator[13]")).await,
+            Err(ActionError::InvalidParameter(_))
+        ));
+        assert!(service.backend().events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_recursive_call_depth_guard() {
+        let mut recursive = ActionSequence::new("recursive".to_string());
+        recursive.add_key_press("1".to_string());
+        recursive.add_call("recursive".to_string());
+
+        let mut sequences = HashMap::new();
+        sequences.insert("recursive".to_string(), recursive.clone());
+        let service = recording_service()
+            .with_resolver(Arc::new(sequences))
+            .with_max_call_depth(4);
+
+        let result = service.execute_sequence(&recursive).await;
+        assert!(matches!(result, Err(ActionError::SequenceError(_))));
+        // 顶层一次加上 4 层调用
+        assert_eq!(service.backend().events().len(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_if_else_branches() {
+        let mut snapshot = ConditionSnapshot::default();
+        snapshot.vision.insert("skill_ready".to_string(), false);
+        let service = recording_service().with_conditions(Arc::new(snapshot));
+
+        let mut sequence = ActionSequence::new("branch".to_string());
+        sequence.add_action(ActionType::If {
+            condition: Condition::Vision("skill_ready".to_string()),
+            then: vec![ActionType::KeyPress("E".to_string())],
+            otherwise: vec![ActionType::KeyPress("Q".to_string())],
+        });
+
+        service.execute_sequence(&sequence).await.unwrap();
+        assert_eq!(service.backend().events(), vec![
+            InputEvent::KeyDown(0x51),
+            InputEvent::KeyUp(0x51),
+        ]);
+
+        // 未配置条件来源时报错
+        let service = recording_service();
+        assert!(service.execute_sequence(&sequence).await.is_err());
+    }
+}
@@ -603,14 +603,34 @@ impl ConfigService {
         .await
     }
 
-    /// 更新按键配置
+    /// 更新当前模式的按键配置，只替换传入的热键
     pub async fn update_hotkeys(
         &self,
         hotkeys: std::collections::HashMap<String, String>,
     ) -> ConfigResult<()> {
         self.update_config(|config| {
-            config.macro_config.hotkeys = hotkeys.clone();
-            config.intelligent_config.hotkeys = hotkeys;
+            let current = match config.mode {
+                crate::models::config::OperationMode::Macro => &mut config.macro_config.hotkeys,
+                crate::models::config::OperationMode::Intelligent => &mut config.intelligent_config.hotkeys,
+            };
+            current.extend(hotkeys);
+        })
+        .await
+    }
+
+    /// 更新指定模式的单个热键，不影响其他热键
+    pub async fn update_mode_hotkey(
+        &self,
+        mode: crate::models::config::OperationMode,
+        operation: &str,
+        hotkey: &str,
+    ) -> ConfigResult<()> {
+        self.update_config(|config| {
+            let hotkeys = match mode {
+                crate::models::config::OperationMode::Macro => &mut config.macro_config.hotkeys,
+                crate::models::config::OperationMode::Intelligent => &mut config.intelligent_config.hotkeys,
+            };
+            hotkeys.insert(operation.to_string(), hotkey.to_string());
         })
         .await
     }
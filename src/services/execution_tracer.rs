@@ -0,0 +1,224 @@
+//! 执行追踪
+//!
+//! 记录每个序列中每个操作的计划时间、实际时间、实际发送的输入事件和执行结果，
+//! 保存在固定容量的环形缓冲区中，可以导出为 JSON Lines
+
+use crate::models::ActionType;
+use crate::services::input_backend::InputEvent;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// 默认保留的追踪条目数量
+pub const DEFAULT_TRACE_CAPACITY: usize = 4096;
+
+/// 追踪条目
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TraceEntry {
+    /// 序列执行编号，同一次执行的条目编号相同
+    pub run_id: u64,
+    /// 序列名称（被调用的序列使用自己的名称）
+    pub sequence: String,
+    /// 本次执行中的第几个操作（从 0 开始，控制流展开后计数）
+    pub index: usize,
+    /// 操作
+    pub action: ActionType,
+    /// 计划时间（相对序列开始，微秒）
+    pub scheduled_us: u64,
+    /// 实际时间（相对序列开始，微秒）
+    pub actual_us: u64,
+    /// 操作开始时的本地时间
+    pub timestamp: DateTime<Local>,
+    /// 实际发送的输入事件（按键为虚拟键码，坐标为屏幕坐标）
+    pub events: Vec<InputEvent>,
+    /// 错误信息，成功时为空
+    pub error: Option<String>,
+}
+
+impl TraceEntry {
+    /// 是否执行成功
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// 执行追踪器
+#[derive(Debug)]
+pub struct ExecutionTracer {
+    /// 最大条目数量
+    capacity: usize,
+    /// 追踪条目
+    entries: Mutex<VecDeque<TraceEntry>>,
+    /// 下一个执行编号
+    next_run_id: AtomicU64,
+}
+
+impl ExecutionTracer {
+    /// 创建指定容量的追踪器
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new(VecDeque::new()),
+            next_run_id: AtomicU64::new(0),
+        }
+    }
+
+    /// 分配新的执行编号
+    pub fn next_run_id(&self) -> u64 {
+        self.next_run_id.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// 记录条目，超出容量时丢弃最旧的条目
+    pub fn record(&self, entry: TraceEntry) {
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() >= self.capacity {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        }
+    }
+
+    /// 全部条目（从旧到新）
+    pub fn entries(&self) -> Vec<TraceEntry> {
+        self.entries.lock()
+            .map(|entries| entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 指定序列的条目
+    pub fn entries_for(&self, sequence: &str) -> Vec<TraceEntry> {
+        self.filter(|entry| entry.sequence == sequence)
+    }
+
+    /// 指定执行编号的条目
+    pub fn entries_for_run(&self, run_id: u64) -> Vec<TraceEntry> {
+        self.filter(|entry| entry.run_id == run_id)
+    }
+
+    /// 失败的条目
+    pub fn errors(&self) -> Vec<TraceEntry> {
+        self.filter(|entry| !entry.is_ok())
+    }
+
+    /// 最近的 `count` 个条目（从旧到新）
+    pub fn recent(&self, count: usize) -> Vec<TraceEntry> {
+        self.entries.lock()
+            .map(|entries| entries.iter().skip(entries.len().saturating_sub(count)).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 按条件筛选条目
+    pub fn filter(&self, predicate: impl Fn(&TraceEntry) -> bool) -> Vec<TraceEntry> {
+        self.entries.lock()
+            .map(|entries| entries.iter().filter(|entry| predicate(entry)).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 条目数量
+    pub fn len(&self) -> usize {
+        self.entries.lock().map(|entries| entries.len()).unwrap_or(0)
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 容量
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 清空
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    /// 以 JSON Lines 格式写出全部条目，返回写出的条目数量
+    pub fn export_jsonl<W: Write>(&self, mut writer: W) -> std::io::Result<usize> {
+        let entries = self.entries();
+        for entry in &entries {
+            serde_json::to_writer(&mut writer, entry)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(entries.len())
+    }
+
+    /// 导出到 JSON Lines 文件
+    pub fn export_jsonl_file<P: AsRef<Path>>(&self, path: P) -> std::io::Result<usize> {
+        let file = std::fs::File::create(path)?;
+        self.export_jsonl(std::io::BufWriter::new(file))
+    }
+}
+
+impl Default for ExecutionTracer {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRACE_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(run_id: u64, index: usize, error: Option<&str>) -> TraceEntry {
+        TraceEntry {
+            run_id,
+            sequence: format!("seq{}", run_id),
+            index,
+            action: ActionType::KeyPress("A".to_string()),
+            scheduled_us: index as u64 * 1000,
+            actual_us: index as u64 * 1000 + 50,
+            timestamp: Local::now(),
+            events: vec![InputEvent::KeyDown(0x41), InputEvent::KeyUp(0x41)],
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_ring_buffer_drops_oldest() {
+        let tracer = ExecutionTracer::new(3);
+        for index in 0..5 {
+            tracer.record(entry(1, index, None));
+        }
+
+        assert_eq!(tracer.len(), 3);
+        let indices: Vec<usize> = tracer.entries().iter().map(|entry| entry.index).collect();
+        assert_eq!(indices, vec![2, 3, 4]);
+        assert_eq!(tracer.recent(2).len(), 2);
+        assert_eq!(tracer.recent(2)[0].index, 3);
+
+        tracer.clear();
+        assert!(tracer.is_empty());
+    }
+
+    #[test]
+    fn test_queries_and_jsonl_export() {
+        let tracer = ExecutionTracer::default();
+        tracer.record(entry(1, 0, None));
+        tracer.record(entry(2, 0, Some("无效的按键: Foo")));
+        tracer.record(entry(2, 1, None));
+
+        assert_eq!(tracer.entries_for("seq2").len(), 2);
+        assert_eq!(tracer.entries_for_run(1).len(), 1);
+        assert_eq!(tracer.errors().len(), 1);
+
+        let mut output = Vec::new();
+        assert_eq!(tracer.export_jsonl(&mut output).unwrap(), 3);
+
+        let text = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+
+        let restored: TraceEntry = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(restored, tracer.entries()[1]);
+        assert_eq!(restored.error.as_deref(), Some("无效的按键: Foo"));
+    }
+}
@@ -0,0 +1,209 @@
+//! 战斗速度跟踪
+//!
+//! 游戏内的变速键在 1 倍速和 2 倍速之间切换。操作服务发送变速键或玩家按下变速键时
+//! 更新记录的速度，进入战斗时重置为 1 倍速。`GameWait` 按记录的速度把游戏内时间换算为现实时间
+
+use crate::models::{GameSpeed, GameState, Key, KeyCombo, ProgramState};
+use crate::services::global_input::{ComboWatch, InputEventSource, KeyTracker};
+use crate::services::input_backend::InputEvent;
+use crate::services::state_manager::StateObserver;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// 游戏内变速功能在 `GlobalSettings.game_keys` 中的名称
+pub const BATTLE_SPEED_FUNCTION: &str = "battle_speed";
+
+/// 战斗速度跟踪器
+#[derive(Debug, Default)]
+pub struct GameSpeedTracker {
+    /// 是否为 2 倍速
+    double: AtomicBool,
+    /// 变速键
+    toggle_key: RwLock<Option<KeyCombo>>,
+    /// 操作服务已发送的按键状态，用于判断发送的组合键
+    sent: Mutex<KeyTracker>,
+}
+
+impl GameSpeedTracker {
+    /// 使用指定的变速键创建，初始为 1 倍速
+    pub fn new(toggle_key: Option<KeyCombo>) -> Self {
+        Self {
+            double: AtomicBool::new(false),
+            toggle_key: RwLock::new(toggle_key),
+            sent: Mutex::new(KeyTracker::new()),
+        }
+    }
+
+    /// 根据游戏内按键配置创建
+    pub fn from_game_keys(game_keys: &HashMap<String, String>) -> Self {
+        Self::new(parse_toggle_key(game_keys))
+    }
+
+    /// 当前战斗速度
+    pub fn speed(&self) -> GameSpeed {
+        if self.double.load(Ordering::SeqCst) {
+            GameSpeed::Double
+        } else {
+            GameSpeed::Normal
+        }
+    }
+
+    /// 设置战斗速度（例如进入新的战斗时重置为 1 倍速）
+    pub fn set(&self, speed: GameSpeed) {
+        self.double.store(speed == GameSpeed::Double, Ordering::SeqCst);
+    }
+
+    /// 切换战斗速度，返回切换后的速度
+    pub fn toggle(&self) -> GameSpeed {
+        let speed = self.speed().toggled();
+        self.set(speed);
+        log::info!("战斗速度切换为 {}", speed);
+        speed
+    }
+
+    /// 变速键
+    pub fn toggle_key(&self) -> Option<KeyCombo> {
+        self.toggle_key.read()
+            .map(|key| key.clone())
+            .unwrap_or_default()
+    }
+
+    /// 更新变速键（游戏内按键配置变化时调用）
+    pub fn set_toggle_key(&self, toggle_key: Option<KeyCombo>) {
+        if let Ok(mut current) = self.toggle_key.write() {
+            *current = toggle_key;
+        }
+    }
+
+    /// 根据游戏内按键配置更新变速键
+    pub fn update_game_keys(&self, game_keys: &HashMap<String, String>) {
+        self.set_toggle_key(parse_toggle_key(game_keys));
+    }
+
+    /// 一批事件中按下变速键的次数（用于模拟执行，不影响已发送的按键状态）
+    pub fn toggles_in(&self, events: &[InputEvent]) -> usize {
+        self.count_toggles(&mut KeyTracker::new(), events)
+    }
+
+    /// 根据已发送的事件更新战斗速度，返回速度是否改变
+    pub fn observe(&self, events: &[InputEvent]) -> bool {
+        let toggles = match self.sent.lock() {
+            Ok(mut sent) => self.count_toggles(&mut sent, events),
+            Err(poisoned) => self.count_toggles(&mut poisoned.into_inner(), events),
+        };
+        for _ in 0..toggles {
+            self.toggle();
+        }
+        toggles % 2 == 1
+    }
+
+    /// 根据玩家按下的组合键更新战斗速度，返回速度是否改变
+    pub fn observe_press(&self, combo: &KeyCombo) -> bool {
+        let pressed = self.toggle_key().is_some_and(|toggle_key| combo.matches(&toggle_key));
+        if pressed {
+            self.toggle();
+        }
+        pressed
+    }
+
+    /// 监视玩家按下的变速键（全局监听已过滤本程序发送的按键）
+    pub fn watch(self: &Arc<Self>, source: &dyn InputEventSource) -> ComboWatch {
+        let tracker = Arc::clone(self);
+        ComboWatch::each_press(source, "battle-speed".to_string(), move |combo| {
+            tracker.observe_press(combo);
+        })
+    }
+
+    /// 按发送的按键更新按键状态，统计按下完整变速组合键的次数
+    fn count_toggles(&self, keys: &mut KeyTracker, events: &[InputEvent]) -> usize {
+        let toggle_key = self.toggle_key();
+        events.iter()
+            .filter_map(|event| match event {
+                InputEvent::KeyDown(vk) => Key::from_vk_code(*vk).and_then(|key| keys.press(key)),
+                InputEvent::KeyUp(vk) => {
+                    if let Some(key) = Key::from_vk_code(*vk) {
+                        keys.release(key);
+                    }
+                    None
+                }
+                _ => None,
+            })
+            .filter(|combo| toggle_key.as_ref().is_some_and(|toggle_key| combo.matches(toggle_key)))
+            .count()
+    }
+}
+
+impl StateObserver for GameSpeedTracker {
+    fn on_program_state_changed(&self, _old_state: ProgramState, _new_state: ProgramState) {}
+
+    /// 进入战斗时重置为 1 倍速
+    fn on_game_state_changed(&self, old_state: GameState, new_state: GameState) {
+        if new_state == GameState::InBattle && old_state != GameState::InBattle {
+            self.set(GameSpeed::Normal);
+        }
+    }
+}
+
+/// 从游戏内按键配置中读取变速键
+fn parse_toggle_key(game_keys: &HashMap<String, String>) -> Option<KeyCombo> {
+    let spec = game_keys.get(BATTLE_SPEED_FUNCTION)?;
+    spec.parse()
+        .map_err(|e| log::warn!("变速键无效: {}", e))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::GlobalSettings;
+    use std::time::Duration;
+
+    #[test]
+    fn test_toggle_by_sent_events() {
+        let tracker = GameSpeedTracker::from_game_keys(&GlobalSettings::default().game_keys);
+        let vk_code = crate::models::Key::Digit2.vk_code();
+        assert_eq!(tracker.speed(), GameSpeed::Normal);
+
+        // 只有按下计入
+        assert!(tracker.observe(&[InputEvent::KeyDown(vk_code), InputEvent::KeyUp(vk_code)]));
+        assert_eq!(tracker.speed(), GameSpeed::Double);
+        assert!(!tracker.observe(&[InputEvent::KeyDown(0x41), InputEvent::KeyUp(vk_code)]));
+        assert_eq!(tracker.speed(), GameSpeed::Double);
+        assert_eq!(tracker.speed().to_real(Duration::from_secs(1)), Duration::from_millis(500));
+
+        assert!(tracker.observe(&[InputEvent::KeyDown(vk_code)]));
+        assert_eq!(tracker.speed(), GameSpeed::Normal);
+
+        // 未配置变速键
+        tracker.set_toggle_key(None);
+        assert!(!tracker.observe(&[InputEvent::KeyDown(vk_code)]));
+        assert_eq!(tracker.toggle(), GameSpeed::Double);
+    }
+
+    #[test]
+    fn test_toggle_key_with_modifiers() {
+        let tracker = GameSpeedTracker::new(Some("Ctrl+2".parse().unwrap()));
+        let (ctrl, digit) = (Key::LeftCtrl.vk_code(), Key::Digit2.vk_code());
+
+        // 只有完整的组合键计入，修饰键可以在之前的批次中按下
+        assert!(!tracker.observe(&[InputEvent::KeyDown(digit), InputEvent::KeyUp(digit)]));
+        assert!(!tracker.observe(&[InputEvent::KeyDown(ctrl)]));
+        assert!(tracker.observe(&[InputEvent::KeyDown(digit), InputEvent::KeyUp(digit), InputEvent::KeyUp(ctrl)]));
+        assert_eq!(tracker.speed(), GameSpeed::Double);
+        assert_eq!(tracker.toggles_in(&[InputEvent::KeyDown(digit)]), 0);
+        assert_eq!(tracker.toggles_in(&[InputEvent::KeyDown(ctrl), InputEvent::KeyDown(digit)]), 1);
+
+        // 玩家按下的组合键
+        assert!(!tracker.observe_press(&"2".parse().unwrap()));
+        assert!(tracker.observe_press(&"RightCtrl+2".parse().unwrap()));
+        assert_eq!(tracker.speed(), GameSpeed::Normal);
+
+        // 进入战斗时重置
+        tracker.toggle();
+        tracker.on_game_state_changed(GameState::InBattle, GameState::InBattle);
+        assert_eq!(tracker.speed(), GameSpeed::Double);
+        tracker.on_game_state_changed(GameState::Detected, GameState::InBattle);
+        assert_eq!(tracker.speed(), GameSpeed::Normal);
+    }
+}
@@ -0,0 +1,428 @@
+//! 全局输入监听
+//!
+//! `rdev::listen` 会阻塞调用线程，且同一进程内只应启动一次。这里使用一个共享的
+//! 监听线程，把系统级键盘鼠标事件分发给所有订阅者。
+//!
+//! rdev 不区分本程序注入的按键，输入服务发送按键前在 `InjectedKeys` 中登记，
+//! 监听线程收到对应的事件时丢弃，订阅者只收到物理输入
+
+use crate::models::{Key, KeyCombo};
+use crate::services::input_backend::InputEvent;
+use crossbeam_channel::{select, unbounded, Receiver, Sender};
+use rdev::EventType;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// 注入的按键等待监听线程收到的最长时间，超时的记录被丢弃
+const INJECTED_KEY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// 输入事件来源
+///
+/// 全局监听使用 `GlobalInputListener`，测试可以提供预先准备好的事件
+pub trait InputEventSource: Send + Sync {
+    /// 订阅输入事件，来源停止时通道断开
+    fn subscribe(&self) -> Receiver<rdev::Event>;
+}
+
+/// 订阅者列表，断开的订阅者在下一次分发时移除
+type Subscribers = Arc<Mutex<Vec<Sender<rdev::Event>>>>;
+
+/// 共享的全局输入监听器
+pub struct GlobalInputListener {
+    /// 订阅者
+    subscribers: Subscribers,
+    /// 监听线程是否已启动
+    started: Arc<AtomicBool>,
+}
+
+impl GlobalInputListener {
+    /// 进程共享的监听器
+    pub fn shared() -> Arc<Self> {
+        static SHARED: OnceLock<Arc<GlobalInputListener>> = OnceLock::new();
+        Arc::clone(SHARED.get_or_init(|| {
+            Arc::new(Self {
+                subscribers: Arc::new(Mutex::new(Vec::new())),
+                started: Arc::new(AtomicBool::new(false)),
+            })
+        }))
+    }
+
+    /// 启动监听线程
+    fn start(&self) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let subscribers = Arc::clone(&self.subscribers);
+        let injected = InjectedKeys::shared();
+        let started = Arc::clone(&self.started);
+        let spawned = std::thread::Builder::new()
+            .name("global-input".to_string())
+            .spawn(move || {
+                if let Err(e) = rdev::listen(move |event| dispatch(&subscribers, &injected, event)) {
+                    log::error!("全局输入监听失败: {:?}", e);
+                    started.store(false, Ordering::SeqCst);
+                }
+            });
+        if let Err(e) = spawned {
+            log::error!("创建全局输入监听线程失败: {}", e);
+            self.started.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+impl InputEventSource for GlobalInputListener {
+    /// 订阅全局输入事件，首次订阅时启动监听线程
+    fn subscribe(&self) -> Receiver<rdev::Event> {
+        let (sender, receiver) = unbounded();
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(sender);
+        }
+        self.start();
+        receiver
+    }
+}
+
+/// 分发事件，本程序注入的按键不分发
+fn dispatch(subscribers: &Subscribers, injected: &InjectedKeys, event: rdev::Event) {
+    if injected.take(&event.event_type) {
+        return;
+    }
+    if let Ok(mut subscribers) = subscribers.lock() {
+        subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+}
+
+/// 本程序注入的按键记录
+///
+/// 左右修饰键视为同一个键：发送 `Shift` 时监听线程收到的是 `ShiftLeft`
+#[derive(Debug, Default)]
+pub struct InjectedKeys {
+    /// 等待监听线程收到的按键（按键，是否按下，发送时间）
+    pending: Mutex<VecDeque<(Key, bool, Instant)>>,
+}
+
+impl InjectedKeys {
+    /// 创建空记录
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 进程共享的记录，全局输入监听使用
+    pub fn shared() -> Arc<Self> {
+        static SHARED: OnceLock<Arc<InjectedKeys>> = OnceLock::new();
+        Arc::clone(SHARED.get_or_init(|| Arc::new(Self::new())))
+    }
+
+    /// 登记即将发送的事件，只记录按键
+    pub fn record(&self, events: &[InputEvent]) {
+        let now = Instant::now();
+        let keys = events.iter().filter_map(|event| match event {
+            InputEvent::KeyDown(vk) => Key::from_vk_code(*vk).map(|key| (key.generic(), true, now)),
+            InputEvent::KeyUp(vk) => Key::from_vk_code(*vk).map(|key| (key.generic(), false, now)),
+            _ => None,
+        });
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.extend(keys);
+        }
+    }
+
+    /// 收到的事件是否为登记过的注入按键，是则移除该记录
+    pub fn take(&self, event: &EventType) -> bool {
+        let (key, pressed) = match event {
+            EventType::KeyPress(key) => (key_from_rdev(*key), true),
+            EventType::KeyRelease(key) => (key_from_rdev(*key), false),
+            _ => return false,
+        };
+        let Some(key) = key.map(Key::generic) else {
+            return false;
+        };
+
+        let Ok(mut pending) = self.pending.lock() else {
+            return false;
+        };
+        let now = Instant::now();
+        pending.retain(|(_, _, sent_at)| now.saturating_duration_since(*sent_at) < INJECTED_KEY_TIMEOUT);
+        match pending.iter().position(|(sent, down, _)| *sent == key && *down == pressed) {
+            Some(index) => {
+                pending.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// 将 rdev 按键转换为按键
+pub fn key_from_rdev(key: rdev::Key) -> Option<Key> {
+    use rdev::Key as R;
+
+    let key = match key {
+        R::KeyA => Key::A, R::KeyB => Key::B, R::KeyC => Key::C, R::KeyD => Key::D,
+        R::KeyE => Key::E, R::KeyF => Key::F, R::KeyG => Key::G, R::KeyH => Key::H,
+        R::KeyI => Key::I, R::KeyJ => Key::J, R::KeyK => Key::K, R::KeyL => Key::L,
+        R::KeyM => Key::M, R::KeyN => Key::N, R::KeyO => Key::O, R::KeyP => Key::P,
+        R::KeyQ => Key::Q, R::KeyR => Key::R, R::KeyS => Key::S, R::KeyT => Key::T,
+        R::KeyU => Key::U, R::KeyV => Key::V, R::KeyW => Key::W, R::KeyX => Key::X,
+        R::KeyY => Key::Y, R::KeyZ => Key::Z,
+
+        R::Num0 => Key::Digit0, R::Num1 => Key::Digit1, R::Num2 => Key::Digit2,
+        R::Num3 => Key::Digit3, R::Num4 => Key::Digit4, R::Num5 => Key::Digit5,
+        R::Num6 => Key::Digit6, R::Num7 => Key::Digit7, R::Num8 => Key::Digit8,
+        R::Num9 => Key::Digit9,
+
+        R::F1 => Key::F1, R::F2 => Key::F2, R::F3 => Key::F3, R::F4 => Key::F4,
+        R::F5 => Key::F5, R::F6 => Key::F6, R::F7 => Key::F7, R::F8 => Key::F8,
+        R::F9 => Key::F9, R::F10 => Key::F10, R::F11 => Key::F11, R::F12 => Key::F12,
+
+        R::UpArrow => Key::Up, R::DownArrow => Key::Down,
+        R::LeftArrow => Key::Left, R::RightArrow => Key::Right,
+
+        R::Kp0 => Key::Numpad0, R::Kp1 => Key::Numpad1, R::Kp2 => Key::Numpad2,
+        R::Kp3 => Key::Numpad3, R::Kp4 => Key::Numpad4, R::Kp5 => Key::Numpad5,
+        R::Kp6 => Key::Numpad6, R::Kp7 => Key::Numpad7, R::Kp8 => Key::Numpad8,
+        R::Kp9 => Key::Numpad9,
+        R::KpMultiply => Key::NumpadMultiply, R::KpPlus => Key::NumpadAdd,
+        R::KpMinus => Key::NumpadSubtract, R::KpDelete => Key::NumpadDecimal,
+        R::KpDivide => Key::NumpadDivide, R::KpReturn => Key::Enter,
+
+        R::Minus => Key::Minus, R::Equal => Key::Equals, R::Comma => Key::Comma,
+        R::Dot => Key::Period, R::Slash => Key::Slash, R::SemiColon => Key::Semicolon,
+        R::Quote => Key::Quote, R::BackQuote => Key::Backquote,
+        R::LeftBracket => Key::LeftBracket, R::RightBracket => Key::RightBracket,
+        R::BackSlash | R::IntlBackslash => Key::Backslash,
+
+        R::ShiftLeft => Key::LeftShift, R::ShiftRight => Key::RightShift,
+        R::ControlLeft => Key::LeftCtrl, R::ControlRight => Key::RightCtrl,
+        R::Alt => Key::LeftAlt, R::AltGr => Key::RightAlt,
+        R::MetaLeft => Key::Win, R::MetaRight => Key::RightWin,
+
+        R::Space => Key::Space, R::Tab => Key::Tab, R::Return => Key::Enter,
+        R::Backspace => Key::Backspace, R::Escape => Key::Escape,
+        R::Delete => Key::Delete, R::Insert => Key::Insert,
+        R::Home => Key::Home, R::End => Key::End,
+        R::PageUp => Key::PageUp, R::PageDown => Key::PageDown,
+        R::CapsLock => Key::CapsLock, R::NumLock => Key::NumLock,
+        R::ScrollLock => Key::ScrollLock, R::PrintScreen => Key::PrintScreen,
+        R::Pause => Key::Pause,
+
+        // Windows 上未识别的按键携带虚拟键码
+        R::Unknown(code) => return u16::try_from(code).ok().and_then(Key::from_vk_code),
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// 将 rdev 鼠标按键转换为按键，只支持侧键
+///
+/// Windows 上侧键为 `Unknown(1)`、`Unknown(2)`，X11 上为 `Unknown(8)`、`Unknown(9)`
+pub fn key_from_rdev_button(button: rdev::Button) -> Option<Key> {
+    match button {
+        rdev::Button::Unknown(1 | 8) => Some(Key::Mouse4),
+        rdev::Button::Unknown(2 | 9) => Some(Key::Mouse5),
+        _ => None,
+    }
+}
+
+/// 按键状态跟踪
+///
+/// 根据按下和释放事件维护当前按住的按键（含鼠标侧键），普通按键按下时给出完整的组合键。
+/// 按住不放产生的重复按下事件不会重复给出组合键
+#[derive(Debug, Default, Clone)]
+pub struct KeyTracker {
+    /// 当前按住的按键（按按下顺序）
+    pressed: Vec<Key>,
+}
+
+impl KeyTracker {
+    /// 创建跟踪器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 处理按键按下，普通按键首次按下时返回组合键
+    pub fn press(&mut self, key: Key) -> Option<KeyCombo> {
+        if self.pressed.contains(&key) {
+            return None;
+        }
+        self.pressed.push(key);
+
+        if key.is_modifier() {
+            return None;
+        }
+        let modifiers = self.pressed.iter().copied().filter(|held| held.is_modifier());
+        Some(KeyCombo::new(modifiers, key))
+    }
+
+    /// 处理按键释放
+    pub fn release(&mut self, key: Key) {
+        self.pressed.retain(|held| *held != key);
+    }
+
+    /// 处理 rdev 事件，普通按键首次按下时返回组合键
+    pub fn handle(&mut self, event: &EventType) -> Option<KeyCombo> {
+        match event {
+            EventType::KeyPress(key) => key_from_rdev(*key).and_then(|key| self.press(key)),
+            EventType::KeyRelease(key) => {
+                if let Some(key) = key_from_rdev(*key) {
+                    self.release(key);
+                }
+                None
+            }
+            EventType::ButtonPress(button) => key_from_rdev_button(*button).and_then(|key| self.press(key)),
+            EventType::ButtonRelease(button) => {
+                if let Some(key) = key_from_rdev_button(*button) {
+                    self.release(key);
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// 当前按住的按键
+    pub fn pressed(&self) -> &[Key] {
+        &self.pressed
+    }
+
+    /// 清空按键状态
+    pub fn clear(&mut self) {
+        self.pressed.clear();
+    }
+}
+
+/// 全局组合键监视
+///
+/// 在独立线程上监听全局输入，按下指定组合键时调用回调。释放时停止监视
+pub struct ComboWatch {
+    /// 停止信号
+    stop: Option<Sender<()>>,
+}
+
+impl ComboWatch {
+    /// 开始监视全局组合键
+    pub fn spawn<F>(combo: KeyCombo, on_trigger: F) -> Self
+    where
+        F: Fn() + Send + 'static,
+    {
+        Self::with_source(GlobalInputListener::shared().as_ref(), combo, on_trigger)
+    }
+
+    /// 从指定事件来源监视组合键
+    pub fn with_source<F>(source: &dyn InputEventSource, combo: KeyCombo, on_trigger: F) -> Self
+    where
+        F: Fn() + Send + 'static,
+    {
+        let name = format!("combo-watch-{}", combo);
+        Self::each_press(source, name, move |pressed| {
+            if pressed.matches(&combo) {
+                on_trigger();
+            }
+        })
+    }
+
+    /// 从指定事件来源监视所有组合键，普通按键首次按下时以组合键调用回调
+    pub fn each_press<F>(source: &dyn InputEventSource, name: String, on_press: F) -> Self
+    where
+        F: Fn(&KeyCombo) + Send + 'static,
+    {
+        let events = source.subscribe();
+        let (stop, stopped) = unbounded::<()>();
+
+        let spawned = std::thread::Builder::new()
+            .name(name)
+            .spawn(move || {
+                let mut tracker = KeyTracker::new();
+                loop {
+                    select! {
+                        recv(events) -> event => match event {
+                            Ok(event) => {
+                                if let Some(pressed) = tracker.handle(&event.event_type) {
+                                    on_press(&pressed);
+                                }
+                            }
+                            Err(_) => return,
+                        },
+                        recv(stopped) -> _ => return,
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            log::error!("创建组合键监视线程失败: {}", e);
+        }
+
+        Self { stop: Some(stop) }
+    }
+
+    /// 停止监视
+    pub fn stop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+    }
+}
+
+impl Drop for ComboWatch {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_from_rdev() {
+        assert_eq!(key_from_rdev(rdev::Key::KeyA), Some(Key::A));
+        assert_eq!(key_from_rdev(rdev::Key::Num1), Some(Key::Digit1));
+        assert_eq!(key_from_rdev(rdev::Key::ControlLeft), Some(Key::LeftCtrl));
+        assert_eq!(key_from_rdev(rdev::Key::F12), Some(Key::F12));
+        assert_eq!(key_from_rdev(rdev::Key::Unknown(0x7C)), Some(Key::F13));
+        assert_eq!(key_from_rdev(rdev::Key::Function), None);
+        assert_eq!(key_from_rdev_button(rdev::Button::Unknown(8)), Some(Key::Mouse4));
+        assert_eq!(key_from_rdev_button(rdev::Button::Unknown(2)), Some(Key::Mouse5));
+        assert_eq!(key_from_rdev_button(rdev::Button::Left), None);
+    }
+
+    #[test]
+    fn test_tracker_reports_combo_once() {
+        let mut tracker = KeyTracker::new();
+        let panic_key: KeyCombo = "Ctrl+Shift+F12".parse().unwrap();
+
+        assert_eq!(tracker.handle(&EventType::KeyPress(rdev::Key::ControlLeft)), None);
+        assert_eq!(tracker.handle(&EventType::KeyPress(rdev::Key::ShiftRight)), None);
+        let combo = tracker.handle(&EventType::KeyPress(rdev::Key::F12)).unwrap();
+        assert!(combo.matches(&panic_key));
+
+        // 按住不放的重复按下事件
+        assert_eq!(tracker.handle(&EventType::KeyPress(rdev::Key::F12)), None);
+
+        tracker.handle(&EventType::KeyRelease(rdev::Key::F12));
+        tracker.handle(&EventType::KeyRelease(rdev::Key::ShiftRight));
+        let combo = tracker.handle(&EventType::KeyPress(rdev::Key::F12)).unwrap();
+        assert!(!combo.matches(&panic_key));
+        assert_eq!(tracker.pressed(), &[Key::LeftCtrl, Key::F12]);
+    }
+
+    #[test]
+    fn test_injected_keys_not_dispatched() {
+        let subscribers: Subscribers = Arc::new(Mutex::new(Vec::new()));
+        let (sender, receiver) = unbounded();
+        subscribers.lock().unwrap().push(sender);
+        let injected = InjectedKeys::new();
+        let event = |event_type| rdev::Event { time: std::time::SystemTime::now(), name: None, event_type };
+
+        // 注入的 Shift 对应监听到的左 Shift，每条记录只抵消一次
+        injected.record(&[InputEvent::KeyDown(0x10), InputEvent::KeyDown(0x32), InputEvent::MouseWheel(0, 120)]);
+        dispatch(&subscribers, &injected, event(EventType::KeyPress(rdev::Key::ShiftLeft)));
+        dispatch(&subscribers, &injected, event(EventType::KeyRelease(rdev::Key::Num2)));
+        dispatch(&subscribers, &injected, event(EventType::KeyPress(rdev::Key::Num2)));
+        dispatch(&subscribers, &injected, event(EventType::KeyPress(rdev::Key::Num2)));
+
+        let received: Vec<EventType> = receiver.try_iter().map(|event| event.event_type).collect();
+        assert_eq!(received, vec![EventType::KeyRelease(rdev::Key::Num2), EventType::KeyPress(rdev::Key::Num2)]);
+    }
+}
@@ -0,0 +1,515 @@
+//! 全局热键服务
+//!
+//! 监听全局键盘事件，按当前模式的热键配置把触发的热键（按下、单击、长按、双击、释放）
+//! 分派为 `GameOperation`，交给 `OperationExecutor` 执行。热键绑定在模式切换和热键更新时重新生成，
+//! 只有程序处于运行状态时才会分派。单击、长按、双击和释放在按下之后才触发，
+//! 按下时同名操作正在执行的热键按执行器忙碌时的冲突策略处理
+
+use crate::models::{GameOperation, Hotkey, Key, ProgramState, TriggerSettings};
+use crate::services::global_input::{key_from_rdev, key_from_rdev_button, InputEventSource};
+use crate::services::input_backend::{InputBackend, PlatformInputBackend};
+use crate::services::mode_manager::{ModeChangeEvent, ModeManager};
+use crate::services::operation_executor::{OperationExecutor, SubmitOutcome};
+use crate::services::state_manager::{StateChangeEvent, StateManager};
+use crate::services::trigger_matcher::TriggerMatcher;
+use crossbeam_channel::{at, never, select, unbounded, Sender};
+use rdev::EventType;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
+
+/// 全局热键服务
+pub struct HotkeyService<B: InputBackend + 'static = PlatformInputBackend> {
+    /// 模式管理器
+    mode_manager: Arc<RwLock<ModeManager>>,
+    /// 操作执行器
+    executor: Arc<OperationExecutor<B>>,
+    /// 热键绑定快照（按操作名称排序）
+    bindings: std::sync::RwLock<Vec<(Hotkey, GameOperation)>>,
+    /// 触发方式匹配器
+    matcher: Mutex<TriggerMatcher>,
+    /// 按键按下时正在执行的操作（按主按键记录）
+    press_owners: Mutex<HashMap<Key, String>>,
+    /// 是否分派热键
+    active: AtomicBool,
+}
+
+impl<B: InputBackend + 'static> HotkeyService<B> {
+    /// 创建热键服务，初始不分派热键
+    pub fn new(mode_manager: Arc<RwLock<ModeManager>>, executor: Arc<OperationExecutor<B>>) -> Arc<Self> {
+        Arc::new(Self {
+            mode_manager,
+            executor,
+            bindings: std::sync::RwLock::new(Vec::new()),
+            matcher: Mutex::new(TriggerMatcher::new(TriggerSettings::default())),
+            press_owners: Mutex::new(HashMap::new()),
+            active: AtomicBool::new(false),
+        })
+    }
+
+    /// 操作执行器
+    pub fn executor(&self) -> &Arc<OperationExecutor<B>> {
+        &self.executor
+    }
+
+    /// 设置长按时间和双击间隔
+    pub fn set_trigger_settings(&self, settings: TriggerSettings) {
+        if let Ok(mut matcher) = self.matcher.lock() {
+            matcher.set_settings(settings);
+        }
+    }
+
+    /// 根据模式管理器重新生成热键绑定，返回绑定数量
+    ///
+    /// 按键状态保留，按住的修饰键在重新绑定后仍然有效；绑定变化的按键不再按旧的绑定触发
+    pub async fn rebind(&self) -> usize {
+        let bindings = self.mode_manager.read().await.current_bindings();
+        let count = bindings.len();
+        if let Ok(mut matcher) = self.matcher.lock() {
+            matcher.set_hotkeys(bindings.iter().map(|(hotkey, _)| hotkey.clone()));
+        }
+        if let Ok(mut current) = self.bindings.write() {
+            *current = bindings;
+        }
+        log::debug!("热键绑定已更新: {} 个", count);
+        count
+    }
+
+    /// 触发的热键对应的操作
+    pub fn binding(&self, fired: &Hotkey) -> Option<GameOperation> {
+        self.bindings.read().ok()?
+            .iter()
+            .find(|(hotkey, _)| hotkey.matches(fired))
+            .map(|(_, operation)| operation.clone())
+    }
+
+    /// 是否分派热键
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// 启用或停用热键分派，停用时清空执行器和按键状态
+    pub fn set_active(&self, active: bool) {
+        if self.active.swap(active, Ordering::SeqCst) == active {
+            return;
+        }
+        if active {
+            log::info!("全局热键已启用");
+        } else {
+            self.clear_triggers();
+            let cleared = self.executor.stop();
+            log::info!("全局热键已停用，清除 {} 个排队操作", cleared);
+        }
+    }
+
+    /// 分派触发的热键，未分派时返回 `None`
+    ///
+    /// 全局监听不分发操作服务登记过的注入按键（见 `InjectedKeys`），序列发出的按键不会触发热键；
+    /// 同名操作执行期间再次触发，或按下时同名操作正在执行的热键，都按操作的 `ConflictPolicy` 处理。
+    /// 需要在 tokio 运行时中调用
+    pub fn dispatch(&self, fired: &Hotkey) -> Option<SubmitOutcome> {
+        if !self.is_active() {
+            return None;
+        }
+        let operation = self.binding(fired)?;
+        let owner = self.press_owners.lock().ok()
+            .and_then(|owners| owners.get(&fired.combo().key()).cloned());
+
+        let name = operation.name.clone();
+        let outcome = if owner.as_deref() == Some(name.as_str()) {
+            self.executor.submit_reentry(operation)
+        } else {
+            self.executor.submit(operation)
+        };
+        log::debug!("热键 {} 触发操作 {}: {:?}", fired, name, outcome);
+        Some(outcome)
+    }
+
+    /// 处理输入事件并分派触发的热键，返回触发的热键
+    ///
+    /// 未分派时同样跟踪按键状态。需要在 tokio 运行时中调用
+    pub fn handle_event(&self, event: &EventType, at: Instant) -> Vec<Hotkey> {
+        let fired = match self.matcher.lock() {
+            Ok(mut matcher) => {
+                // 只记录首次按下，按住时的重复按下事件不改变记录
+                if let Some(key) = pressed_key(event).filter(|key| !matcher.is_pressed(*key)) {
+                    self.record_press(key);
+                }
+                matcher.handle(event, at)
+            }
+            Err(_) => return Vec::new(),
+        };
+        self.dispatch_all(fired)
+    }
+
+    /// 判定到达时间点的长按和单击并分派，返回触发的热键
+    pub fn poll(&self, at: Instant) -> Vec<Hotkey> {
+        let fired = match self.matcher.lock() {
+            Ok(mut matcher) => matcher.poll(at),
+            Err(_) => return Vec::new(),
+        };
+        self.dispatch_all(fired)
+    }
+
+    /// 下一个需要调用 `poll` 的时间点
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.matcher.lock().ok()?.next_deadline()
+    }
+
+    /// 记录按键按下时正在执行的操作
+    fn record_press(&self, key: Key) {
+        if let Ok(mut owners) = self.press_owners.lock() {
+            match self.executor.running() {
+                Some(name) => {
+                    owners.insert(key, name);
+                }
+                None => {
+                    owners.remove(&key);
+                }
+            }
+        }
+    }
+
+    /// 清空按键状态、等待中的单击和按下记录
+    fn clear_triggers(&self) {
+        if let Ok(mut matcher) = self.matcher.lock() {
+            matcher.clear();
+        }
+        if let Ok(mut owners) = self.press_owners.lock() {
+            owners.clear();
+        }
+    }
+
+    fn dispatch_all(&self, fired: Vec<Hotkey>) -> Vec<Hotkey> {
+        for hotkey in &fired {
+            self.dispatch(hotkey);
+        }
+        fired
+    }
+
+    /// 开始监听全局热键
+    ///
+    /// 按程序当前状态决定是否分派，之后跟随状态变更和模式变更事件。
+    /// 需要在 tokio 运行时中调用，返回的句柄释放时停止监听
+    pub async fn start(self: &Arc<Self>, source: &dyn InputEventSource, state_manager: &RwLock<StateManager>) -> HotkeyHandle {
+        self.rebind().await;
+        let mode_events = self.mode_manager.read().await.subscribe_events();
+        let (state_events, program_state) = {
+            let state_manager = state_manager.read().await;
+            (state_manager.subscribe_events(), state_manager.get_program_state().await)
+        };
+        self.set_active(program_state == ProgramState::Running);
+
+        let task = tokio::spawn(Arc::clone(self).follow(mode_events, state_events));
+
+        let events = source.subscribe();
+        let (stop, stopped) = unbounded::<()>();
+        let runtime = tokio::runtime::Handle::current();
+        let service = Arc::clone(self);
+        let spawned = std::thread::Builder::new()
+            .name("hotkey-service".to_string())
+            .spawn(move || {
+                let _runtime = runtime.enter();
+                loop {
+                    let deadline = service.next_deadline().map(at).unwrap_or_else(never);
+                    select! {
+                        recv(events) -> event => match event {
+                            Ok(event) => {
+                                service.handle_event(&event.event_type, Instant::now());
+                            }
+                            Err(_) => return,
+                        },
+                        recv(deadline) -> _ => {
+                            service.poll(Instant::now());
+                        }
+                        recv(stopped) -> _ => return,
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            log::error!("创建全局热键线程失败: {}", e);
+        }
+
+        HotkeyHandle {
+            stop: Some(stop),
+            task: Some(task),
+        }
+    }
+
+    /// 跟随模式变更和程序状态变更
+    async fn follow(
+        self: Arc<Self>,
+        mut mode_events: broadcast::Receiver<ModeChangeEvent>,
+        mut state_events: broadcast::Receiver<StateChangeEvent>,
+    ) {
+        loop {
+            tokio::select! {
+                event = mode_events.recv() => match event {
+                    // 错过事件时同样重新绑定
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                        self.rebind().await;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+                event = state_events.recv() => match event {
+                    Ok(StateChangeEvent::ProgramStateChanged { new_state, .. }) => {
+                        self.set_active(new_state == ProgramState::Running);
+                    }
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+            }
+        }
+    }
+}
+
+/// 按下事件的按键
+fn pressed_key(event: &EventType) -> Option<Key> {
+    match event {
+        EventType::KeyPress(key) => key_from_rdev(*key),
+        EventType::ButtonPress(button) => key_from_rdev_button(*button),
+        _ => None,
+    }
+}
+
+/// 全局热键监听句柄
+///
+/// 释放时停止监听
+pub struct HotkeyHandle {
+    /// 按键线程停止信号
+    stop: Option<Sender<()>>,
+    /// 事件跟随任务
+    task: Option<JoinHandle<()>>,
+}
+
+impl HotkeyHandle {
+    /// 停止监听
+    pub fn stop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for HotkeyHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::action_service::ActionService;
+    use crate::services::input_backend::{InputEvent, RecordingInputBackend};
+    use crossbeam_channel::Receiver;
+    use rdev::{Event, EventType};
+    use std::sync::Mutex;
+    use std::time::{Duration, SystemTime};
+
+    /// 由测试控制发送时机的事件来源
+    struct ChannelSource {
+        receiver: Mutex<Option<Receiver<Event>>>,
+    }
+
+    impl InputEventSource for ChannelSource {
+        fn subscribe(&self) -> Receiver<Event> {
+            self.receiver.lock().unwrap().take().unwrap_or_else(|| unbounded().1)
+        }
+    }
+
+    /// 发送按下和释放，监听停止后的发送失败可以忽略
+    fn tap(sender: &Sender<Event>, key: rdev::Key) {
+        for event_type in [EventType::KeyPress(key), EventType::KeyRelease(key)] {
+            let _ = sender.send(Event { time: SystemTime::now(), name: None, event_type });
+        }
+    }
+
+    fn service() -> (Arc<HotkeyService<RecordingInputBackend>>, Arc<ActionService<RecordingInputBackend>>) {
+        let actions = Arc::new(ActionService::with_backend(RecordingInputBackend::new()));
+        let executor = OperationExecutor::new(Arc::clone(&actions), 4);
+        (HotkeyService::new(Arc::new(RwLock::new(ModeManager::new())), executor), actions)
+    }
+
+    fn pressed(actions: &ActionService<RecordingInputBackend>) -> Vec<u16> {
+        actions.backend().events().into_iter()
+            .filter_map(|event| match event {
+                InputEvent::KeyDown(vk) => Some(vk),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 等待后台线程和任务处理完事件
+    async fn settle(service: &HotkeyService<RecordingInputBackend>) {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        tokio::time::timeout(Duration::from_secs(2), service.executor().wait_idle()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_follows_bindings() {
+        let (service, _) = service();
+        assert_eq!(service.rebind().await, 5);
+
+        let key: Hotkey = "4".parse().unwrap();
+        assert_eq!(service.dispatch(&key), None);
+
+        service.set_active(true);
+        assert_eq!(service.dispatch(&key), Some(SubmitOutcome::Started));
+        assert_eq!(service.dispatch(&"Ctrl+4".parse().unwrap()), None);
+
+        // 执行期间再次触发时按冲突策略处理
+        assert_eq!(service.dispatch(&key), Some(SubmitOutcome::Queued(0)));
+        service.executor().wait_idle().await;
+        let mut focus = service.mode_manager.read().await.get_game_operation("focus_view").unwrap().clone();
+        focus.conflict_policy = crate::models::ConflictPolicy::Coalesce;
+        service.mode_manager.write().await.set_game_operation(focus);
+        service.rebind().await;
+        assert_eq!(service.dispatch(&key), Some(SubmitOutcome::Started));
+        assert_eq!(service.dispatch(&key), Some(SubmitOutcome::Coalesced));
+        service.executor().wait_idle().await;
+        assert_eq!(service.executor().stats().coalesced, 1);
+
+        // 重新绑定前仍使用旧的热键
+        service.mode_manager.write().await.update_hotkey("focus_view".to_string(), "Ctrl+F".to_string()).unwrap();
+        assert!(service.binding(&key).is_some());
+        service.rebind().await;
+        assert!(service.binding(&key).is_none());
+        assert_eq!(service.binding(&"Ctrl+F".parse().unwrap()).unwrap().name, "focus_view");
+    }
+
+    #[tokio::test]
+    async fn test_trigger_kinds_share_key() {
+        let (service, actions) = service();
+        {
+            let mut mode_manager = service.mode_manager.write().await;
+            mode_manager.update_hotkey("focus_view".to_string(), "tap:4".to_string()).unwrap();
+            mode_manager.update_hotkey("pause_game".to_string(), "hold:4".to_string()).unwrap();
+        }
+        service.rebind().await;
+        service.set_trigger_settings(TriggerSettings { hold_threshold_ms: 200, ..Default::default() });
+        service.set_active(true);
+
+        let origin = Instant::now();
+        let at = |ms: u64| origin + Duration::from_millis(ms);
+        let names = |fired: Vec<Hotkey>| -> Vec<String> {
+            fired.iter().map(|hotkey| service.binding(hotkey).unwrap().name).collect()
+        };
+
+        // 短按为单击
+        assert!(service.handle_event(&EventType::KeyPress(rdev::Key::Num4), at(0)).is_empty());
+        assert_eq!(names(service.handle_event(&EventType::KeyRelease(rdev::Key::Num4), at(80))), vec!["focus_view"]);
+        service.executor().wait_idle().await;
+        assert_eq!(pressed(&actions), vec![0x46]);
+
+        // 按住到长按时间时触发长按
+        assert!(service.handle_event(&EventType::KeyPress(rdev::Key::Num4), at(1000)).is_empty());
+        assert_eq!(service.next_deadline(), Some(at(1200)));
+        assert_eq!(names(service.poll(at(1200))), vec!["pause_game"]);
+        assert!(service.handle_event(&EventType::KeyRelease(rdev::Key::Num4), at(1500)).is_empty());
+        service.executor().wait_idle().await;
+        assert_eq!(pressed(&actions), vec![0x46, 0x1B]);
+    }
+
+    #[tokio::test]
+    async fn test_triggers_pressed_while_running() {
+        let (service, actions) = service();
+        {
+            let mut mode_manager = service.mode_manager.write().await;
+            mode_manager.update_hotkey("focus_view".to_string(), "tap:4".to_string()).unwrap();
+            let mut focus = mode_manager.get_game_operation("focus_view").unwrap().clone();
+            focus.conflict_policy = crate::models::ConflictPolicy::Coalesce;
+            mode_manager.set_game_operation(focus);
+        }
+        service.rebind().await;
+        service.set_active(true);
+
+        let origin = Instant::now();
+        let at = |ms: u64| origin + Duration::from_millis(ms);
+        let press = |ms: u64| service.handle_event(&EventType::KeyPress(rdev::Key::Num4), at(ms));
+        let release = |ms: u64| service.handle_event(&EventType::KeyRelease(rdev::Key::Num4), at(ms));
+
+        // 执行期间按下、结束后才释放的单击按忙碌时的冲突策略合并
+        press(0);
+        assert_eq!(release(80).len(), 1);
+        press(100);
+        service.executor().wait_idle().await;
+        assert_eq!(release(180).len(), 1);
+        service.executor().wait_idle().await;
+        assert_eq!(pressed(&actions), vec![0x46]);
+        assert_eq!(service.executor().stats().coalesced, 1);
+
+        // 操作结束后按下的单击正常触发
+        press(1000);
+        assert_eq!(release(1080).len(), 1);
+        service.executor().wait_idle().await;
+        assert_eq!(pressed(&actions), vec![0x46, 0x46]);
+
+        // 重新绑定时清除绑定变化的按键，停用时清空按住的按键
+        press(2000);
+        service.mode_manager.write().await.update_hotkey("focus_view".to_string(), "tap:5".to_string()).unwrap();
+        service.rebind().await;
+        assert!(release(2080).is_empty());
+        service.mode_manager.write().await.update_hotkey("focus_view".to_string(), "tap:4".to_string()).unwrap();
+        service.rebind().await;
+        press(3000);
+        service.set_active(false);
+        service.set_active(true);
+        assert!(release(3080).is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_listens_while_running() {
+        let (service, actions) = service();
+        let state_manager = RwLock::new(StateManager::new());
+        let (sender, receiver) = unbounded();
+        let source = ChannelSource { receiver: Mutex::new(Some(receiver)) };
+
+        let mut handle = service.start(&source, &state_manager).await;
+        assert!(!service.is_active());
+
+        // 未运行时不分派
+        tap(&sender, rdev::Key::Num4);
+        settle(&service).await;
+        assert!(pressed(&actions).is_empty());
+
+        state_manager.write().await.update_game_state(crate::models::GameState::Detected).await;
+        state_manager.write().await.start_core().await.unwrap();
+        settle(&service).await;
+        assert!(service.is_active());
+        tap(&sender, rdev::Key::Num4);
+        settle(&service).await;
+        assert_eq!(pressed(&actions), vec![0x46]);
+
+        // 热键更新后立即生效
+        service.mode_manager.write().await.update_hotkey("focus_view".to_string(), "5".to_string()).unwrap();
+        settle(&service).await;
+        tap(&sender, rdev::Key::Num4);
+        tap(&sender, rdev::Key::Num5);
+        settle(&service).await;
+        assert_eq!(pressed(&actions), vec![0x46, 0x46]);
+
+        // 切换模式后使用新模式的热键
+        service.mode_manager.write().await.switch_mode(crate::models::OperationMode::Intelligent).unwrap();
+        settle(&service).await;
+        tap(&sender, rdev::Key::Num5);
+        tap(&sender, rdev::Key::Num4);
+        settle(&service).await;
+        assert_eq!(pressed(&actions), vec![0x46, 0x46, 0x46]);
+
+        state_manager.write().await.stop_core().await.unwrap();
+        settle(&service).await;
+        assert!(!service.is_active());
+
+        handle.stop();
+        tap(&sender, rdev::Key::Num4);
+        settle(&service).await;
+        assert_eq!(pressed(&actions).len(), 3);
+    }
+}
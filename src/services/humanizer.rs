@@ -0,0 +1,173 @@
+//! 拟人化输入
+//!
+//! 按序列的 `HumanizeConfig` 为等待时长、点击位置和拖拽路径加入有界的随机偏移
+
+use crate::models::HumanizeConfig;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f64::consts::PI;
+use std::time::Duration;
+
+/// 拟人化随机偏移生成器
+///
+/// 使用相同种子和相同调用顺序时结果完全一致
+#[derive(Debug, Clone)]
+pub struct Humanizer {
+    /// 当前生效的设置
+    config: HumanizeConfig,
+    /// 随机数生成器
+    rng: StdRng,
+}
+
+impl Humanizer {
+    /// 根据设置创建，偏移量超过上限时按上限处理
+    pub fn new(config: HumanizeConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+        Self {
+            config: config.clamped(),
+            rng,
+        }
+    }
+
+    /// 当前生效的设置
+    pub fn config(&self) -> HumanizeConfig {
+        self.config
+    }
+
+    /// 切换设置（例如进入被调用的序列），保留随机数状态，返回之前的设置
+    pub fn replace_config(&mut self, config: HumanizeConfig) -> HumanizeConfig {
+        std::mem::replace(&mut self.config, config.clamped())
+    }
+
+    /// 是否启用
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 为等待时长加入偏移，结果不小于 0
+    pub fn wait(&mut self, duration: Duration) -> Duration {
+        let jitter = self.config.wait_jitter.as_nanos() as i64;
+        if !self.config.enabled || jitter == 0 {
+            return duration;
+        }
+
+        let offset = self.rng.random_range(-jitter..=jitter);
+        let nanos = (duration.as_nanos() as i64).saturating_add(offset).max(0);
+        Duration::from_nanos(nanos as u64)
+    }
+
+    /// 为点击位置加入偏移
+    pub fn position(&mut self, (x, y): (i32, i32)) -> (i32, i32) {
+        let jitter = self.config.position_jitter as i32;
+        if !self.config.enabled || jitter == 0 {
+            return (x, y);
+        }
+
+        (
+            x + self.rng.random_range(-jitter..=jitter),
+            y + self.rng.random_range(-jitter..=jitter),
+        )
+    }
+
+    /// 将拖拽路径弯曲成随机弧线
+    ///
+    /// 偏移按 sin(πt) 分布，中段最大，起点和终点保持不变
+    pub fn drag_path(&mut self, path: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+        let jitter = self.config.drag_jitter as i32;
+        if !self.config.enabled || jitter == 0 || path.len() < 2 {
+            return path;
+        }
+
+        let amplitude_x = self.rng.random_range(-jitter..=jitter) as f64;
+        let amplitude_y = self.rng.random_range(-jitter..=jitter) as f64;
+        let steps = path.len() as f64;
+
+        path.into_iter()
+            .enumerate()
+            .map(|(index, (x, y))| {
+                let bend = (PI * (index + 1) as f64 / steps).sin();
+                (
+                    x + (amplitude_x * bend).round() as i32,
+                    y + (amplitude_y * bend).round() as i32,
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{drag_path, Easing};
+
+    fn config(seed: u64) -> HumanizeConfig {
+        HumanizeConfig {
+            enabled: true,
+            wait_jitter: Duration::from_millis(20),
+            position_jitter: 5,
+            drag_jitter: 10,
+            seed: Some(seed),
+        }
+    }
+
+    #[test]
+    fn test_disabled_is_identity() {
+        let mut humanizer = Humanizer::new(HumanizeConfig {
+            enabled: false,
+            ..config(1)
+        });
+        let path = drag_path((0, 0), (100, 0), 5, Easing::Linear);
+
+        assert_eq!(humanizer.wait(Duration::from_millis(33)), Duration::from_millis(33));
+        assert_eq!(humanizer.position((10, 20)), (10, 20));
+        assert_eq!(humanizer.drag_path(path.clone()), path);
+    }
+
+    #[test]
+    fn test_jitter_is_bounded_and_reproducible() {
+        let mut first = Humanizer::new(config(42));
+        let mut second = Humanizer::new(config(42));
+
+        for _ in 0..100 {
+            let wait = first.wait(Duration::from_millis(100));
+            assert_eq!(wait, second.wait(Duration::from_millis(100)));
+            assert!(wait >= Duration::from_millis(80) && wait <= Duration::from_millis(120));
+
+            let (x, y) = first.position((500, 500));
+            assert_eq!((x, y), second.position((500, 500)));
+            assert!((495..=505).contains(&x) && (495..=505).contains(&y));
+        }
+
+        // 短等待不会变为负数
+        assert!(first.wait(Duration::from_millis(1)) <= Duration::from_millis(21));
+    }
+
+    #[test]
+    fn test_drag_path_keeps_end_point() {
+        let mut humanizer = Humanizer::new(config(7));
+        let path = drag_path((0, 0), (200, 100), 8, Easing::EaseInOut);
+
+        let bent = humanizer.drag_path(path.clone());
+        assert_eq!(bent.len(), path.len());
+        assert_eq!(bent.last(), path.last());
+        for (original, bent) in path.iter().zip(&bent) {
+            assert!((original.0 - bent.0).abs() <= 10);
+            assert!((original.1 - bent.1).abs() <= 10);
+        }
+    }
+
+    #[test]
+    fn test_config_is_clamped() {
+        let humanizer = Humanizer::new(HumanizeConfig {
+            wait_jitter: Duration::from_secs(10),
+            position_jitter: 1000,
+            ..config(1)
+        });
+
+        assert_eq!(humanizer.config().wait_jitter, crate::models::MAX_WAIT_JITTER);
+        assert_eq!(humanizer.config().position_jitter, crate::models::MAX_POSITION_JITTER);
+    }
+}
@@ -0,0 +1,654 @@
+//! 输入后端
+//!
+//! 抽象键盘和鼠标事件的实际注入方式，使操作服务可以在不同平台
+//! 或测试环境中复用同一套执行逻辑
+
+use crate::models::MouseButton;
+use crate::utils::{ActionError, ActionResult};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[cfg(windows)]
+use winapi::um::winuser::{
+    SendInput, INPUT, INPUT_KEYBOARD, INPUT_MOUSE, KEYBDINPUT, MOUSEINPUT,
+    KEYEVENTF_KEYUP, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
+    MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_MIDDLEDOWN,
+    MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_WHEEL, MOUSEEVENTF_HWHEEL, MOUSEEVENTF_MOVE,
+    GetCursorPos, SetCursorPos,
+};
+
+/// 输入事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputEvent {
+    /// 按键按下（虚拟键码）
+    KeyDown(u16),
+    /// 按键释放（虚拟键码）
+    KeyUp(u16),
+    /// 鼠标移动到屏幕坐标
+    MouseMove(i32, i32),
+    /// 鼠标相对移动 (dx, dy)
+    MouseMoveRelative(i32, i32),
+    /// 鼠标按钮按下
+    MouseDown(MouseButton),
+    /// 鼠标按钮释放
+    MouseUp(MouseButton),
+    /// 鼠标滚轮 (水平, 垂直)
+    MouseWheel(i32, i32),
+}
+
+/// 输入后端trait
+///
+/// 键码统一使用 Windows 虚拟键码，其他平台的实现负责转换
+pub trait InputBackend: Send + Sync {
+    /// 后端名称
+    fn name(&self) -> &str;
+
+    /// 按下按键
+    fn key_down(&self, vk_code: u16) -> ActionResult<()>;
+
+    /// 释放按键
+    fn key_up(&self, vk_code: u16) -> ActionResult<()>;
+
+    /// 移动鼠标到屏幕坐标
+    fn mouse_move(&self, x: i32, y: i32) -> ActionResult<()>;
+
+    /// 相对当前位置移动鼠标
+    fn mouse_move_relative(&self, dx: i32, dy: i32) -> ActionResult<()>;
+
+    /// 按下或释放鼠标按钮
+    fn mouse_button(&self, button: MouseButton, pressed: bool) -> ActionResult<()>;
+
+    /// 滚动鼠标滚轮（120 为一格，正值向上/向右）
+    fn mouse_wheel(&self, dx: i32, dy: i32) -> ActionResult<()>;
+
+    /// 获取当前鼠标位置
+    fn cursor_position(&self) -> ActionResult<(i32, i32)>;
+
+    /// 发送单个输入事件
+    fn send_event(&self, event: InputEvent) -> ActionResult<()> {
+        match event {
+            InputEvent::KeyDown(vk_code) => self.key_down(vk_code),
+            InputEvent::KeyUp(vk_code) => self.key_up(vk_code),
+            InputEvent::MouseMove(x, y) => self.mouse_move(x, y),
+            InputEvent::MouseMoveRelative(dx, dy) => self.mouse_move_relative(dx, dy),
+            InputEvent::MouseDown(button) => self.mouse_button(button, true),
+            InputEvent::MouseUp(button) => self.mouse_button(button, false),
+            InputEvent::MouseWheel(dx, dy) => self.mouse_wheel(dx, dy),
+        }
+    }
+
+    /// 批量发送输入事件
+    ///
+    /// 支持原子注入的后端应重写此方法，保证事件之间不被其他输入插入
+    fn send_events(&self, events: &[InputEvent]) -> ActionResult<()> {
+        for event in events {
+            self.send_event(*event)?;
+        }
+        Ok(())
+    }
+}
+
+/// 记录的输入事件
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecordedInputEvent {
+    /// 输入事件
+    pub event: InputEvent,
+    /// 相对于后端创建时间的偏移
+    pub timestamp: Duration,
+}
+
+/// 记录输入后端
+///
+/// 不向系统注入任何输入，只记录收到的事件及其时间戳，用于测试
+pub struct RecordingInputBackend {
+    /// 已记录的事件
+    events: Mutex<Vec<RecordedInputEvent>>,
+    /// 模拟的鼠标位置
+    cursor: Mutex<(i32, i32)>,
+    /// 下一次发送时失败的事件
+    failures: Mutex<Vec<InputEvent>>,
+    /// 创建时间
+    start_time: Instant,
+}
+
+impl RecordingInputBackend {
+    /// 创建新的记录后端
+    pub fn new() -> Self {
+        Self {
+            events: Mutex::new(Vec::new()),
+            cursor: Mutex::new((0, 0)),
+            failures: Mutex::new(Vec::new()),
+            start_time: Instant::now(),
+        }
+    }
+
+    /// 获取所有已记录的事件（含时间戳）
+    pub fn recorded_events(&self) -> Vec<RecordedInputEvent> {
+        self.events.lock()
+            .map(|events| events.clone())
+            .unwrap_or_default()
+    }
+
+    /// 获取已记录的事件流（不含时间戳）
+    pub fn events(&self) -> Vec<InputEvent> {
+        self.recorded_events().into_iter().map(|e| e.event).collect()
+    }
+
+    /// 取出并清空已记录的事件
+    pub fn take_events(&self) -> Vec<RecordedInputEvent> {
+        self.events.lock()
+            .map(|mut events| std::mem::take(&mut *events))
+            .unwrap_or_default()
+    }
+
+    /// 清空已记录的事件
+    pub fn clear(&self) {
+        if let Ok(mut events) = self.events.lock() {
+            events.clear();
+        }
+    }
+
+    /// 让下一次发送该事件时失败（只失败一次，失败的事件不记录），用于模拟注入失败
+    pub fn fail_on(&self, event: InputEvent) {
+        if let Ok(mut failures) = self.failures.lock() {
+            failures.push(event);
+        }
+    }
+
+    /// 记录事件
+    fn record(&self, event: InputEvent) -> ActionResult<()> {
+        if let Ok(mut failures) = self.failures.lock() {
+            if let Some(index) = failures.iter().position(|failure| *failure == event) {
+                failures.remove(index);
+                return Err(ActionError::SystemCall(format!("模拟发送失败: {:?}", event)));
+            }
+        }
+
+        let mut events = self.events.lock()
+            .map_err(|_| ActionError::SystemCall("记录事件锁定失败".to_string()))?;
+        events.push(RecordedInputEvent {
+            event,
+            timestamp: self.start_time.elapsed(),
+        });
+        Ok(())
+    }
+}
+
+impl Default for RecordingInputBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputBackend for RecordingInputBackend {
+    fn name(&self) -> &str {
+        "recording"
+    }
+
+    fn key_down(&self, vk_code: u16) -> ActionResult<()> {
+        self.record(InputEvent::KeyDown(vk_code))
+    }
+
+    fn key_up(&self, vk_code: u16) -> ActionResult<()> {
+        self.record(InputEvent::KeyUp(vk_code))
+    }
+
+    fn mouse_move(&self, x: i32, y: i32) -> ActionResult<()> {
+        if let Ok(mut cursor) = self.cursor.lock() {
+            *cursor = (x, y);
+        }
+        self.record(InputEvent::MouseMove(x, y))
+    }
+
+    fn mouse_move_relative(&self, dx: i32, dy: i32) -> ActionResult<()> {
+        if let Ok(mut cursor) = self.cursor.lock() {
+            *cursor = (cursor.0 + dx, cursor.1 + dy);
+        }
+        self.record(InputEvent::MouseMoveRelative(dx, dy))
+    }
+
+    fn mouse_button(&self, button: MouseButton, pressed: bool) -> ActionResult<()> {
+        if pressed {
+            self.record(InputEvent::MouseDown(button))
+        } else {
+            self.record(InputEvent::MouseUp(button))
+        }
+    }
+
+    fn mouse_wheel(&self, dx: i32, dy: i32) -> ActionResult<()> {
+        self.record(InputEvent::MouseWheel(dx, dy))
+    }
+
+    fn cursor_position(&self) -> ActionResult<(i32, i32)> {
+        self.cursor.lock()
+            .map(|cursor| *cursor)
+            .map_err(|_| ActionError::SystemCall("鼠标位置锁定失败".to_string()))
+    }
+}
+
+/// 不支持的平台使用的输入后端（占位符）
+#[derive(Debug, Default)]
+pub struct UnsupportedInputBackend;
+
+impl InputBackend for UnsupportedInputBackend {
+    fn name(&self) -> &str {
+        "unsupported"
+    }
+
+    fn key_down(&self, vk_code: u16) -> ActionResult<()> {
+        log::warn!("按键操作在当前平台上不支持: 0x{:02X}", vk_code);
+        Err(ActionError::UnsupportedPlatform("Key press not supported on this platform".to_string()))
+    }
+
+    fn key_up(&self, vk_code: u16) -> ActionResult<()> {
+        log::warn!("按键操作在当前平台上不支持: 0x{:02X}", vk_code);
+        Err(ActionError::UnsupportedPlatform("Key press not supported on this platform".to_string()))
+    }
+
+    fn mouse_move(&self, x: i32, y: i32) -> ActionResult<()> {
+        log::warn!("鼠标移动在当前平台上不支持: ({}, {})", x, y);
+        Err(ActionError::UnsupportedPlatform("Mouse move not supported on this platform".to_string()))
+    }
+
+    fn mouse_move_relative(&self, dx: i32, dy: i32) -> ActionResult<()> {
+        log::warn!("鼠标相对移动在当前平台上不支持: ({}, {})", dx, dy);
+        Err(ActionError::UnsupportedPlatform("Mouse move not supported on this platform".to_string()))
+    }
+
+    fn mouse_button(&self, button: MouseButton, _pressed: bool) -> ActionResult<()> {
+        log::warn!("鼠标点击在当前平台上不支持: {:?}", button);
+        Err(ActionError::UnsupportedPlatform("Mouse click not supported on this platform".to_string()))
+    }
+
+    fn mouse_wheel(&self, dx: i32, dy: i32) -> ActionResult<()> {
+        log::warn!("鼠标滚轮在当前平台上不支持: ({}, {})", dx, dy);
+        Err(ActionError::UnsupportedPlatform("Mouse wheel not supported on this platform".to_string()))
+    }
+
+    fn cursor_position(&self) -> ActionResult<(i32, i32)> {
+        Err(ActionError::UnsupportedPlatform("Get cursor position not supported on this platform".to_string()))
+    }
+}
+
+/// Windows 输入后端（SendInput / SetCursorPos）
+#[cfg(windows)]
+pub struct WindowsInputBackend;
+
+#[cfg(windows)]
+impl WindowsInputBackend {
+    /// 创建新的 Windows 输入后端
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 将输入事件转换为 SendInput 结构
+    ///
+    /// 鼠标移动不经过 SendInput，返回 None
+    unsafe fn to_input(event: InputEvent) -> Option<INPUT> {
+        match event {
+            InputEvent::KeyDown(vk_code) | InputEvent::KeyUp(vk_code) => {
+                let mut input = INPUT {
+                    type_: INPUT_KEYBOARD,
+                    u: std::mem::zeroed(),
+                };
+                *input.u.ki_mut() = KEYBDINPUT {
+                    wVk: vk_code,
+                    wScan: 0,
+                    dwFlags: if matches!(event, InputEvent::KeyUp(_)) { KEYEVENTF_KEYUP } else { 0 },
+                    time: 0,
+                    dwExtraInfo: 0,
+                };
+                Some(input)
+            }
+            InputEvent::MouseDown(button) | InputEvent::MouseUp(button) => {
+                let (down_flag, up_flag) = match button {
+                    MouseButton::Left => (MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP),
+                    MouseButton::Right => (MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP),
+                    MouseButton::Middle => (MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP),
+                };
+                Some(Self::mouse_input(
+                    if matches!(event, InputEvent::MouseDown(_)) { down_flag } else { up_flag },
+                    0,
+                ))
+            }
+            InputEvent::MouseWheel(dx, dy) => {
+                // 同时存在两个方向时分两次发送，这里只处理单一方向
+                if dy != 0 {
+                    Some(Self::mouse_input(MOUSEEVENTF_WHEEL, dy as u32))
+                } else {
+                    Some(Self::mouse_input(MOUSEEVENTF_HWHEEL, dx as u32))
+                }
+            }
+            InputEvent::MouseMoveRelative(dx, dy) => {
+                let mut input = Self::mouse_input(MOUSEEVENTF_MOVE, 0);
+                input.u.mi_mut().dx = dx;
+                input.u.mi_mut().dy = dy;
+                Some(input)
+            }
+            InputEvent::MouseMove(_, _) => None,
+        }
+    }
+
+    /// 构造鼠标输入结构
+    unsafe fn mouse_input(flags: u32, mouse_data: u32) -> INPUT {
+        let mut input = INPUT {
+            type_: INPUT_MOUSE,
+            u: std::mem::zeroed(),
+        };
+        *input.u.mi_mut() = MOUSEINPUT {
+            dx: 0,
+            dy: 0,
+            mouseData: mouse_data,
+            dwFlags: flags,
+            time: 0,
+            dwExtraInfo: 0,
+        };
+        input
+    }
+
+    /// 调用 SendInput 注入输入
+    fn send_inputs(inputs: &[INPUT], description: &str) -> ActionResult<()> {
+        if inputs.is_empty() {
+            return Ok(());
+        }
+
+        let result = unsafe {
+            SendInput(
+                inputs.len() as u32,
+                inputs.as_ptr() as *mut INPUT,
+                std::mem::size_of::<INPUT>() as i32,
+            )
+        };
+
+        if result != inputs.len() as u32 {
+            return Err(ActionError::SystemCall(format!(
+                "SendInput failed for {}", description
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+impl Default for WindowsInputBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(windows)]
+impl InputBackend for WindowsInputBackend {
+    fn name(&self) -> &str {
+        "windows"
+    }
+
+    fn key_down(&self, vk_code: u16) -> ActionResult<()> {
+        self.send_events(&[InputEvent::KeyDown(vk_code)])
+    }
+
+    fn key_up(&self, vk_code: u16) -> ActionResult<()> {
+        self.send_events(&[InputEvent::KeyUp(vk_code)])
+    }
+
+    fn mouse_move(&self, x: i32, y: i32) -> ActionResult<()> {
+        unsafe {
+            let result = SetCursorPos(x, y);
+            if result == 0 {
+                return Err(ActionError::SystemCall(format!(
+                    "SetCursorPos failed for position: ({}, {})", x, y
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn mouse_move_relative(&self, dx: i32, dy: i32) -> ActionResult<()> {
+        self.send_events(&[InputEvent::MouseMoveRelative(dx, dy)])
+    }
+
+    fn mouse_button(&self, button: MouseButton, pressed: bool) -> ActionResult<()> {
+        if pressed {
+            self.send_events(&[InputEvent::MouseDown(button)])
+        } else {
+            self.send_events(&[InputEvent::MouseUp(button)])
+        }
+    }
+
+    fn mouse_wheel(&self, dx: i32, dy: i32) -> ActionResult<()> {
+        self.send_events(&[InputEvent::MouseWheel(dx, dy)])
+    }
+
+    fn cursor_position(&self) -> ActionResult<(i32, i32)> {
+        unsafe {
+            let mut point = winapi::shared::windef::POINT { x: 0, y: 0 };
+            let result = GetCursorPos(&mut point);
+            if result == 0 {
+                return Err(ActionError::SystemCall("GetCursorPos failed".to_string()));
+            }
+            Ok((point.x, point.y))
+        }
+    }
+
+    /// 将连续的按键/按钮事件合并为一次 SendInput 调用，鼠标移动单独处理
+    fn send_events(&self, events: &[InputEvent]) -> ActionResult<()> {
+        let mut pending: Vec<INPUT> = Vec::new();
+
+        for event in events {
+            match event {
+                InputEvent::MouseMove(x, y) => {
+                    Self::send_inputs(&pending, "input batch")?;
+                    pending.clear();
+                    self.mouse_move(*x, *y)?;
+                }
+                InputEvent::MouseWheel(dx, dy) if *dx != 0 && *dy != 0 => {
+                    unsafe {
+                        pending.push(Self::mouse_input(MOUSEEVENTF_WHEEL, *dy as u32));
+                        pending.push(Self::mouse_input(MOUSEEVENTF_HWHEEL, *dx as u32));
+                    }
+                }
+                _ => {
+                    if let Some(input) = unsafe { Self::to_input(*event) } {
+                        pending.push(input);
+                    }
+                }
+            }
+        }
+
+        Self::send_inputs(&pending, "input batch")
+    }
+}
+
+/// 当前平台的默认输入后端
+#[cfg(windows)]
+pub type PlatformInputBackend = WindowsInputBackend;
+
+/// 当前平台的默认输入后端
+#[cfg(target_os = "linux")]
+pub type PlatformInputBackend = crate::services::linux_input_backend::LinuxInputBackend;
+
+/// 当前平台的默认输入后端
+#[cfg(not(any(windows, target_os = "linux")))]
+pub type PlatformInputBackend = UnsupportedInputBackend;
+
+/// 运行时选择的输入后端
+impl InputBackend for Box<dyn InputBackend> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn key_down(&self, vk_code: u16) -> ActionResult<()> {
+        (**self).key_down(vk_code)
+    }
+
+    fn key_up(&self, vk_code: u16) -> ActionResult<()> {
+        (**self).key_up(vk_code)
+    }
+
+    fn mouse_move(&self, x: i32, y: i32) -> ActionResult<()> {
+        (**self).mouse_move(x, y)
+    }
+
+    fn mouse_move_relative(&self, dx: i32, dy: i32) -> ActionResult<()> {
+        (**self).mouse_move_relative(dx, dy)
+    }
+
+    fn mouse_button(&self, button: MouseButton, pressed: bool) -> ActionResult<()> {
+        (**self).mouse_button(button, pressed)
+    }
+
+    fn mouse_wheel(&self, dx: i32, dy: i32) -> ActionResult<()> {
+        (**self).mouse_wheel(dx, dy)
+    }
+
+    fn cursor_position(&self) -> ActionResult<(i32, i32)> {
+        (**self).cursor_position()
+    }
+
+    fn send_event(&self, event: InputEvent) -> ActionResult<()> {
+        (**self).send_event(event)
+    }
+
+    fn send_events(&self, events: &[InputEvent]) -> ActionResult<()> {
+        (**self).send_events(events)
+    }
+}
+
+/// 共享的输入后端
+impl<T: InputBackend + ?Sized> InputBackend for Arc<T> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn key_down(&self, vk_code: u16) -> ActionResult<()> {
+        (**self).key_down(vk_code)
+    }
+
+    fn key_up(&self, vk_code: u16) -> ActionResult<()> {
+        (**self).key_up(vk_code)
+    }
+
+    fn mouse_move(&self, x: i32, y: i32) -> ActionResult<()> {
+        (**self).mouse_move(x, y)
+    }
+
+    fn mouse_move_relative(&self, dx: i32, dy: i32) -> ActionResult<()> {
+        (**self).mouse_move_relative(dx, dy)
+    }
+
+    fn mouse_button(&self, button: MouseButton, pressed: bool) -> ActionResult<()> {
+        (**self).mouse_button(button, pressed)
+    }
+
+    fn mouse_wheel(&self, dx: i32, dy: i32) -> ActionResult<()> {
+        (**self).mouse_wheel(dx, dy)
+    }
+
+    fn cursor_position(&self) -> ActionResult<(i32, i32)> {
+        (**self).cursor_position()
+    }
+
+    fn send_event(&self, event: InputEvent) -> ActionResult<()> {
+        (**self).send_event(event)
+    }
+
+    fn send_events(&self, events: &[InputEvent]) -> ActionResult<()> {
+        (**self).send_events(events)
+    }
+}
+
+/// 根据名称创建输入后端
+///
+/// 支持 "platform"、"recording"，Linux 上还支持 "uinput"、"xtest" 和 "auto"
+pub fn create_input_backend(name: &str) -> ActionResult<Box<dyn InputBackend>> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "" | "platform" => Ok(Box::new(PlatformInputBackend::default())),
+        "recording" => Ok(Box::new(RecordingInputBackend::new())),
+        #[cfg(target_os = "linux")]
+        other => {
+            use crate::services::linux_input_backend::{LinuxInputBackend, LinuxInputMethod};
+            let method: LinuxInputMethod = other.parse()?;
+            Ok(Box::new(LinuxInputBackend::new(method)))
+        }
+        #[cfg(not(target_os = "linux"))]
+        other => Err(ActionError::UnsupportedPlatform(format!("不支持的输入后端: {}", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_backend_records_events() {
+        let backend = RecordingInputBackend::new();
+
+        backend.key_down(0x41).unwrap();
+        backend.key_up(0x41).unwrap();
+        backend.mouse_move(10, 20).unwrap();
+        backend.mouse_button(MouseButton::Left, true).unwrap();
+        backend.mouse_button(MouseButton::Left, false).unwrap();
+        backend.mouse_wheel(0, -120).unwrap();
+        backend.mouse_move_relative(-3, 4).unwrap();
+
+        assert_eq!(backend.events(), vec![
+            InputEvent::KeyDown(0x41),
+            InputEvent::KeyUp(0x41),
+            InputEvent::MouseMove(10, 20),
+            InputEvent::MouseDown(MouseButton::Left),
+            InputEvent::MouseUp(MouseButton::Left),
+            InputEvent::MouseWheel(0, -120),
+            InputEvent::MouseMoveRelative(-3, 4),
+        ]);
+        assert_eq!(backend.cursor_position().unwrap(), (7, 24));
+    }
+
+    #[test]
+    fn test_recording_backend_timestamps_are_monotonic() {
+        let backend = RecordingInputBackend::new();
+
+        backend.send_events(&[InputEvent::KeyDown(0x20), InputEvent::KeyUp(0x20)]).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        backend.send_event(InputEvent::MouseMove(1, 1)).unwrap();
+
+        let recorded = backend.recorded_events();
+        assert_eq!(recorded.len(), 3);
+        assert!(recorded[0].timestamp <= recorded[1].timestamp);
+        assert!(recorded[2].timestamp - recorded[1].timestamp >= Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_recording_backend_take_and_clear() {
+        let backend = RecordingInputBackend::new();
+
+        backend.key_down(0x31).unwrap();
+        assert_eq!(backend.take_events().len(), 1);
+        assert!(backend.events().is_empty());
+
+        backend.key_up(0x31).unwrap();
+        backend.clear();
+        assert!(backend.events().is_empty());
+    }
+
+    #[test]
+    fn test_unsupported_backend() {
+        let backend = UnsupportedInputBackend;
+
+        assert!(matches!(backend.key_down(0x41), Err(ActionError::UnsupportedPlatform(_))));
+        assert!(matches!(backend.cursor_position(), Err(ActionError::UnsupportedPlatform(_))));
+    }
+
+    #[test]
+    fn test_create_input_backend_by_name() {
+        let backend = create_input_backend("recording").unwrap();
+        assert_eq!(backend.name(), "recording");
+
+        backend.send_events(&[InputEvent::KeyDown(0x41), InputEvent::KeyUp(0x41)]).unwrap();
+        backend.mouse_move(5, 6).unwrap();
+        assert_eq!(backend.cursor_position().unwrap(), (5, 6));
+
+        assert!(create_input_backend("no-such-backend").is_err());
+    }
+}
@@ -0,0 +1,227 @@
+//! 按键捕获
+//!
+//! 界面“按下按键”配置流程使用：监听下一个按下的组合键（含修饰键和鼠标侧键），
+//! 单独按下取消键时放弃，超过时限后超时
+
+use crate::models::{Key, KeyCombo};
+use crate::services::global_input::{key_from_rdev, GlobalInputListener, InputEventSource, KeyTracker};
+use crossbeam_channel::RecvTimeoutError;
+use rdev::EventType;
+use std::time::{Duration, Instant};
+
+/// 默认的捕获时限
+pub const DEFAULT_CAPTURE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 捕获结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureOutcome {
+    /// 捕获到组合键（左右修饰键已转换为通用修饰键）
+    Captured(KeyCombo),
+    /// 按下取消键
+    Cancelled,
+    /// 超时
+    TimedOut,
+    /// 事件来源已停止
+    Closed,
+}
+
+impl CaptureOutcome {
+    /// 捕获到的组合键
+    pub fn combo(&self) -> Option<&KeyCombo> {
+        match self {
+            CaptureOutcome::Captured(combo) => Some(combo),
+            _ => None,
+        }
+    }
+}
+
+/// 按键捕获
+///
+/// 普通按键或鼠标侧键按下时捕获当时按住的修饰键和该按键；
+/// 只按修饰键时在释放时捕获，如按住 Ctrl 再按 Shift 后释放得到 `Ctrl+Shift`
+#[derive(Debug, Clone)]
+pub struct KeyCapture {
+    /// 捕获时限
+    timeout: Duration,
+    /// 取消键（不带修饰键按下时取消）
+    cancel_key: Key,
+}
+
+impl Default for KeyCapture {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_CAPTURE_TIMEOUT,
+            cancel_key: Key::Escape,
+        }
+    }
+}
+
+impl KeyCapture {
+    /// 创建按键捕获，Esc 取消
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置捕获时限
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置取消键
+    pub fn with_cancel_key(mut self, cancel_key: Key) -> Self {
+        self.cancel_key = cancel_key;
+        self
+    }
+
+    /// 捕获时限
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// 从全局输入捕获下一个组合键，阻塞直到得到结果
+    pub fn capture(&self) -> CaptureOutcome {
+        self.capture_from(GlobalInputListener::shared().as_ref())
+    }
+
+    /// 从指定事件来源捕获下一个组合键，阻塞直到得到结果
+    pub fn capture_from(&self, source: &dyn InputEventSource) -> CaptureOutcome {
+        let events = source.subscribe();
+        let deadline = Instant::now() + self.timeout;
+        let mut tracker = KeyTracker::new();
+
+        loop {
+            match events.recv_deadline(deadline) {
+                Ok(event) => {
+                    if let Some(outcome) = self.handle(&mut tracker, &event.event_type) {
+                        log::debug!("按键捕获结果: {:?}", outcome);
+                        return outcome;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => return CaptureOutcome::TimedOut,
+                Err(RecvTimeoutError::Disconnected) => return CaptureOutcome::Closed,
+            }
+        }
+    }
+
+    /// 处理单个事件，得到结果时返回
+    fn handle(&self, tracker: &mut KeyTracker, event: &EventType) -> Option<CaptureOutcome> {
+        if let EventType::KeyRelease(key) = event {
+            // 普通按键按下时已经返回，这里按住的都是修饰键；捕获开始前按住的按键不计入
+            let key = key_from_rdev(*key)?;
+            let held = tracker.pressed().to_vec();
+            tracker.release(key);
+            if !key.is_modifier() || !held.contains(&key) {
+                return None;
+            }
+            return Some(CaptureOutcome::Captured(KeyCombo::new(held, key).generic()));
+        }
+
+        let combo = tracker.handle(event)?;
+        if combo.modifiers().is_empty() && combo.key() == self.cancel_key {
+            return Some(CaptureOutcome::Cancelled);
+        }
+        Some(CaptureOutcome::Captured(combo.generic()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::{unbounded, Receiver, Sender};
+    use rdev::{Button, Event, Key as RKey};
+    use std::sync::Mutex;
+    use std::time::SystemTime;
+
+    /// 按顺序发送预先准备的事件，`open` 时发送完后保持连接
+    struct ScriptedSource {
+        events: Vec<EventType>,
+        open: bool,
+        sender: Mutex<Option<Sender<Event>>>,
+    }
+
+    impl ScriptedSource {
+        fn new(events: Vec<EventType>) -> Self {
+            Self { events, open: false, sender: Mutex::new(None) }
+        }
+
+        fn open(events: Vec<EventType>) -> Self {
+            Self { open: true, ..Self::new(events) }
+        }
+    }
+
+    impl InputEventSource for ScriptedSource {
+        fn subscribe(&self) -> Receiver<Event> {
+            let (sender, receiver) = unbounded();
+            for event_type in &self.events {
+                sender.send(Event { time: SystemTime::now(), name: None, event_type: *event_type }).unwrap();
+            }
+            if self.open {
+                *self.sender.lock().unwrap() = Some(sender);
+            }
+            receiver
+        }
+    }
+
+    fn capture(events: Vec<EventType>) -> CaptureOutcome {
+        KeyCapture::new().capture_from(&ScriptedSource::new(events))
+    }
+
+    fn captured(events: Vec<EventType>) -> String {
+        capture(events).combo().map(|combo| combo.to_string()).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_capture_combos() {
+        use EventType::{ButtonPress, ButtonRelease, KeyPress, KeyRelease};
+
+        // 修饰键规范化，鼠标点击被忽略
+        assert_eq!(captured(vec![
+            ButtonPress(Button::Left),
+            ButtonRelease(Button::Left),
+            KeyPress(RKey::ShiftRight),
+            KeyPress(RKey::ControlLeft),
+            KeyPress(RKey::KeyT),
+        ]), "Ctrl+Shift+T");
+
+        // 捕获开始前按住的按键不计入
+        assert_eq!(captured(vec![KeyRelease(RKey::ControlLeft), KeyPress(RKey::Num1)]), "1");
+
+        // 鼠标侧键
+        assert_eq!(captured(vec![KeyPress(RKey::Alt), ButtonPress(Button::Unknown(8))]), "Alt+Mouse4");
+
+        // 只按修饰键
+        assert_eq!(captured(vec![KeyPress(RKey::ShiftLeft), KeyRelease(RKey::ShiftLeft)]), "Shift");
+        assert_eq!(captured(vec![
+            KeyPress(RKey::ControlRight),
+            KeyPress(RKey::ShiftLeft),
+            KeyRelease(RKey::ShiftLeft),
+        ]), "Ctrl+Shift");
+
+        // 带修饰键的 Esc 不是取消
+        assert_eq!(captured(vec![KeyPress(RKey::ControlLeft), KeyPress(RKey::Escape)]), "Ctrl+Escape");
+    }
+
+    #[test]
+    fn test_cancel_timeout_and_close() {
+        use EventType::{KeyPress, KeyRelease};
+
+        assert_eq!(capture(vec![KeyPress(RKey::Escape), KeyPress(RKey::KeyA)]), CaptureOutcome::Cancelled);
+        assert_eq!(
+            KeyCapture::new()
+                .with_cancel_key(Key::Backspace)
+                .capture_from(&ScriptedSource::new(vec![KeyPress(RKey::Escape)])),
+            CaptureOutcome::Captured(KeyCombo::single(Key::Escape))
+        );
+
+        // 来源停止
+        assert_eq!(capture(vec![KeyPress(RKey::ShiftLeft)]), CaptureOutcome::Closed);
+
+        // 没有完整的组合键时超时
+        let source = ScriptedSource::open(vec![KeyRelease(RKey::KeyA)]);
+        let started = Instant::now();
+        let outcome = KeyCapture::new().with_timeout(Duration::from_millis(30)).capture_from(&source);
+        assert_eq!(outcome, CaptureOutcome::TimedOut);
+        assert!(started.elapsed() >= Duration::from_millis(30));
+    }
+}
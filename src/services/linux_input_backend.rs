@@ -0,0 +1,818 @@
+//! Linux 输入后端
+//!
+//! 通过 `/dev/uinput` 虚拟设备或 X11 XTest 扩展注入键盘和鼠标事件，
+//! 用于在 Linux 上通过模拟器运行游戏的场景
+
+use crate::models::MouseButton;
+use crate::services::input_backend::InputBackend;
+use crate::utils::{ActionError, ActionResult};
+use std::ffi::{c_void, CStr};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::raw::{c_char, c_int, c_uchar, c_uint, c_ulong};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Linux 输入注入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinuxInputMethod {
+    /// 自动选择：存在 X11 显示时使用 XTest，否则使用 uinput
+    Auto,
+    /// 内核 uinput 虚拟设备（需要 /dev/uinput 写权限）
+    Uinput,
+    /// X11 XTest 扩展（需要 DISPLAY 环境变量）
+    XTest,
+}
+
+impl std::fmt::Display for LinuxInputMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinuxInputMethod::Auto => write!(f, "auto"),
+            LinuxInputMethod::Uinput => write!(f, "uinput"),
+            LinuxInputMethod::XTest => write!(f, "xtest"),
+        }
+    }
+}
+
+impl FromStr for LinuxInputMethod {
+    type Err = ActionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(LinuxInputMethod::Auto),
+            "uinput" => Ok(LinuxInputMethod::Uinput),
+            "xtest" | "x11" => Ok(LinuxInputMethod::XTest),
+            other => Err(ActionError::InvalidParameter(format!("未知的输入方式: {}", other))),
+        }
+    }
+}
+
+/// Linux 输入后端
+///
+/// 设备在第一次注入事件时才会打开，创建后端本身没有副作用
+pub struct LinuxInputBackend {
+    /// 请求的注入方式
+    method: LinuxInputMethod,
+    /// uinput 绝对坐标使用的屏幕尺寸
+    screen_size: (u32, u32),
+    /// 已连接的设备
+    device: Mutex<Option<LinuxDevice>>,
+}
+
+/// 已连接的输入设备
+enum LinuxDevice {
+    Uinput(UinputDevice),
+    XTest(XTestConnection),
+}
+
+impl LinuxInputBackend {
+    /// 使用指定的注入方式创建后端
+    pub fn new(method: LinuxInputMethod) -> Self {
+        Self {
+            method,
+            screen_size: (1920, 1080),
+            device: Mutex::new(None),
+        }
+    }
+
+    /// 设置屏幕尺寸（uinput 需要它来映射绝对坐标）
+    pub fn with_screen_size(mut self, width: u32, height: u32) -> Self {
+        self.screen_size = (width.max(1), height.max(1));
+        self
+    }
+
+    /// 获取请求的注入方式
+    pub fn method(&self) -> LinuxInputMethod {
+        self.method
+    }
+
+    /// 获取实际使用的注入方式（尚未连接时返回 None）
+    pub fn active_method(&self) -> Option<LinuxInputMethod> {
+        self.device.lock().ok()?.as_ref().map(|device| match device {
+            LinuxDevice::Uinput(_) => LinuxInputMethod::Uinput,
+            LinuxDevice::XTest(_) => LinuxInputMethod::XTest,
+        })
+    }
+
+    /// 打开设备
+    fn connect(&self) -> ActionResult<LinuxDevice> {
+        match self.method {
+            LinuxInputMethod::Uinput => UinputDevice::open(self.screen_size).map(LinuxDevice::Uinput),
+            LinuxInputMethod::XTest => XTestConnection::open().map(LinuxDevice::XTest),
+            LinuxInputMethod::Auto => {
+                if std::env::var_os("DISPLAY").is_some() {
+                    match XTestConnection::open() {
+                        Ok(connection) => return Ok(LinuxDevice::XTest(connection)),
+                        Err(e) => log::warn!("XTest 不可用，改用 uinput: {}", e),
+                    }
+                }
+                UinputDevice::open(self.screen_size).map(LinuxDevice::Uinput)
+            }
+        }
+    }
+
+    /// 在已连接的设备上执行操作
+    fn with_device<T>(&self, f: impl FnOnce(&mut LinuxDevice) -> ActionResult<T>) -> ActionResult<T> {
+        let mut guard = self.device.lock()
+            .map_err(|_| ActionError::SystemCall("输入设备锁定失败".to_string()))?;
+
+        if guard.is_none() {
+            let device = self.connect()?;
+            log::info!("Linux 输入后端已连接: {}", match device {
+                LinuxDevice::Uinput(_) => "uinput",
+                LinuxDevice::XTest(_) => "xtest",
+            });
+            *guard = Some(device);
+        }
+
+        match guard.as_mut() {
+            Some(device) => f(device),
+            None => Err(ActionError::SystemCall("输入设备未连接".to_string())),
+        }
+    }
+}
+
+impl Default for LinuxInputBackend {
+    fn default() -> Self {
+        Self::new(LinuxInputMethod::Auto)
+    }
+}
+
+impl InputBackend for LinuxInputBackend {
+    fn name(&self) -> &str {
+        "linux"
+    }
+
+    fn key_down(&self, vk_code: u16) -> ActionResult<()> {
+        self.with_device(|device| match device {
+            LinuxDevice::Uinput(uinput) => uinput.key(vk_code, true),
+            LinuxDevice::XTest(xtest) => xtest.key(vk_code, true),
+        })
+    }
+
+    fn key_up(&self, vk_code: u16) -> ActionResult<()> {
+        self.with_device(|device| match device {
+            LinuxDevice::Uinput(uinput) => uinput.key(vk_code, false),
+            LinuxDevice::XTest(xtest) => xtest.key(vk_code, false),
+        })
+    }
+
+    fn mouse_move(&self, x: i32, y: i32) -> ActionResult<()> {
+        self.with_device(|device| match device {
+            LinuxDevice::Uinput(uinput) => uinput.move_to(x, y),
+            LinuxDevice::XTest(xtest) => xtest.move_to(x, y),
+        })
+    }
+
+    fn mouse_move_relative(&self, dx: i32, dy: i32) -> ActionResult<()> {
+        self.with_device(|device| match device {
+            LinuxDevice::Uinput(uinput) => uinput.move_relative(dx, dy),
+            LinuxDevice::XTest(xtest) => xtest.move_relative(dx, dy),
+        })
+    }
+
+    fn mouse_button(&self, button: MouseButton, pressed: bool) -> ActionResult<()> {
+        self.with_device(|device| match device {
+            LinuxDevice::Uinput(uinput) => uinput.button(button, pressed),
+            LinuxDevice::XTest(xtest) => xtest.button(button, pressed),
+        })
+    }
+
+    fn mouse_wheel(&self, dx: i32, dy: i32) -> ActionResult<()> {
+        self.with_device(|device| match device {
+            LinuxDevice::Uinput(uinput) => uinput.wheel(dx, dy),
+            LinuxDevice::XTest(xtest) => xtest.wheel(dx, dy),
+        })
+    }
+
+    fn cursor_position(&self) -> ActionResult<(i32, i32)> {
+        self.with_device(|device| match device {
+            LinuxDevice::Uinput(uinput) => uinput.cursor_position(),
+            LinuxDevice::XTest(xtest) => xtest.cursor_position(),
+        })
+    }
+}
+
+/// Windows 滚轮单位（一格）
+const WHEEL_DELTA: i32 = 120;
+
+/// 将滚轮量转换为格数，非零值至少为一格
+fn wheel_notches(delta: i32) -> i32 {
+    if delta == 0 {
+        0
+    } else if delta.abs() < WHEEL_DELTA {
+        delta.signum()
+    } else {
+        delta / WHEEL_DELTA
+    }
+}
+
+// ========== uinput ==========
+
+const UINPUT_PATH: &str = "/dev/uinput";
+
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const EV_ABS: u16 = 0x03;
+const SYN_REPORT: u16 = 0x00;
+const REL_X: u16 = 0x00;
+const REL_Y: u16 = 0x01;
+const REL_HWHEEL: u16 = 0x06;
+const REL_WHEEL: u16 = 0x08;
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const BTN_LEFT: u16 = 0x110;
+const BTN_RIGHT: u16 = 0x111;
+const BTN_MIDDLE: u16 = 0x112;
+const BUS_VIRTUAL: u16 = 0x06;
+
+const UI_DEV_CREATE: c_ulong = 0x5501;
+const UI_DEV_DESTROY: c_ulong = 0x5502;
+const UI_SET_EVBIT: c_ulong = 0x4004_5564;
+const UI_SET_KEYBIT: c_ulong = 0x4004_5565;
+const UI_SET_RELBIT: c_ulong = 0x4004_5566;
+const UI_SET_ABSBIT: c_ulong = 0x4004_5567;
+
+const ABS_CNT: usize = 0x40;
+
+/// 内核 input_event 结构
+#[repr(C)]
+struct RawInputEvent {
+    time: libc::timeval,
+    type_: u16,
+    code: u16,
+    value: i32,
+}
+
+/// 内核 input_id 结构
+#[repr(C)]
+struct RawInputId {
+    bustype: u16,
+    vendor: u16,
+    product: u16,
+    version: u16,
+}
+
+/// 内核 uinput_user_dev 结构
+#[repr(C)]
+struct RawUinputUserDev {
+    name: [u8; 80],
+    id: RawInputId,
+    ff_effects_max: u32,
+    absmax: [i32; ABS_CNT],
+    absmin: [i32; ABS_CNT],
+    absfuzz: [i32; ABS_CNT],
+    absflat: [i32; ABS_CNT],
+}
+
+/// 将 repr(C) 结构视为字节切片
+fn struct_bytes<T>(value: &T) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(value as *const T as *const u8, std::mem::size_of::<T>()) }
+}
+
+/// uinput 虚拟设备
+struct UinputDevice {
+    file: File,
+    /// uinput 无法查询光标，记录最后一次设置的位置
+    cursor: Option<(i32, i32)>,
+    screen_size: (u32, u32),
+}
+
+impl UinputDevice {
+    /// 创建虚拟键鼠设备
+    fn open(screen_size: (u32, u32)) -> ActionResult<Self> {
+        let file = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(UINPUT_PATH)
+            .map_err(|e| ActionError::SystemCall(format!("无法打开 {}: {}", UINPUT_PATH, e)))?;
+        let fd = file.as_raw_fd();
+
+        Self::ioctl(fd, UI_SET_EVBIT, EV_KEY as c_int)?;
+        Self::ioctl(fd, UI_SET_EVBIT, EV_REL as c_int)?;
+        Self::ioctl(fd, UI_SET_EVBIT, EV_ABS as c_int)?;
+        Self::ioctl(fd, UI_SET_EVBIT, EV_SYN as c_int)?;
+
+        for vk_code in 0..=0xFFu16 {
+            if let Some(code) = vk_to_evdev(vk_code) {
+                Self::ioctl(fd, UI_SET_KEYBIT, code as c_int)?;
+            }
+        }
+        for code in [BTN_LEFT, BTN_RIGHT, BTN_MIDDLE] {
+            Self::ioctl(fd, UI_SET_KEYBIT, code as c_int)?;
+        }
+        Self::ioctl(fd, UI_SET_RELBIT, REL_X as c_int)?;
+        Self::ioctl(fd, UI_SET_RELBIT, REL_Y as c_int)?;
+        Self::ioctl(fd, UI_SET_RELBIT, REL_WHEEL as c_int)?;
+        Self::ioctl(fd, UI_SET_RELBIT, REL_HWHEEL as c_int)?;
+        Self::ioctl(fd, UI_SET_ABSBIT, ABS_X as c_int)?;
+        Self::ioctl(fd, UI_SET_ABSBIT, ABS_Y as c_int)?;
+
+        let mut setup = RawUinputUserDev {
+            name: [0; 80],
+            id: RawInputId { bustype: BUS_VIRTUAL, vendor: 0x1209, product: 0x0AF0, version: 1 },
+            ff_effects_max: 0,
+            absmax: [0; ABS_CNT],
+            absmin: [0; ABS_CNT],
+            absfuzz: [0; ABS_CNT],
+            absflat: [0; ABS_CNT],
+        };
+        let name = b"AutoZeroFrameAction virtual input";
+        setup.name[..name.len()].copy_from_slice(name);
+        setup.absmax[ABS_X as usize] = screen_size.0 as i32 - 1;
+        setup.absmax[ABS_Y as usize] = screen_size.1 as i32 - 1;
+
+        let mut device = Self { file, cursor: None, screen_size };
+        device.file.write_all(struct_bytes(&setup))
+            .map_err(|e| ActionError::SystemCall(format!("uinput 设备配置失败: {}", e)))?;
+        Self::ioctl(fd, UI_DEV_CREATE, 0)?;
+
+        // 等待桌面环境识别新设备，否则最初的事件会丢失
+        std::thread::sleep(Duration::from_millis(200));
+
+        Ok(device)
+    }
+
+    /// 调用 uinput ioctl
+    fn ioctl(fd: c_int, request: c_ulong, value: c_int) -> ActionResult<()> {
+        let result = unsafe { libc::ioctl(fd, request as _, value) };
+        if result < 0 {
+            return Err(ActionError::SystemCall(format!(
+                "uinput ioctl 0x{:X} failed: {}", request, std::io::Error::last_os_error()
+            )));
+        }
+        Ok(())
+    }
+
+    /// 写入事件并同步
+    fn emit(&mut self, events: &[(u16, u16, i32)]) -> ActionResult<()> {
+        for &(type_, code, value) in events.iter().chain(std::iter::once(&(EV_SYN, SYN_REPORT, 0))) {
+            let event = RawInputEvent {
+                time: libc::timeval { tv_sec: 0, tv_usec: 0 },
+                type_,
+                code,
+                value,
+            };
+            self.file.write_all(struct_bytes(&event))
+                .map_err(|e| ActionError::SystemCall(format!("uinput 写入失败: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    fn key(&mut self, vk_code: u16, pressed: bool) -> ActionResult<()> {
+        let code = vk_to_evdev(vk_code)
+            .ok_or_else(|| ActionError::InvalidKey(format!("0x{:02X}", vk_code)))?;
+        self.emit(&[(EV_KEY, code, pressed as i32)])
+    }
+
+    fn move_to(&mut self, x: i32, y: i32) -> ActionResult<()> {
+        let x = x.clamp(0, self.screen_size.0 as i32 - 1);
+        let y = y.clamp(0, self.screen_size.1 as i32 - 1);
+        self.emit(&[(EV_ABS, ABS_X, x), (EV_ABS, ABS_Y, y)])?;
+        self.cursor = Some((x, y));
+        Ok(())
+    }
+
+    /// 已知光标位置时换算为绝对移动，否则发送相对位移事件
+    fn move_relative(&mut self, dx: i32, dy: i32) -> ActionResult<()> {
+        match self.cursor {
+            Some((x, y)) => self.move_to(x + dx, y + dy),
+            None => self.emit(&[(EV_REL, REL_X, dx), (EV_REL, REL_Y, dy)]),
+        }
+    }
+
+    fn button(&mut self, button: MouseButton, pressed: bool) -> ActionResult<()> {
+        let code = match button {
+            MouseButton::Left => BTN_LEFT,
+            MouseButton::Right => BTN_RIGHT,
+            MouseButton::Middle => BTN_MIDDLE,
+        };
+        self.emit(&[(EV_KEY, code, pressed as i32)])
+    }
+
+    fn wheel(&mut self, dx: i32, dy: i32) -> ActionResult<()> {
+        let mut events = Vec::new();
+        if dy != 0 {
+            events.push((EV_REL, REL_WHEEL, wheel_notches(dy)));
+        }
+        if dx != 0 {
+            events.push((EV_REL, REL_HWHEEL, wheel_notches(dx)));
+        }
+        self.emit(&events)
+    }
+
+    fn cursor_position(&self) -> ActionResult<(i32, i32)> {
+        self.cursor.ok_or_else(|| ActionError::UnsupportedOperation(
+            "uinput 无法查询光标位置，需先移动一次鼠标".to_string()
+        ))
+    }
+}
+
+impl Drop for UinputDevice {
+    fn drop(&mut self) {
+        let _ = Self::ioctl(self.file.as_raw_fd(), UI_DEV_DESTROY, 0);
+    }
+}
+
+// ========== XTest ==========
+
+type XDisplay = c_void;
+
+/// 运行时加载的 Xlib / XTest 函数
+struct XTestLibrary {
+    close_display: unsafe extern "C" fn(*mut XDisplay) -> c_int,
+    flush: unsafe extern "C" fn(*mut XDisplay) -> c_int,
+    default_root_window: unsafe extern "C" fn(*mut XDisplay) -> c_ulong,
+    query_pointer: unsafe extern "C" fn(
+        *mut XDisplay, c_ulong, *mut c_ulong, *mut c_ulong,
+        *mut c_int, *mut c_int, *mut c_int, *mut c_int, *mut c_uint,
+    ) -> c_int,
+    keysym_to_keycode: unsafe extern "C" fn(*mut XDisplay, c_ulong) -> c_uchar,
+    fake_key_event: unsafe extern "C" fn(*mut XDisplay, c_uint, c_int, c_ulong) -> c_int,
+    fake_button_event: unsafe extern "C" fn(*mut XDisplay, c_uint, c_int, c_ulong) -> c_int,
+    fake_motion_event: unsafe extern "C" fn(*mut XDisplay, c_int, c_int, c_int, c_ulong) -> c_int,
+    fake_relative_motion_event: unsafe extern "C" fn(*mut XDisplay, c_int, c_int, c_ulong) -> c_int,
+}
+
+/// 加载动态库
+fn dl_open(name: &CStr) -> ActionResult<*mut c_void> {
+    let handle = unsafe { libc::dlopen(name.as_ptr(), libc::RTLD_NOW | libc::RTLD_GLOBAL) };
+    if handle.is_null() {
+        return Err(ActionError::UnsupportedPlatform(format!(
+            "无法加载 {}", name.to_string_lossy()
+        )));
+    }
+    Ok(handle)
+}
+
+/// 查找动态库符号
+fn dl_sym(handle: *mut c_void, name: &CStr) -> ActionResult<*mut c_void> {
+    let symbol = unsafe { libc::dlsym(handle, name.as_ptr()) };
+    if symbol.is_null() {
+        return Err(ActionError::UnsupportedPlatform(format!(
+            "缺少符号 {}", name.to_string_lossy()
+        )));
+    }
+    Ok(symbol)
+}
+
+/// 查找函数符号并转换为函数指针
+///
+/// 调用方需保证 `F` 与符号的真实签名一致
+unsafe fn dl_fn<F: Copy>(handle: *mut c_void, name: &CStr) -> ActionResult<F> {
+    debug_assert_eq!(std::mem::size_of::<F>(), std::mem::size_of::<*mut c_void>());
+    let symbol = dl_sym(handle, name)?;
+    Ok(std::mem::transmute_copy::<*mut c_void, F>(&symbol))
+}
+
+/// X11 XTest 连接
+struct XTestConnection {
+    library: XTestLibrary,
+    display: *mut XDisplay,
+}
+
+// Display 只在持有后端互斥锁时访问
+unsafe impl Send for XTestConnection {}
+
+impl XTestConnection {
+    /// 连接到 DISPLAY 指定的 X 服务器
+    fn open() -> ActionResult<Self> {
+        let x11 = dl_open(c"libX11.so.6")?;
+        let xtst = dl_open(c"libXtst.so.6")?;
+
+        let library = unsafe {
+            XTestLibrary {
+                close_display: dl_fn(x11, c"XCloseDisplay")?,
+                flush: dl_fn(x11, c"XFlush")?,
+                default_root_window: dl_fn(x11, c"XDefaultRootWindow")?,
+                query_pointer: dl_fn(x11, c"XQueryPointer")?,
+                keysym_to_keycode: dl_fn(x11, c"XKeysymToKeycode")?,
+                fake_key_event: dl_fn(xtst, c"XTestFakeKeyEvent")?,
+                fake_button_event: dl_fn(xtst, c"XTestFakeButtonEvent")?,
+                fake_motion_event: dl_fn(xtst, c"XTestFakeMotionEvent")?,
+                fake_relative_motion_event: dl_fn(xtst, c"XTestFakeRelativeMotionEvent")?,
+            }
+        };
+
+        let open_display: unsafe extern "C" fn(*const c_char) -> *mut XDisplay =
+            unsafe { dl_fn(x11, c"XOpenDisplay")? };
+        let display = unsafe { open_display(std::ptr::null()) };
+        if display.is_null() {
+            return Err(ActionError::UnsupportedPlatform("无法连接到 X 服务器".to_string()));
+        }
+
+        Ok(Self { library, display })
+    }
+
+    /// 刷新请求队列并检查返回值
+    fn finish(&self, status: c_int, description: &str) -> ActionResult<()> {
+        unsafe { (self.library.flush)(self.display) };
+        if status == 0 {
+            return Err(ActionError::SystemCall(format!("XTest failed for {}", description)));
+        }
+        Ok(())
+    }
+
+    fn key(&mut self, vk_code: u16, pressed: bool) -> ActionResult<()> {
+        let keysym = vk_to_keysym(vk_code)
+            .ok_or_else(|| ActionError::InvalidKey(format!("0x{:02X}", vk_code)))?;
+        let keycode = unsafe { (self.library.keysym_to_keycode)(self.display, keysym) };
+        if keycode == 0 {
+            return Err(ActionError::InvalidKey(format!("keysym 0x{:X} 没有对应的键码", keysym)));
+        }
+        let status = unsafe {
+            (self.library.fake_key_event)(self.display, keycode as c_uint, pressed as c_int, 0)
+        };
+        self.finish(status, "key event")
+    }
+
+    fn move_to(&mut self, x: i32, y: i32) -> ActionResult<()> {
+        let status = unsafe { (self.library.fake_motion_event)(self.display, -1, x, y, 0) };
+        self.finish(status, "motion event")
+    }
+
+    fn move_relative(&mut self, dx: i32, dy: i32) -> ActionResult<()> {
+        let status = unsafe { (self.library.fake_relative_motion_event)(self.display, dx, dy, 0) };
+        self.finish(status, "relative motion event")
+    }
+
+    fn button(&mut self, button: MouseButton, pressed: bool) -> ActionResult<()> {
+        let x_button = match button {
+            MouseButton::Left => 1,
+            MouseButton::Middle => 2,
+            MouseButton::Right => 3,
+        };
+        let status = unsafe {
+            (self.library.fake_button_event)(self.display, x_button, pressed as c_int, 0)
+        };
+        self.finish(status, "button event")
+    }
+
+    fn wheel(&mut self, dx: i32, dy: i32) -> ActionResult<()> {
+        // X11 中滚轮是按钮 4/5（垂直）和 6/7（水平）
+        let vertical = if dy > 0 { 4 } else { 5 };
+        let horizontal = if dx > 0 { 7 } else { 6 };
+        let clicks = [(vertical, wheel_notches(dy).abs()), (horizontal, wheel_notches(dx).abs())];
+
+        for (x_button, count) in clicks {
+            for _ in 0..count {
+                let status = unsafe {
+                    (self.library.fake_button_event)(self.display, x_button, 1, 0)
+                        & (self.library.fake_button_event)(self.display, x_button, 0, 0)
+                };
+                self.finish(status, "wheel event")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn cursor_position(&self) -> ActionResult<(i32, i32)> {
+        let mut root: c_ulong = 0;
+        let mut child: c_ulong = 0;
+        let (mut root_x, mut root_y, mut win_x, mut win_y) = (0, 0, 0, 0);
+        let mut mask: c_uint = 0;
+
+        let result = unsafe {
+            let root_window = (self.library.default_root_window)(self.display);
+            (self.library.query_pointer)(
+                self.display, root_window, &mut root, &mut child,
+                &mut root_x, &mut root_y, &mut win_x, &mut win_y, &mut mask,
+            )
+        };
+        if result == 0 {
+            return Err(ActionError::SystemCall("XQueryPointer failed".to_string()));
+        }
+        Ok((root_x, root_y))
+    }
+}
+
+impl Drop for XTestConnection {
+    fn drop(&mut self) {
+        unsafe { (self.library.close_display)(self.display) };
+    }
+}
+
+// ========== 键码转换 ==========
+
+/// 将 Windows 虚拟键码转换为 Linux evdev 键码
+pub fn vk_to_evdev(vk_code: u16) -> Option<u16> {
+    const LETTERS: [u16; 26] = [
+        30, 48, 46, 32, 18, 33, 34, 35, 23, 36, 37, 38, 50, // A-M
+        49, 24, 25, 16, 19, 31, 20, 22, 47, 17, 45, 21, 44, // N-Z
+    ];
+
+    let code = match vk_code {
+        0x08 => 14,                                 // Backspace
+        0x09 => 15,                                 // Tab
+        0x0D => 28,                                 // Enter
+        0x10 | 0xA0 => 42,                          // Shift / LeftShift
+        0x11 | 0xA2 => 29,                          // Ctrl / LeftCtrl
+        0x12 | 0xA4 => 56,                          // Alt / LeftAlt
+        0x13 => 119,                                // Pause
+        0x14 => 58,                                 // CapsLock
+        0x1B => 1,                                  // Escape
+        0x20 => 57,                                 // Space
+        0x21 => 104,                                // PageUp
+        0x22 => 109,                                // PageDown
+        0x23 => 107,                                // End
+        0x24 => 102,                                // Home
+        0x25 => 105,                                // Left
+        0x26 => 103,                                // Up
+        0x27 => 106,                                // Right
+        0x28 => 108,                                // Down
+        0x2C => 99,                                 // PrintScreen
+        0x2D => 110,                                // Insert
+        0x2E => 111,                                // Delete
+        0x30 => 11,                                 // 0
+        0x31..=0x39 => vk_code - 0x31 + 2,          // 1-9
+        0x41..=0x5A => LETTERS[(vk_code - 0x41) as usize],
+        0x5B => 125,                                // LeftWin
+        0x5C => 126,                                // RightWin
+        0x5D => 127,                                // Menu
+        0x60 => 82,                                 // Numpad0
+        0x61 => 79,                                 // Numpad1
+        0x62 => 80,                                 // Numpad2
+        0x63 => 81,                                 // Numpad3
+        0x64 => 75,                                 // Numpad4
+        0x65 => 76,                                 // Numpad5
+        0x66 => 77,                                 // Numpad6
+        0x67 => 71,                                 // Numpad7
+        0x68 => 72,                                 // Numpad8
+        0x69 => 73,                                 // Numpad9
+        0x6A => 55,                                 // NumpadMultiply
+        0x6B => 78,                                 // NumpadAdd
+        0x6C => 96,                                 // NumpadEnter
+        0x6D => 74,                                 // NumpadSubtract
+        0x6E => 83,                                 // NumpadDecimal
+        0x6F => 98,                                 // NumpadDivide
+        0x70..=0x79 => vk_code - 0x70 + 59,         // F1-F10
+        0x7A => 87,                                 // F11
+        0x7B => 88,                                 // F12
+        0x7C..=0x87 => vk_code - 0x7C + 183,        // F13-F24
+        0x90 => 69,                                 // NumLock
+        0x91 => 70,                                 // ScrollLock
+        0xA1 => 54,                                 // RightShift
+        0xA3 => 97,                                 // RightCtrl
+        0xA5 => 100,                                // RightAlt
+        0xBA => 39,                                 // ;
+        0xBB => 13,                                 // =
+        0xBC => 51,                                 // ,
+        0xBD => 12,                                 // -
+        0xBE => 52,                                 // .
+        0xBF => 53,                                 // /
+        0xC0 => 41,                                 // `
+        0xDB => 26,                                 // [
+        0xDC => 43,                                 // \
+        0xDD => 27,                                 // ]
+        0xDE => 40,                                 // '
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+/// 将 Windows 虚拟键码转换为 X11 keysym
+pub fn vk_to_keysym(vk_code: u16) -> Option<c_ulong> {
+    let keysym = match vk_code {
+        0x08 => 0xFF08,                             // BackSpace
+        0x09 => 0xFF09,                             // Tab
+        0x0D => 0xFF0D,                             // Return
+        0x10 | 0xA0 => 0xFFE1,                      // Shift_L
+        0x11 | 0xA2 => 0xFFE3,                      // Control_L
+        0x12 | 0xA4 => 0xFFE9,                      // Alt_L
+        0x13 => 0xFF13,                             // Pause
+        0x14 => 0xFFE5,                             // Caps_Lock
+        0x1B => 0xFF1B,                             // Escape
+        0x20 => 0x0020,                             // space
+        0x21 => 0xFF55,                             // Prior
+        0x22 => 0xFF56,                             // Next
+        0x23 => 0xFF57,                             // End
+        0x24 => 0xFF50,                             // Home
+        0x25 => 0xFF51,                             // Left
+        0x26 => 0xFF52,                             // Up
+        0x27 => 0xFF53,                             // Right
+        0x28 => 0xFF54,                             // Down
+        0x2C => 0xFF61,                             // Print
+        0x2D => 0xFF63,                             // Insert
+        0x2E => 0xFFFF,                             // Delete
+        0x30..=0x39 => vk_code as c_ulong,          // 0-9
+        0x41..=0x5A => (vk_code + 0x20) as c_ulong, // a-z
+        0x5B => 0xFFEB,                             // Super_L
+        0x5C => 0xFFEC,                             // Super_R
+        0x5D => 0xFF67,                             // Menu
+        0x60..=0x69 => 0xFFB0 + (vk_code - 0x60) as c_ulong, // KP_0-KP_9
+        0x6A => 0xFFAA,                             // KP_Multiply
+        0x6B => 0xFFAB,                             // KP_Add
+        0x6C => 0xFF8D,                             // KP_Enter
+        0x6D => 0xFFAD,                             // KP_Subtract
+        0x6E => 0xFFAE,                             // KP_Decimal
+        0x6F => 0xFFAF,                             // KP_Divide
+        0x70..=0x87 => 0xFFBE + (vk_code - 0x70) as c_ulong, // F1-F24
+        0x90 => 0xFF7F,                             // Num_Lock
+        0x91 => 0xFF14,                             // Scroll_Lock
+        0xA1 => 0xFFE2,                             // Shift_R
+        0xA3 => 0xFFE4,                             // Control_R
+        0xA5 => 0xFFEA,                             // Alt_R
+        0xBA => 0x003B,                             // semicolon
+        0xBB => 0x003D,                             // equal
+        0xBC => 0x002C,                             // comma
+        0xBD => 0x002D,                             // minus
+        0xBE => 0x002E,                             // period
+        0xBF => 0x002F,                             // slash
+        0xC0 => 0x0060,                             // grave
+        0xDB => 0x005B,                             // bracketleft
+        0xDC => 0x005C,                             // backslash
+        0xDD => 0x005D,                             // bracketright
+        0xDE => 0x0027,                             // apostrophe
+        _ => return None,
+    };
+
+    Some(keysym)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_method_parsing() {
+        assert_eq!("auto".parse::<LinuxInputMethod>().unwrap(), LinuxInputMethod::Auto);
+        assert_eq!("UInput".parse::<LinuxInputMethod>().unwrap(), LinuxInputMethod::Uinput);
+        assert_eq!("xtest".parse::<LinuxInputMethod>().unwrap(), LinuxInputMethod::XTest);
+        assert!("wayland".parse::<LinuxInputMethod>().is_err());
+        assert_eq!(LinuxInputMethod::XTest.to_string(), "xtest");
+    }
+
+    #[test]
+    fn test_vk_to_evdev() {
+        assert_eq!(vk_to_evdev(0x41), Some(30)); // A
+        assert_eq!(vk_to_evdev(0x51), Some(16)); // Q
+        assert_eq!(vk_to_evdev(0x5A), Some(44)); // Z
+        assert_eq!(vk_to_evdev(0x31), Some(2)); // 1
+        assert_eq!(vk_to_evdev(0x30), Some(11)); // 0
+        assert_eq!(vk_to_evdev(0x20), Some(57)); // Space
+        assert_eq!(vk_to_evdev(0x70), Some(59)); // F1
+        assert_eq!(vk_to_evdev(0x7B), Some(88)); // F12
+        assert_eq!(vk_to_evdev(0x87), Some(194)); // F24
+        assert_eq!(vk_to_evdev(0xBD), Some(12)); // -
+        assert_eq!(vk_to_evdev(0xFF), None);
+    }
+
+    #[test]
+    fn test_vk_to_keysym() {
+        assert_eq!(vk_to_keysym(0x41), Some(0x61)); // a
+        assert_eq!(vk_to_keysym(0x31), Some(0x31)); // 1
+        assert_eq!(vk_to_keysym(0x1B), Some(0xFF1B)); // Escape
+        assert_eq!(vk_to_keysym(0x87), Some(0xFFD5)); // F24
+        assert_eq!(vk_to_keysym(0x07), None);
+    }
+
+    #[test]
+    fn test_every_evdev_key_has_keysym() {
+        for vk_code in 0..=0xFFu16 {
+            if vk_to_evdev(vk_code).is_some() {
+                assert!(vk_to_keysym(vk_code).is_some(), "VK 0x{:02X} 缺少 keysym", vk_code);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wheel_notches() {
+        assert_eq!(wheel_notches(0), 0);
+        assert_eq!(wheel_notches(120), 1);
+        assert_eq!(wheel_notches(-240), -2);
+        assert_eq!(wheel_notches(30), 1);
+        assert_eq!(wheel_notches(-1), -1);
+    }
+
+    #[test]
+    fn test_backend_is_lazy() {
+        let backend = LinuxInputBackend::new(LinuxInputMethod::Uinput);
+        assert_eq!(backend.method(), LinuxInputMethod::Uinput);
+        assert_eq!(backend.active_method(), None);
+    }
+
+    /// 需要 X 服务器，可使用 `xvfb-run cargo test -- --ignored` 运行
+    #[test]
+    #[ignore]
+    fn test_xtest_backend_under_xvfb() {
+        let backend = LinuxInputBackend::new(LinuxInputMethod::XTest);
+
+        backend.mouse_move(123, 45).unwrap();
+        assert_eq!(backend.active_method(), Some(LinuxInputMethod::XTest));
+        assert_eq!(backend.cursor_position().unwrap(), (123, 45));
+
+        backend.mouse_move_relative(7, -5).unwrap();
+        assert_eq!(backend.cursor_position().unwrap(), (130, 40));
+
+        backend.key_down(0x41).unwrap();
+        backend.key_up(0x41).unwrap();
+        backend.mouse_button(MouseButton::Left, true).unwrap();
+        backend.mouse_button(MouseButton::Left, false).unwrap();
+        backend.mouse_wheel(0, -120).unwrap();
+    }
+}
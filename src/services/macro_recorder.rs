@@ -0,0 +1,710 @@
+//! 宏录制
+//!
+//! 在开始和停止热键之间捕获键盘鼠标事件，并转换为操作序列：紧接着的按下和释放合并为按键，
+//! 按住鼠标按钮期间的移动合并为拖拽，事件之间的间隔转换为等待
+
+use crate::models::{
+    ActionSequence, ActionType, CoordinateSpace, Easing, Key, KeyCombo, MouseButton, Position, WindowInfo,
+};
+use crate::services::global_input::{key_from_rdev, InputEventSource, KeyTracker};
+use crate::services::window_service::TargetWindow;
+use crate::utils::{ActionError, ActionResult};
+use rdev::EventType;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// 录制的拖拽最多保留的插值步数
+pub const MAX_RECORDED_DRAG_STEPS: u32 = 120;
+
+/// 录制选项
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecorderOptions {
+    /// 开始录制热键，为空时立即开始
+    pub start_key: Option<KeyCombo>,
+    /// 停止录制热键，为空时录制到事件来源断开或超时
+    pub stop_key: Option<KeyCombo>,
+    /// 鼠标位置使用的坐标系，窗口坐标系需要目标窗口
+    pub coordinate_space: CoordinateSpace,
+    /// 最小等待时长：等待按该值取整，取整后为零的间隔被忽略；为零时保留原始间隔
+    pub min_wait: Duration,
+    /// 按住达到该时长的按键记录为 `KeyHold`，否则为 `KeyPress`
+    pub hold_threshold: Duration,
+    /// 按下到释放之间移动超过该距离（像素）时记录为拖拽，否则为点击
+    pub drag_threshold: u32,
+}
+
+impl Default for RecorderOptions {
+    fn default() -> Self {
+        Self {
+            start_key: Some(KeyCombo::single(Key::F9)),
+            stop_key: Some(KeyCombo::single(Key::F10)),
+            coordinate_space: CoordinateSpace::Screen,
+            min_wait: Duration::from_millis(10),
+            hold_threshold: Duration::from_millis(250),
+            drag_threshold: 5,
+        }
+    }
+}
+
+/// 录制状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecorderState {
+    /// 等待开始热键
+    Waiting,
+    /// 录制中
+    Recording,
+    /// 已停止
+    Stopped,
+}
+
+/// 捕获的输入
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapturedInput {
+    /// 按键按下
+    KeyDown(Key),
+    /// 按键释放
+    KeyUp(Key),
+    /// 鼠标按钮按下
+    ButtonDown(MouseButton),
+    /// 鼠标按钮释放
+    ButtonUp(MouseButton),
+    /// 鼠标移动到屏幕坐标
+    MouseMove(i32, i32),
+    /// 滚轮（格）
+    Wheel(i32, i32),
+}
+
+/// 带时间的捕获输入
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapturedEvent {
+    /// 相对录制开始的时间
+    pub offset: Duration,
+    /// 输入
+    pub input: CapturedInput,
+}
+
+/// 宏录制器
+pub struct MacroRecorder {
+    /// 录制选项
+    options: RecorderOptions,
+    /// 窗口坐标系使用的目标窗口
+    window: Option<Arc<dyn TargetWindow>>,
+    /// 录制状态
+    state: RecorderState,
+    /// 按键状态
+    tracker: KeyTracker,
+    /// 开始录制前已按住的按键，其释放事件不录制
+    ignored: Vec<Key>,
+    /// 录制中按住的鼠标按钮
+    held_buttons: Vec<MouseButton>,
+    /// 最近的鼠标位置
+    cursor: Option<(i32, i32)>,
+    /// 录制开始时的鼠标位置
+    start_cursor: Option<(i32, i32)>,
+    /// 录制开始时间
+    origin: Option<SystemTime>,
+    /// 捕获的输入
+    events: Vec<CapturedEvent>,
+}
+
+impl MacroRecorder {
+    /// 根据录制选项创建
+    pub fn new(options: RecorderOptions) -> Self {
+        let state = if options.start_key.is_some() {
+            RecorderState::Waiting
+        } else {
+            RecorderState::Recording
+        };
+        Self {
+            options,
+            window: None,
+            state,
+            tracker: KeyTracker::new(),
+            ignored: Vec::new(),
+            held_buttons: Vec::new(),
+            cursor: None,
+            start_cursor: None,
+            origin: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// 设置目标窗口（通常是 `WindowService::shared_target_window`）
+    pub fn with_target_window(mut self, window: Arc<dyn TargetWindow>) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    /// 录制选项
+    pub fn options(&self) -> &RecorderOptions {
+        &self.options
+    }
+
+    /// 录制状态
+    pub fn state(&self) -> RecorderState {
+        self.state
+    }
+
+    /// 捕获的输入
+    pub fn events(&self) -> &[CapturedEvent] {
+        &self.events
+    }
+
+    /// 从事件来源录制，直到按下停止热键、来源断开或超过 `timeout`，返回最终状态
+    pub fn record(&mut self, source: &dyn InputEventSource, timeout: Option<Duration>) -> RecorderState {
+        let events = source.subscribe();
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        while self.state != RecorderState::Stopped {
+            let received = match deadline {
+                Some(deadline) => events.recv_deadline(deadline).ok(),
+                None => events.recv().ok(),
+            };
+            let Some(event) = received else {
+                break;
+            };
+            self.handle(&event);
+        }
+
+        if self.state == RecorderState::Recording {
+            self.stop();
+        }
+        self.state
+    }
+
+    /// 处理一个输入事件，返回处理后的状态
+    pub fn handle(&mut self, event: &rdev::Event) -> RecorderState {
+        match event.event_type {
+            EventType::KeyPress(key) => {
+                let Some(key) = key_from_rdev(key) else {
+                    return self.state;
+                };
+                // 按住不放产生的重复按下事件
+                if self.tracker.pressed().contains(&key) {
+                    return self.state;
+                }
+                let combo = self.tracker.press(key);
+                let matches = |hotkey: &Option<KeyCombo>| {
+                    matches!((hotkey, &combo), (Some(hotkey), Some(combo)) if combo.matches(hotkey))
+                };
+
+                match self.state {
+                    RecorderState::Waiting if matches(&self.options.start_key) => {
+                        log::info!("开始录制");
+                        self.state = RecorderState::Recording;
+                        self.origin = Some(event.time);
+                        self.ignored = self.tracker.pressed().to_vec();
+                        self.start_cursor = self.cursor;
+                    }
+                    RecorderState::Recording if matches(&self.options.stop_key) => {
+                        self.tracker.release(key);
+                        self.stop();
+                    }
+                    RecorderState::Recording => self.capture(event.time, CapturedInput::KeyDown(key)),
+                    _ => {}
+                }
+            }
+            EventType::KeyRelease(key) => {
+                let Some(key) = key_from_rdev(key) else {
+                    return self.state;
+                };
+                self.tracker.release(key);
+                if self.state != RecorderState::Recording {
+                    return self.state;
+                }
+                if self.ignored.contains(&key) {
+                    self.ignored.retain(|ignored| *ignored != key);
+                    return self.state;
+                }
+                self.capture(event.time, CapturedInput::KeyUp(key));
+            }
+            EventType::ButtonPress(button) => {
+                if let (RecorderState::Recording, Some(button)) = (self.state, button_from_rdev(button)) {
+                    if !self.held_buttons.contains(&button) {
+                        self.held_buttons.push(button);
+                        self.capture(event.time, CapturedInput::ButtonDown(button));
+                    }
+                }
+            }
+            EventType::ButtonRelease(button) => {
+                if let (RecorderState::Recording, Some(button)) = (self.state, button_from_rdev(button)) {
+                    // 开始录制前按下的按钮不录制
+                    if self.held_buttons.contains(&button) {
+                        self.held_buttons.retain(|held| *held != button);
+                        self.capture(event.time, CapturedInput::ButtonUp(button));
+                    }
+                }
+            }
+            EventType::MouseMove { x, y } => {
+                let position = (x.round() as i32, y.round() as i32);
+                self.cursor = Some(position);
+                if self.state == RecorderState::Recording {
+                    self.capture(event.time, CapturedInput::MouseMove(position.0, position.1));
+                }
+            }
+            EventType::Wheel { delta_x, delta_y } => {
+                if self.state == RecorderState::Recording {
+                    self.capture(event.time, CapturedInput::Wheel(delta_x as i32, delta_y as i32));
+                }
+            }
+        }
+        self.state
+    }
+
+    /// 停止录制
+    ///
+    /// 仍被按住的按键和鼠标按钮（包括停止热键的修饰键）的按下事件被丢弃
+    pub fn stop(&mut self) {
+        if self.state == RecorderState::Stopped {
+            return;
+        }
+
+        let held_keys: Vec<Key> = self.tracker.pressed().iter()
+            .copied()
+            .filter(|key| !self.ignored.contains(key))
+            .collect();
+        for key in held_keys {
+            self.remove_last(CapturedInput::KeyDown(key));
+        }
+        for button in std::mem::take(&mut self.held_buttons) {
+            self.remove_last(CapturedInput::ButtonDown(button));
+        }
+
+        log::info!("停止录制，共 {} 个事件", self.events.len());
+        self.state = RecorderState::Stopped;
+    }
+
+    /// 转换为操作序列
+    ///
+    /// 窗口坐标系下未锁定目标窗口或点击位置在窗口外时返回 `CoordinateError`
+    pub fn to_sequence(&self, name: &str) -> ActionResult<ActionSequence> {
+        let window = match self.options.coordinate_space {
+            CoordinateSpace::Screen => None,
+            _ => Some(
+                self.window.as_ref()
+                    .and_then(|window| window.target_window())
+                    .ok_or(ActionError::CoordinateError)?,
+            ),
+        };
+
+        let mut builder = SequenceBuilder {
+            options: &self.options,
+            window: window.as_ref(),
+            actions: Vec::new(),
+            time: Duration::ZERO,
+            cursor: self.start_cursor.unwrap_or_default(),
+            placed: self.start_cursor,
+        };
+        builder.build(&self.events)?;
+
+        let mut sequence = ActionSequence::new(name.to_string());
+        for action in builder.actions {
+            sequence.add_action(action);
+        }
+        Ok(sequence)
+    }
+
+    /// 记录捕获的输入
+    fn capture(&mut self, time: SystemTime, input: CapturedInput) {
+        let origin = *self.origin.get_or_insert(time);
+        self.events.push(CapturedEvent {
+            offset: time.duration_since(origin).unwrap_or_default(),
+            input,
+        });
+    }
+
+    /// 移除最后一个指定的输入
+    fn remove_last(&mut self, input: CapturedInput) {
+        if let Some(index) = self.events.iter().rposition(|event| event.input == input) {
+            self.events.remove(index);
+        }
+    }
+}
+
+/// 将捕获的输入转换为操作
+struct SequenceBuilder<'a> {
+    /// 录制选项
+    options: &'a RecorderOptions,
+    /// 目标窗口（窗口坐标系）
+    window: Option<&'a WindowInfo>,
+    /// 生成的操作
+    actions: Vec<ActionType>,
+    /// 回放时间线的当前位置（相对录制开始）
+    time: Duration,
+    /// 当前鼠标位置
+    cursor: (i32, i32),
+    /// 已生成的操作使鼠标所在的位置
+    placed: Option<(i32, i32)>,
+}
+
+impl SequenceBuilder<'_> {
+    fn build(&mut self, events: &[CapturedEvent]) -> ActionResult<()> {
+        let mut consumed = vec![false; events.len()];
+
+        for index in 0..events.len() {
+            if consumed[index] {
+                continue;
+            }
+            consumed[index] = true;
+            let event = events[index];
+
+            match event.input {
+                CapturedInput::KeyDown(key) => {
+                    self.wait_until(event.offset);
+                    // 紧接着释放同一个按键时合并（跳过已合并到拖拽中的移动）
+                    let release = (index + 1..events.len())
+                        .find(|&later| !consumed[later])
+                        .filter(|&later| events[later].input == CapturedInput::KeyUp(key));
+                    match release {
+                        Some(release) => {
+                            consumed[release] = true;
+                            let release = events[release];
+                            let held = release.offset - event.offset;
+                            if held >= self.options.hold_threshold {
+                                let held = quantize(held, self.options.min_wait);
+                                self.actions.push(ActionType::KeyHold(key.name().to_string(), held));
+                                self.time += held;
+                            } else {
+                                self.actions.push(ActionType::KeyPress(key.name().to_string()));
+                            }
+                        }
+                        None => self.actions.push(ActionType::KeyDown(key.name().to_string())),
+                    }
+                }
+                CapturedInput::KeyUp(key) => {
+                    self.wait_until(event.offset);
+                    self.actions.push(ActionType::KeyUp(key.name().to_string()));
+                }
+                CapturedInput::ButtonDown(button) => {
+                    let Some(release) = (index + 1..events.len())
+                        .find(|&later| events[later].input == CapturedInput::ButtonUp(button))
+                    else {
+                        continue;
+                    };
+                    consumed[release] = true;
+
+                    // 按住期间的移动属于拖拽，其他输入在拖拽之后生成
+                    let mut moves = 0u32;
+                    let mut to = self.cursor;
+                    for later in index + 1..release {
+                        if let CapturedInput::MouseMove(x, y) = events[later].input {
+                            consumed[later] = true;
+                            moves += 1;
+                            to = (x, y);
+                        }
+                    }
+
+                    self.wait_until(event.offset);
+                    let duration = events[release].offset - event.offset;
+                    self.push_press(button, to, moves, duration)?;
+                }
+                CapturedInput::ButtonUp(_) => {}
+                CapturedInput::MouseMove(x, y) => self.cursor = (x, y),
+                CapturedInput::Wheel(dx, dy) => {
+                    self.wait_until(event.offset);
+                    self.place_cursor()?;
+                    self.actions.push(ActionType::Scroll { dx, dy });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 生成点击或拖拽
+    fn push_press(&mut self, button: MouseButton, to: (i32, i32), moves: u32, duration: Duration) -> ActionResult<()> {
+        let from = self.cursor;
+        let distance = ((to.0 - from.0) as f64).hypot((to.1 - from.1) as f64);
+
+        if distance <= self.options.drag_threshold as f64 {
+            let action = match self.position(from)? {
+                None => ActionType::MouseClick(button, from.0, from.1),
+                Some(position) => ActionType::MouseClickAt(button, position),
+            };
+            self.actions.push(action);
+            self.placed = Some(from);
+        } else {
+            let duration = quantize(duration, self.options.min_wait);
+            let steps = moves.clamp(1, MAX_RECORDED_DRAG_STEPS);
+            let action = match (self.position(from)?, self.position(to)?) {
+                (Some(from), Some(to)) => {
+                    ActionType::MouseDragAt { button, from, to, duration, steps, easing: Easing::Linear }
+                }
+                _ => ActionType::MouseDrag { button, from, to, duration, steps, easing: Easing::Linear },
+            };
+            self.actions.push(action);
+            self.time += duration;
+            self.placed = Some(to);
+        }
+
+        self.cursor = to;
+        Ok(())
+    }
+
+    /// 鼠标不在当前位置时先移动过去
+    fn place_cursor(&mut self) -> ActionResult<()> {
+        if self.placed == Some(self.cursor) {
+            return Ok(());
+        }
+        let (x, y) = self.cursor;
+        let action = match self.position(self.cursor)? {
+            None => ActionType::MouseMove(x, y),
+            Some(position) => ActionType::MouseMoveTo(position),
+        };
+        self.actions.push(action);
+        self.placed = Some(self.cursor);
+        Ok(())
+    }
+
+    /// 将屏幕坐标转换为录制坐标系中的位置，屏幕坐标系返回空
+    fn position(&self, screen: (i32, i32)) -> ActionResult<Option<Position>> {
+        match self.options.coordinate_space {
+            CoordinateSpace::Screen => Ok(None),
+            space => Position::from_screen(screen, space, self.window).map(Some),
+        }
+    }
+
+    /// 在回放时间线上等待到指定时间
+    fn wait_until(&mut self, offset: Duration) {
+        let wait = quantize(offset.saturating_sub(self.time), self.options.min_wait);
+        if !wait.is_zero() {
+            self.actions.push(ActionType::Wait(wait));
+            self.time += wait;
+        }
+    }
+}
+
+/// 按最小等待时长四舍五入取整，步数超出范围时不取整
+fn quantize(duration: Duration, step: Duration) -> Duration {
+    if step.is_zero() {
+        return duration;
+    }
+    let steps = (duration.as_nanos() + step.as_nanos() / 2) / step.as_nanos();
+    u32::try_from(steps).ok()
+        .and_then(|steps| step.checked_mul(steps))
+        .unwrap_or(duration)
+}
+
+/// 将 rdev 鼠标按钮转换为鼠标按钮
+fn button_from_rdev(button: rdev::Button) -> Option<MouseButton> {
+    match button {
+        rdev::Button::Left => Some(MouseButton::Left),
+        rdev::Button::Right => Some(MouseButton::Right),
+        rdev::Button::Middle => Some(MouseButton::Middle),
+        rdev::Button::Unknown(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::{unbounded, Receiver};
+    use rdev::{Button, Event, Key as RKey};
+    use std::sync::Mutex;
+
+    /// 按顺序发送预先准备的事件，发送完后断开
+    struct FakeSource {
+        events: Vec<Event>,
+    }
+
+    impl InputEventSource for FakeSource {
+        fn subscribe(&self) -> Receiver<Event> {
+            let (sender, receiver) = unbounded();
+            for event in &self.events {
+                sender.send(event.clone()).unwrap();
+            }
+            receiver
+        }
+    }
+
+    /// 事件脚本，时间为相对起点的毫秒数
+    struct Script {
+        base: SystemTime,
+        events: Vec<Event>,
+    }
+
+    impl Script {
+        fn new() -> Self {
+            Self { base: SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000), events: Vec::new() }
+        }
+
+        fn at(mut self, ms: u64, event_type: EventType) -> Self {
+            self.events.push(Event {
+                time: self.base + Duration::from_millis(ms),
+                name: None,
+                event_type,
+            });
+            self
+        }
+
+        fn tap(self, ms: u64, key: RKey, held: u64) -> Self {
+            self.at(ms, EventType::KeyPress(key)).at(ms + held, EventType::KeyRelease(key))
+        }
+
+        fn source(self) -> FakeSource {
+            FakeSource { events: self.events }
+        }
+    }
+
+    fn mouse(x: f64, y: f64) -> EventType {
+        EventType::MouseMove { x, y }
+    }
+
+    #[test]
+    fn test_record_between_hotkeys() {
+        let source = Script::new()
+            // 开始前的输入不录制
+            .tap(0, RKey::KeyQ, 30)
+            .at(10, mouse(100.0, 100.0))
+            .tap(100, RKey::F9, 20)
+            .tap(150, RKey::Num1, 30)
+            // 拖拽
+            .at(300, EventType::ButtonPress(Button::Left))
+            .at(350, mouse(150.0, 100.0))
+            .at(400, mouse(200.0, 100.0))
+            .at(420, EventType::ButtonRelease(Button::Left))
+            // 点击（微小移动不算拖拽）
+            .at(600, EventType::ButtonPress(Button::Right))
+            .at(610, mouse(202.0, 101.0))
+            .at(640, EventType::ButtonRelease(Button::Right))
+            // 长按、重复按下事件
+            .at(700, EventType::KeyPress(RKey::KeyW))
+            .at(750, EventType::KeyPress(RKey::KeyW))
+            .at(1003, EventType::KeyRelease(RKey::KeyW))
+            .at(1100, EventType::Wheel { delta_x: 0, delta_y: -1 })
+            // 停止热键的修饰键不录制
+            .at(1200, EventType::KeyPress(RKey::ControlLeft))
+            .at(1250, EventType::KeyPress(RKey::F10))
+            .tap(1300, RKey::KeyE, 10)
+            .source();
+
+        let mut recorder = MacroRecorder::new(RecorderOptions {
+            stop_key: Some("Ctrl+F10".parse().unwrap()),
+            ..RecorderOptions::default()
+        });
+        assert_eq!(recorder.record(&source, None), RecorderState::Stopped);
+
+        let sequence = recorder.to_sequence("recorded").unwrap();
+        assert_eq!(sequence.name, "recorded");
+        assert_eq!(sequence.actions, vec![
+            ActionType::Wait(Duration::from_millis(50)),
+            ActionType::KeyPress("1".to_string()),
+            ActionType::Wait(Duration::from_millis(150)),
+            ActionType::MouseDrag {
+                button: MouseButton::Left,
+                from: (100, 100),
+                to: (200, 100),
+                duration: Duration::from_millis(120),
+                steps: 2,
+                easing: Easing::Linear,
+            },
+            ActionType::Wait(Duration::from_millis(180)),
+            ActionType::MouseClick(MouseButton::Right, 200, 100),
+            ActionType::Wait(Duration::from_millis(100)),
+            ActionType::KeyHold("W".to_string(), Duration::from_millis(300)),
+            ActionType::Wait(Duration::from_millis(100)),
+            ActionType::MouseMove(202, 101),
+            ActionType::Scroll { dx: 0, dy: -1 },
+        ]);
+    }
+
+    #[test]
+    fn test_interleaved_keys_and_quantization() {
+        let source = Script::new()
+            .at(0, mouse(10.0, 10.0))
+            .at(0, EventType::KeyPress(RKey::ShiftLeft))
+            .tap(12, RKey::KeyA, 5)
+            .at(20, EventType::ButtonPress(Button::Left))
+            .at(30, EventType::KeyPress(RKey::KeyS))
+            .at(60, mouse(110.0, 10.0))
+            .at(70, EventType::KeyRelease(RKey::KeyS))
+            .at(80, EventType::ButtonRelease(Button::Left))
+            .at(140, EventType::KeyRelease(RKey::ShiftLeft))
+            .source();
+
+        let mut recorder = MacroRecorder::new(RecorderOptions {
+            start_key: None,
+            stop_key: None,
+            min_wait: Duration::from_millis(50),
+            ..RecorderOptions::default()
+        });
+        assert_eq!(recorder.record(&source, Some(Duration::from_secs(1))), RecorderState::Stopped);
+
+        let sequence = recorder.to_sequence("interleaved").unwrap();
+        assert_eq!(sequence.actions, vec![
+            // 12ms 和 20ms 的间隔被忽略
+            ActionType::KeyDown("LeftShift".to_string()),
+            ActionType::KeyPress("A".to_string()),
+            ActionType::MouseDrag {
+                button: MouseButton::Left,
+                from: (10, 10),
+                to: (110, 10),
+                duration: Duration::from_millis(50),
+                steps: 1,
+                easing: Easing::Linear,
+            },
+            // 拖拽期间的按键在拖拽之后生成
+            ActionType::KeyPress("S".to_string()),
+            ActionType::Wait(Duration::from_millis(100)),
+            ActionType::KeyUp("LeftShift".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_window_relative_coordinates() {
+        let source = Script::new()
+            .at(0, mouse(300.0, 200.0))
+            .at(10, EventType::ButtonPress(Button::Left))
+            .at(20, EventType::ButtonRelease(Button::Left))
+            .at(30, mouse(600.0, 300.0))
+            .at(40, EventType::ButtonPress(Button::Left))
+            .at(50, EventType::ButtonRelease(Button::Left))
+            .source();
+
+        let target = Arc::new(Mutex::new(None));
+        let mut recorder = MacroRecorder::new(RecorderOptions {
+            start_key: None,
+            stop_key: None,
+            coordinate_space: CoordinateSpace::WindowClient,
+            min_wait: Duration::ZERO,
+            ..RecorderOptions::default()
+        })
+        .with_target_window(target.clone());
+        recorder.record(&source, None);
+
+        // 未锁定目标窗口
+        assert!(matches!(recorder.to_sequence("window"), Err(ActionError::CoordinateError)));
+
+        *target.lock().unwrap() = Some(WindowInfo::test_window((200, 100), (800, 400)));
+
+        let sequence = recorder.to_sequence("window").unwrap();
+        assert_eq!(sequence.actions, vec![
+            ActionType::Wait(Duration::from_millis(10)),
+            ActionType::MouseClickAt(MouseButton::Left, Position::window_client(100, 100)),
+            ActionType::Wait(Duration::from_millis(30)),
+            ActionType::MouseClickAt(MouseButton::Left, Position::window_client(400, 200)),
+        ]);
+    }
+
+    #[test]
+    fn test_waiting_for_start_key() {
+        let source = Script::new().tap(0, RKey::KeyA, 10).source();
+        let mut recorder = MacroRecorder::new(RecorderOptions::default());
+
+        assert_eq!(recorder.record(&source, None), RecorderState::Waiting);
+        assert!(recorder.events().is_empty());
+        assert!(recorder.to_sequence("empty").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_quantize() {
+        let step = Duration::from_millis(10);
+        assert_eq!(quantize(Duration::from_millis(4), step), Duration::ZERO);
+        assert_eq!(quantize(Duration::from_millis(5), step), Duration::from_millis(10));
+        assert_eq!(quantize(Duration::from_millis(33), step), Duration::from_millis(30));
+        assert_eq!(quantize(Duration::from_micros(33_333), Duration::ZERO), Duration::from_micros(33_333));
+        // 步数超过 u32 时不截断
+        let long = Duration::from_secs(u64::from(u32::MAX) + 7);
+        assert_eq!(quantize(long, Duration::from_secs(1)), long);
+        assert_eq!(quantize(Duration::MAX, Duration::from_millis(10)), Duration::MAX);
+    }
+}
@@ -5,6 +5,22 @@
 pub mod config_service;
 pub mod window_service;
 pub mod action_service;
+pub mod action_scheduler;
+pub mod execution_tracer;
+pub mod game_speed;
+pub mod global_input;
+pub mod hotkey_service;
+pub mod humanizer;
+pub mod input_backend;
+pub mod key_capture;
+#[cfg(target_os = "linux")]
+pub mod linux_input_backend;
+pub mod macro_recorder;
+pub mod operation_executor;
+pub mod safety_guard;
+pub mod sequence_flow;
+pub mod sequence_registry;
+pub mod trigger_matcher;
 pub mod vision_service;
 pub mod state_manager;
 pub mod mode_manager;
@@ -15,6 +31,22 @@ pub mod state_manager_test;
 pub use config_service::*;
 pub use window_service::*;
 pub use action_service::*;
+pub use action_scheduler::*;
+pub use execution_tracer::*;
+pub use game_speed::*;
+pub use global_input::*;
+pub use hotkey_service::*;
+pub use humanizer::*;
+pub use input_backend::*;
+pub use key_capture::*;
+#[cfg(target_os = "linux")]
+pub use linux_input_backend::*;
+pub use macro_recorder::*;
+pub use operation_executor::*;
+pub use safety_guard::*;
+pub use sequence_flow::*;
+pub use sequence_registry::*;
+pub use trigger_matcher::*;
 pub use vision_service::*;
 pub use state_manager::*;
 pub use mode_manager::*;
\ No newline at end of file
@@ -2,8 +2,11 @@
 //! 
 //! 负责管理宏模式和智能模式的切换，以及各模式特定的配置和行为
 
-use crate::models::{OperationMode, MacroModeConfig, IntelligentModeConfig, GameOperation, DefaultOperations};
-use crate::utils::{ModeResult};
+use crate::models::{
+    is_slot_operation, slot_key, OperationMode, MacroModeConfig, IntelligentModeConfig, GameOperation, DefaultOperations, Hotkey,
+    KeyCombo, TriggerKind,
+};
+use crate::utils::{ModeError, ModeResult};
 use std::collections::HashMap;
 use tokio::sync::broadcast;
 use log::{info, debug, warn};
@@ -258,8 +261,19 @@ impl ModeManager {
     }
     
     /// 更新当前模式的热键
+    ///
+    /// 热键会被规范化后保存，如 `Hold:shift+ctrl+t` 保存为 `hold:Ctrl+Shift+T`
     pub fn update_hotkey(&mut self, operation: String, hotkey: String) -> ModeResult<()> {
-        let old_hotkey = match self.current_mode {
+        self.update_mode_hotkey(self.current_mode, operation, hotkey).map(|_| ())
+    }
+    
+    /// 更新指定模式的热键，返回规范化后的热键
+    pub fn update_mode_hotkey(&mut self, mode: OperationMode, operation: String, hotkey: String) -> ModeResult<String> {
+        let hotkeys = Hotkey::parse_list(&hotkey)
+            .map_err(|e| ModeError::ConfigError(format!("热键无效 {}: {}", operation, e)))?;
+        let hotkey = Hotkey::format_list(&hotkeys);
+        
+        let old_hotkey = match mode {
             OperationMode::Macro => {
                 let old = self.macro_config.hotkeys.get(&operation).cloned();
                 self.macro_config.set_hotkey(operation.clone(), hotkey.clone());
@@ -274,18 +288,75 @@ impl ModeManager {
         
         // 发送热键更新事件
         let event = ModeChangeEvent::HotkeyUpdate {
-            mode: self.current_mode,
+            mode,
             operation: operation.clone(),
             old_hotkey,
-            new_hotkey: hotkey,
+            new_hotkey: hotkey.clone(),
         };
         
         if let Err(e) = self.event_sender.send(event) {
             warn!("发送热键更新事件失败: {}", e);
         }
         
-        debug!("更新热键: {} -> {}", operation, self.get_current_hotkeys().get(&operation).unwrap_or(&"未设置".to_string()));
-        Ok(())
+        debug!("更新{:?}模式热键: {} -> {}", mode, operation, hotkey);
+        Ok(hotkey)
+    }
+    
+    /// 根据按下的组合键查找当前模式下按下即触发的游戏操作
+    ///
+    /// 优先使用当前模式配置的热键，未配置时使用操作自带的热键。
+    /// 按槽位区分的操作返回对应槽位的操作（如 `deploy_operator[2]`）
+    pub fn find_operation_by_hotkey(&self, pressed: &KeyCombo) -> Option<GameOperation> {
+        let pressed = Hotkey::press(pressed.clone());
+        self.current_bindings().into_iter()
+            .find(|(hotkey, _)| hotkey.matches(&pressed))
+            .map(|(_, operation)| operation)
+    }
+    
+    /// 当前模式下已启用操作的热键绑定（按操作名称排序）
+    ///
+    /// 与 `find_operation_by_hotkey` 使用相同的规则，按顺序取第一个匹配的绑定即为查找结果。
+    /// 按槽位区分的操作（如 `deploy_operator`）的热键列表中，同一触发方式的第 n 个热键
+    /// 绑定到 `操作名[n]`，槽位编号作为序列参数传入，序列中的 `$slot_key` 替换为槽位按键配置中的第 n 个按键
+    pub fn current_bindings(&self) -> Vec<(Hotkey, GameOperation)> {
+        let mut operations: Vec<&GameOperation> = self.game_operations.values()
+            .filter(|operation| operation.enabled)
+            .collect();
+        operations.sort_by(|a, b| a.name.cmp(&b.name));
+        
+        operations.into_iter()
+            .flat_map(|operation| {
+                let slotted = is_slot_operation(&operation.name);
+                let mut slots: HashMap<TriggerKind, usize> = HashMap::new();
+                self.operation_hotkeys(operation).into_iter()
+                    .map(move |hotkey| {
+                        let slot = slots.entry(hotkey.trigger()).or_default();
+                        *slot += 1;
+                        if !slotted {
+                            return (hotkey, operation.clone());
+                        }
+                        let slotted = operation.for_slot(*slot);
+                        match self.slot_key(&operation.name, *slot) {
+                            Some(key) => (hotkey, slotted.with_slot_key(&key)),
+                            None => (hotkey, slotted),
+                        }
+                    })
+            })
+            .collect()
+    }
+    
+    /// 槽位操作第 n 个槽位在游戏内的按键（槽位按键配置中的第 n 个按键）
+    pub fn slot_key(&self, operation: &str, slot: usize) -> Option<String> {
+        self.macro_config.slot_keys.get(operation).and_then(|keys| slot_key(keys, slot))
+    }
+    
+    /// 操作在当前模式下的热键
+    fn operation_hotkeys(&self, operation: &GameOperation) -> Vec<Hotkey> {
+        let spec = self.get_current_hotkeys().get(&operation.name).unwrap_or(&operation.hotkey);
+        Hotkey::parse_list(spec).unwrap_or_else(|e| {
+            warn!("操作 {} 的热键无效: {}", operation.name, e);
+            Vec::new()
+        })
     }
     
     /// 获取当前模式的启用功能
@@ -398,6 +469,13 @@ impl ModeManager {
             self.game_operations.insert(operation.name.clone(), operation);
         }
         
+        // 配置中的自定义序列替换默认序列
+        for (name, sequence) in &self.macro_config.sequences {
+            if let Some(operation) = self.game_operations.get_mut(name) {
+                operation.sequence = sequence.clone();
+            }
+        }
+        
         debug!("初始化了 {} 个默认游戏操作", self.game_operations.len());
     }
     
@@ -457,6 +535,7 @@ impl Default for ModeManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::SLOT_PARAM;
 
     #[test]
     fn test_mode_manager_creation() {
@@ -488,6 +567,92 @@ mod tests {
         assert_eq!(hotkeys.get("test_operation"), Some(&"Ctrl+T".to_string()));
     }
 
+    #[test]
+    fn test_hotkey_normalization_and_validation() {
+        let mut manager = ModeManager::new();
+        
+        manager.update_hotkey("test_operation".to_string(), "shift+ctrl+t".to_string()).unwrap();
+        assert_eq!(manager.get_current_hotkeys().get("test_operation"), Some(&"Ctrl+Shift+T".to_string()));
+        
+        assert!(manager.update_hotkey("test_operation".to_string(), "Ctrl+Nope".to_string()).is_err());
+        assert_eq!(manager.get_current_hotkeys().get("test_operation"), Some(&"Ctrl+Shift+T".to_string()));
+    }
+
+    #[test]
+    fn test_update_other_mode_hotkey() {
+        let mut manager = ModeManager::new();
+        let mut events = manager.subscribe_events();
+        
+        let hotkey = manager.update_mode_hotkey(OperationMode::Intelligent, "focus_view".to_string(), "ctrl+f".to_string()).unwrap();
+        assert_eq!(hotkey, "Ctrl+F");
+        assert_eq!(manager.get_intelligent_config().hotkeys.get("focus_view"), Some(&"Ctrl+F".to_string()));
+        assert_ne!(manager.get_current_hotkeys().get("focus_view"), Some(&"Ctrl+F".to_string()));
+        assert!(matches!(
+            events.try_recv(),
+            Ok(ModeChangeEvent::HotkeyUpdate { mode: OperationMode::Intelligent, .. })
+        ));
+    }
+
+    #[test]
+    fn test_find_operation_by_hotkey() {
+        let mut manager = ModeManager::new();
+        
+        manager.update_hotkey("focus_view".to_string(), "Ctrl+F".to_string()).unwrap();
+        
+        let pressed: KeyCombo = "LeftCtrl+F".parse().unwrap();
+        assert_eq!(manager.find_operation_by_hotkey(&pressed).unwrap().name, "focus_view");
+        
+        let pressed: KeyCombo = "1".parse().unwrap();
+        assert_eq!(manager.find_operation_by_hotkey(&pressed).unwrap().name, "deploy_operator[1]");
+        
+        let pressed: KeyCombo = "Ctrl+1".parse().unwrap();
+        assert!(manager.find_operation_by_hotkey(&pressed).is_none());
+    }
+
+    #[test]
+    fn test_slot_bindings() {
+        let mut manager = ModeManager::new();
+        
+        manager.update_hotkey("deploy_operator".to_string(), "1,2,Ctrl+3".to_string()).unwrap();
+        let bindings = manager.current_bindings();
+        let deploys: Vec<(String, String, Option<i64>)> = bindings.iter()
+            .filter(|(_, operation)| operation.name.starts_with("deploy_operator"))
+            .map(|(combo, operation)| {
+                (combo.to_string(), operation.name.clone(), operation.sequence.params.get(SLOT_PARAM).copied())
+            })
+            .collect();
+        assert_eq!(deploys, vec![
+            ("1".to_string(), "deploy_operator[1]".to_string(), Some(1)),
+            ("2".to_string(), "deploy_operator[2]".to_string(), Some(2)),
+            ("Ctrl+3".to_string(), "deploy_operator[3]".to_string(), Some(3)),
+        ]);
+        
+        // 不按槽位区分的操作保持原名
+        let (_, focus) = bindings.iter().find(|(_, operation)| operation.name.starts_with("focus_view")).unwrap();
+        assert_eq!(focus.name, "focus_view");
+        assert!(focus.sequence.params.is_empty());
+        
+        let pressed: KeyCombo = "Ctrl+3".parse().unwrap();
+        let deploy = manager.find_operation_by_hotkey(&pressed).unwrap();
+        assert_eq!(deploy.name, "deploy_operator[3]");
+        assert_eq!(deploy.sequence.params.get(SLOT_PARAM), Some(&3));
+        
+        // 槽位按触发方式分别编号
+        manager.update_hotkey("deploy_operator".to_string(), "hold:1,tap:1,hold:2".to_string()).unwrap();
+        let deploys: Vec<(String, String)> = manager.current_bindings().into_iter()
+            .filter(|(_, operation)| operation.name.starts_with("deploy_operator"))
+            .map(|(hotkey, operation)| (hotkey.to_string(), operation.name))
+            .collect();
+        assert_eq!(deploys, vec![
+            ("hold:1".to_string(), "deploy_operator[1]".to_string()),
+            ("tap:1".to_string(), "deploy_operator[1]".to_string()),
+            ("hold:2".to_string(), "deploy_operator[2]".to_string()),
+        ]);
+        
+        // 按组合键查找时只匹配按下即触发的热键
+        assert!(manager.find_operation_by_hotkey(&"1".parse().unwrap()).is_none());
+    }
+
     #[test]
     fn test_mode_history() {
         let mut manager = ModeManager::new();
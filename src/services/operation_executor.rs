@@ -0,0 +1,554 @@
+//! 操作执行器
+//!
+//! 集中执行热键触发的 `GameOperation`，同一时刻只执行一个操作，
+//! 其余操作按优先级进入有界队列，按各操作的 `ConflictPolicy` 处理冲突
+
+use crate::models::{ConflictPolicy, GameOperation};
+use crate::services::action_service::{ActionService, SequenceHandle};
+use crate::services::input_backend::{InputBackend, PlatformInputBackend};
+use crate::utils::ActionError;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// 默认队列长度
+pub const DEFAULT_QUEUE_CAPACITY: usize = 8;
+
+/// 提交结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmitOutcome {
+    /// 立即开始执行
+    Started,
+    /// 进入队列（队列中的位置，从 0 开始）
+    Queued(usize),
+    /// 打断了正在执行的操作，在其停止后最先执行（排在队首）
+    Preempted(String),
+    /// 与正在执行或排队的同名操作合并
+    Coalesced,
+    /// 被丢弃
+    Dropped(DropReason),
+}
+
+/// 丢弃原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropReason {
+    /// 操作未启用
+    Disabled,
+    /// 执行器忙碌且策略为 `DropIfBusy`
+    Busy,
+    /// 队列已满
+    QueueFull,
+}
+
+/// 执行器统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecutorStats {
+    /// 提交次数
+    pub submitted: u64,
+    /// 执行成功次数
+    pub completed: u64,
+    /// 执行失败次数（不含取消和打断）
+    pub failed: u64,
+    /// 被取消次数（不含打断）
+    pub cancelled: u64,
+    /// 被丢弃次数
+    pub dropped: u64,
+    /// 被合并次数
+    pub coalesced: u64,
+    /// 被打断次数
+    pub preempted: u64,
+}
+
+/// 正在执行的操作
+#[derive(Debug)]
+struct RunningOperation {
+    /// 操作名称
+    name: String,
+    /// 优先级
+    priority: u8,
+    /// 序列 ID
+    id: u64,
+    /// 是否被打断
+    preempted: bool,
+}
+
+/// 执行器状态
+#[derive(Debug, Default)]
+struct ExecutorState {
+    /// 正在执行的操作
+    running: Option<RunningOperation>,
+    /// 等待执行的操作（按优先级排序，同优先级按提交顺序）
+    queue: VecDeque<GameOperation>,
+    /// 统计
+    stats: ExecutorStats,
+}
+
+/// 操作执行器
+///
+/// 需要在 tokio 运行时中使用
+pub struct OperationExecutor<B: InputBackend + 'static = PlatformInputBackend> {
+    /// 操作服务
+    service: Arc<ActionService<B>>,
+    /// 执行器状态
+    state: Mutex<ExecutorState>,
+    /// 执行器空闲时通知
+    idle: Notify,
+    /// 队列长度上限
+    capacity: usize,
+    /// 单个操作的最长执行时间
+    timeout: Option<Duration>,
+}
+
+impl<B: InputBackend + 'static> OperationExecutor<B> {
+    /// 创建执行器
+    pub fn new(service: Arc<ActionService<B>>, capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            service,
+            state: Mutex::new(ExecutorState::default()),
+            idle: Notify::new(),
+            capacity,
+            timeout: None,
+        })
+    }
+
+    /// 创建带单个操作超时的执行器
+    pub fn with_timeout(service: Arc<ActionService<B>>, capacity: usize, timeout: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            service,
+            state: Mutex::new(ExecutorState::default()),
+            idle: Notify::new(),
+            capacity,
+            timeout: Some(timeout),
+        })
+    }
+
+    /// 提交操作
+    pub fn submit(self: &Arc<Self>, operation: GameOperation) -> SubmitOutcome {
+        self.submit_with(operation, false)
+    }
+
+    /// 提交在同名操作执行期间按下、触发时才提交的操作
+    ///
+    /// 单击、长按、双击和释放晚于按下触发，同名操作可能已经结束。
+    /// 按下时执行器对同名操作是忙碌的，`DropIfBusy` 丢弃、`Coalesce` 合并，其他策略与 `submit` 相同
+    pub fn submit_reentry(self: &Arc<Self>, operation: GameOperation) -> SubmitOutcome {
+        self.submit_with(operation, true)
+    }
+
+    fn submit_with(self: &Arc<Self>, operation: GameOperation, reentry: bool) -> SubmitOutcome {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        state.stats.submitted += 1;
+
+        if !operation.enabled {
+            state.stats.dropped += 1;
+            return SubmitOutcome::Dropped(DropReason::Disabled);
+        }
+
+        if reentry {
+            match operation.conflict_policy {
+                ConflictPolicy::DropIfBusy => {
+                    log::debug!("热键在操作 {} 执行期间按下，丢弃", operation.name);
+                    state.stats.dropped += 1;
+                    return SubmitOutcome::Dropped(DropReason::Busy);
+                }
+                ConflictPolicy::Coalesce => {
+                    log::debug!("热键在操作 {} 执行期间按下，合并", operation.name);
+                    state.stats.coalesced += 1;
+                    return SubmitOutcome::Coalesced;
+                }
+                ConflictPolicy::Queue | ConflictPolicy::PreemptLower => {}
+            }
+        }
+
+        let Some(running) = state.running.as_mut() else {
+            self.start(&mut state, operation);
+            return SubmitOutcome::Started;
+        };
+
+        match operation.conflict_policy {
+            ConflictPolicy::Queue => {}
+            ConflictPolicy::DropIfBusy => {
+                log::debug!("执行器忙碌，丢弃操作: {}", operation.name);
+                state.stats.dropped += 1;
+                return SubmitOutcome::Dropped(DropReason::Busy);
+            }
+            ConflictPolicy::PreemptLower => {
+                if operation.priority > running.priority && !running.preempted {
+                    let (id, preempted) = (running.id, running.name.clone());
+                    let name = operation.name.clone();
+                    // 先放入队首再打断，队列已满且无法挤掉更低优先级的操作时不打断
+                    if let Err(outcome) = self.reserve(&mut state, &operation) {
+                        return outcome;
+                    }
+                    state.queue.push_front(operation);
+
+                    log::info!("操作 {} 打断正在执行的 {}", name, preempted);
+                    if let Some(running) = state.running.as_mut() {
+                        running.preempted = true;
+                    }
+                    self.service.registry().cancel(id);
+                    return SubmitOutcome::Preempted(preempted);
+                }
+            }
+            ConflictPolicy::Coalesce => {
+                if running.name == operation.name
+                    || state.queue.iter().any(|queued| queued.name == operation.name)
+                {
+                    state.stats.coalesced += 1;
+                    return SubmitOutcome::Coalesced;
+                }
+            }
+        }
+
+        match self.enqueue(&mut state, operation) {
+            Ok(position) => SubmitOutcome::Queued(position),
+            Err(outcome) => outcome,
+        }
+    }
+
+    /// 按优先级插入队列（同优先级先到先执行）
+    fn enqueue(&self, state: &mut ExecutorState, operation: GameOperation) -> Result<usize, SubmitOutcome> {
+        self.reserve(state, &operation)?;
+        let position = state.queue.iter()
+            .position(|queued| queued.priority < operation.priority)
+            .unwrap_or(state.queue.len());
+        state.queue.insert(position, operation);
+        Ok(position)
+    }
+
+    /// 为新操作腾出队列位置
+    ///
+    /// 队列已满时挤掉队尾优先级更低的操作，没有更低优先级的操作则丢弃新操作
+    fn reserve(&self, state: &mut ExecutorState, operation: &GameOperation) -> Result<(), SubmitOutcome> {
+        if state.queue.len() >= self.capacity {
+            match state.queue.back() {
+                Some(last) if last.priority < operation.priority => {
+                    log::warn!("操作队列已满（{}），丢弃优先级较低的操作: {}", self.capacity, last.name);
+                    state.queue.pop_back();
+                    state.stats.dropped += 1;
+                }
+                _ => {
+                    log::warn!("操作队列已满（{}），丢弃操作: {}", self.capacity, operation.name);
+                    state.stats.dropped += 1;
+                    return Err(SubmitOutcome::Dropped(DropReason::QueueFull));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 开始执行操作并启动后台任务
+    fn start(self: &Arc<Self>, state: &mut ExecutorState, operation: GameOperation) {
+        let handle = self.launch(state, operation);
+        let executor = Arc::clone(self);
+        tokio::spawn(async move { executor.drain(handle).await });
+    }
+
+    /// 执行操作序列并记录为正在执行
+    fn launch(&self, state: &mut ExecutorState, operation: GameOperation) -> SequenceHandle {
+        log::info!("执行操作: {}", operation.name);
+        let handle = self.service.spawn_sequence(operation.sequence, self.timeout);
+        state.running = Some(RunningOperation {
+            name: operation.name,
+            priority: operation.priority,
+            id: handle.id(),
+            preempted: false,
+        });
+        handle
+    }
+
+    /// 依次执行直到队列为空
+    async fn drain(self: Arc<Self>, mut handle: SequenceHandle) {
+        loop {
+            let result = handle.await;
+
+            let mut state = match self.state.lock() {
+                Ok(state) => state,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            let running = state.running.take();
+            match (&result, running) {
+                (Ok(_), _) => state.stats.completed += 1,
+                (Err(ActionError::Cancelled), Some(running)) if running.preempted => {
+                    state.stats.preempted += 1;
+                }
+                (Err(ActionError::Cancelled), _) => state.stats.cancelled += 1,
+                (Err(e), running) => {
+                    let name = running.map(|running| running.name).unwrap_or_default();
+                    log::error!("操作 {} 执行失败: {}", name, e);
+                    state.stats.failed += 1;
+                }
+            }
+
+            match state.queue.pop_front() {
+                Some(operation) => handle = self.launch(&mut state, operation),
+                None => {
+                    drop(state);
+                    self.idle.notify_waiters();
+                    return;
+                }
+            }
+        }
+    }
+
+    /// 清空队列并取消正在执行的操作，返回清除的排队数量
+    pub fn stop(&self) -> usize {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let cleared = state.queue.len();
+        state.queue.clear();
+        if let Some(running) = &state.running {
+            self.service.registry().cancel(running.id);
+        }
+        cleared
+    }
+
+    /// 等待执行器空闲
+    pub async fn wait_idle(&self) {
+        loop {
+            let notified = self.idle.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if !self.is_busy() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// 是否正在执行操作
+    pub fn is_busy(&self) -> bool {
+        self.state.lock().map(|state| state.running.is_some()).unwrap_or(false)
+    }
+
+    /// 正在执行的操作名称
+    pub fn running(&self) -> Option<String> {
+        self.state.lock().ok()?.running.as_ref().map(|running| running.name.clone())
+    }
+
+    /// 排队中的操作名称（按执行顺序）
+    pub fn queued(&self) -> Vec<String> {
+        self.state.lock()
+            .map(|state| state.queue.iter().map(|operation| operation.name.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// 当前队列长度
+    pub fn queue_len(&self) -> usize {
+        self.state.lock().map(|state| state.queue.len()).unwrap_or(0)
+    }
+
+    /// 队列长度上限
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 统计
+    pub fn stats(&self) -> ExecutorStats {
+        self.state.lock().map(|state| state.stats).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::input_backend::{InputEvent, RecordingInputBackend};
+
+    fn executor(capacity: usize) -> Arc<OperationExecutor<RecordingInputBackend>> {
+        OperationExecutor::new(
+            Arc::new(ActionService::with_backend(RecordingInputBackend::new())),
+            capacity,
+        )
+    }
+
+    fn operation(name: &str, key: &str, wait_ms: u64, policy: ConflictPolicy, priority: u8) -> GameOperation {
+        let mut operation = GameOperation::new(name.to_string(), key.to_string(), key.to_string());
+        operation.sequence.add_wait(Duration::from_millis(wait_ms));
+        operation.sequence.add_key_press(key.to_string());
+        operation.conflict_policy = policy;
+        operation.priority = priority;
+        operation
+    }
+
+    fn pressed(executor: &OperationExecutor<RecordingInputBackend>) -> Vec<u16> {
+        executor.service.backend().events().into_iter()
+            .filter_map(|event| match event {
+                InputEvent::KeyDown(vk) => Some(vk),
+                _ => None,
+            })
+            .collect()
+    }
+
+    async fn settle(executor: &OperationExecutor<RecordingInputBackend>) {
+        tokio::time::timeout(Duration::from_secs(2), executor.wait_idle()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_queue_runs_in_priority_order() {
+        let executor = executor(4);
+
+        assert_eq!(executor.submit(operation("a", "A", 20, ConflictPolicy::Queue, 0)), SubmitOutcome::Started);
+        assert_eq!(executor.submit(operation("b", "B", 0, ConflictPolicy::Queue, 0)), SubmitOutcome::Queued(0));
+        assert_eq!(executor.submit(operation("c", "C", 0, ConflictPolicy::Queue, 5)), SubmitOutcome::Queued(0));
+        assert_eq!(executor.running(), Some("a".to_string()));
+        assert_eq!(executor.queued(), vec!["c".to_string(), "b".to_string()]);
+
+        settle(&executor).await;
+        assert_eq!(pressed(&executor), vec![0x41, 0x43, 0x42]);
+        assert_eq!(executor.stats().completed, 3);
+        assert_eq!(executor.queue_len(), 0);
+        assert!(!executor.is_busy());
+    }
+
+    #[tokio::test]
+    async fn test_drop_if_busy_and_queue_full() {
+        let executor = executor(1);
+
+        executor.submit(operation("a", "A", 20, ConflictPolicy::Queue, 0));
+        assert_eq!(
+            executor.submit(operation("b", "B", 0, ConflictPolicy::DropIfBusy, 0)),
+            SubmitOutcome::Dropped(DropReason::Busy)
+        );
+        assert_eq!(executor.submit(operation("c", "C", 0, ConflictPolicy::Queue, 0)), SubmitOutcome::Queued(0));
+        assert_eq!(
+            executor.submit(operation("d", "D", 0, ConflictPolicy::Queue, 0)),
+            SubmitOutcome::Dropped(DropReason::QueueFull)
+        );
+
+        let mut disabled = operation("e", "E", 0, ConflictPolicy::Queue, 0);
+        disabled.enabled = false;
+        assert_eq!(executor.submit(disabled), SubmitOutcome::Dropped(DropReason::Disabled));
+
+        settle(&executor).await;
+        assert_eq!(pressed(&executor), vec![0x41, 0x43]);
+
+        let stats = executor.stats();
+        assert_eq!(stats.submitted, 5);
+        assert_eq!(stats.dropped, 3);
+        assert_eq!(stats.completed, 2);
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_same_operation() {
+        let executor = executor(4);
+
+        executor.submit(operation("skill", "E", 20, ConflictPolicy::Coalesce, 0));
+        assert_eq!(
+            executor.submit(operation("skill", "E", 20, ConflictPolicy::Coalesce, 0)),
+            SubmitOutcome::Coalesced
+        );
+        executor.submit(operation("other", "Q", 0, ConflictPolicy::Coalesce, 0));
+        assert_eq!(
+            executor.submit(operation("other", "Q", 0, ConflictPolicy::Coalesce, 0)),
+            SubmitOutcome::Coalesced
+        );
+
+        settle(&executor).await;
+        assert_eq!(pressed(&executor), vec![0x45, 0x51]);
+        assert_eq!(executor.stats().coalesced, 2);
+    }
+
+    #[tokio::test]
+    async fn test_preempt_lower_priority() {
+        let executor = executor(4);
+
+        executor.submit(operation("slow", "A", 5_000, ConflictPolicy::Queue, 0));
+        executor.submit(operation("queued", "B", 0, ConflictPolicy::Queue, 0));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert_eq!(
+            executor.submit(operation("low", "C", 0, ConflictPolicy::PreemptLower, 0)),
+            SubmitOutcome::Queued(1)
+        );
+        assert_eq!(
+            executor.submit(operation("pause", "Escape", 0, ConflictPolicy::PreemptLower, 100)),
+            SubmitOutcome::Preempted("slow".to_string())
+        );
+
+        settle(&executor).await;
+        assert_eq!(pressed(&executor), vec![0x1B, 0x42, 0x43]);
+
+        let stats = executor.stats();
+        assert_eq!(stats.preempted, 1);
+        assert_eq!(stats.completed, 3);
+        assert_eq!(stats.failed, 0);
+    }
+
+    #[tokio::test]
+    async fn test_preempt_respects_capacity_and_priority() {
+        let executor = executor(2);
+
+        executor.submit(operation("slow", "A", 5_000, ConflictPolicy::Queue, 0));
+        executor.submit(operation("high", "B", 0, ConflictPolicy::Queue, 50));
+        executor.submit(operation("low", "C", 0, ConflictPolicy::Queue, 0));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // 挤掉队尾优先级更低的操作，打断后最先执行
+        assert_eq!(
+            executor.submit(operation("pause", "Escape", 0, ConflictPolicy::PreemptLower, 10)),
+            SubmitOutcome::Preempted("slow".to_string())
+        );
+        assert_eq!(executor.queued(), vec!["pause".to_string(), "high".to_string()]);
+        settle(&executor).await;
+        assert_eq!(pressed(&executor), vec![0x1B, 0x42]);
+
+        // 队列中没有更低优先级的操作时丢弃，不再打断
+        let executor = self::executor(1);
+        executor.submit(operation("slow", "A", 5_000, ConflictPolicy::Queue, 0));
+        executor.submit(operation("high", "B", 0, ConflictPolicy::Queue, 50));
+        assert_eq!(
+            executor.submit(operation("pause", "Escape", 0, ConflictPolicy::PreemptLower, 10)),
+            SubmitOutcome::Dropped(DropReason::QueueFull)
+        );
+        assert_eq!(executor.running(), Some("slow".to_string()));
+        assert_eq!(executor.stats().preempted, 0);
+        assert_eq!(executor.stop(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reentry_follows_policy() {
+        let executor = executor(4);
+
+        // 同名操作已经结束，按下时忙碌的策略仍然生效
+        assert_eq!(
+            executor.submit_reentry(operation("drop", "A", 0, ConflictPolicy::DropIfBusy, 0)),
+            SubmitOutcome::Dropped(DropReason::Busy)
+        );
+        assert_eq!(
+            executor.submit_reentry(operation("merge", "B", 0, ConflictPolicy::Coalesce, 0)),
+            SubmitOutcome::Coalesced
+        );
+        assert_eq!(
+            executor.submit_reentry(operation("queue", "C", 0, ConflictPolicy::Queue, 0)),
+            SubmitOutcome::Started
+        );
+        settle(&executor).await;
+        assert_eq!(pressed(&executor), vec![0x43]);
+
+        let stats = executor.stats();
+        assert_eq!((stats.submitted, stats.dropped, stats.coalesced, stats.completed), (3, 1, 1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_stop_clears_queue() {
+        let executor = executor(4);
+
+        executor.submit(operation("slow", "A", 5_000, ConflictPolicy::Queue, 0));
+        executor.submit(operation("next", "B", 0, ConflictPolicy::Queue, 0));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert_eq!(executor.stop(), 1);
+        settle(&executor).await;
+        assert!(pressed(&executor).is_empty());
+        assert_eq!(executor.stats().cancelled, 1);
+        assert_eq!(executor.stats().failed, 0);
+    }
+}
@@ -0,0 +1,263 @@
+//! 安全保护
+//!
+//! 限制每秒发送的输入事件数量和单个序列的执行时间，并提供紧急停止开关。
+//! 紧急停止后除释放类事件外的所有输入都会被拒绝，直到调用 `reset`
+
+use crate::models::{KeyCombo, SafetySettings};
+use crate::services::input_backend::InputEvent;
+use crate::utils::{ActionError, ActionResult};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// 速率统计窗口
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// 安全违规
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SafetyViolation {
+    /// 输入速率超过限制
+    RateLimit {
+        /// 序列名称
+        sequence: String,
+        /// 每秒事件数量上限
+        limit: u32,
+    },
+    /// 序列执行时间超过上限
+    DurationExceeded {
+        /// 序列名称
+        sequence: String,
+        /// 执行时间上限
+        limit: Duration,
+    },
+    /// 紧急停止
+    EmergencyStop,
+}
+
+impl fmt::Display for SafetyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SafetyViolation::RateLimit { sequence, limit } => {
+                write!(f, "序列 {} 输入过快（每秒上限 {} 个事件），已停止", sequence, limit)
+            }
+            SafetyViolation::DurationExceeded { sequence, limit } => {
+                write!(f, "序列 {} 执行超过 {:?}，已停止", sequence, limit)
+            }
+            SafetyViolation::EmergencyStop => write!(f, "已紧急停止，所有序列已取消"),
+        }
+    }
+}
+
+/// 安全保护
+#[derive(Debug)]
+pub struct SafetyGuard {
+    /// 安全设置
+    settings: RwLock<SafetySettings>,
+    /// 最近一秒内已放行事件的时间
+    window: Mutex<VecDeque<Instant>>,
+    /// 是否处于紧急停止状态
+    engaged: AtomicBool,
+    /// 违规通知
+    violations: broadcast::Sender<SafetyViolation>,
+}
+
+impl SafetyGuard {
+    /// 根据安全设置创建
+    pub fn new(settings: SafetySettings) -> Self {
+        let (violations, _) = broadcast::channel(32);
+        Self {
+            settings: RwLock::new(settings),
+            window: Mutex::new(VecDeque::new()),
+            engaged: AtomicBool::new(false),
+            violations,
+        }
+    }
+
+    /// 当前安全设置
+    pub fn settings(&self) -> SafetySettings {
+        self.settings.read()
+            .map(|settings| settings.clone())
+            .unwrap_or_default()
+    }
+
+    /// 更新安全设置
+    pub fn update_settings(&self, settings: SafetySettings) {
+        if let Ok(mut current) = self.settings.write() {
+            *current = settings;
+        }
+    }
+
+    /// 单个序列的最长执行时间，未限制时为空
+    pub fn max_sequence_duration(&self) -> Option<Duration> {
+        match self.settings().max_sequence_duration_ms {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+
+    /// 紧急停止组合键，未配置或无效时为空
+    pub fn panic_key(&self) -> Option<KeyCombo> {
+        let spec = self.settings().panic_key;
+        if spec.trim().is_empty() {
+            return None;
+        }
+        spec.parse()
+            .map_err(|e| log::warn!("紧急停止按键无效: {}", e))
+            .ok()
+    }
+
+    /// 按下的组合键是否为当前设置的紧急停止按键
+    pub fn is_panic_key(&self, pressed: &KeyCombo) -> bool {
+        let spec = self.settings().panic_key;
+        !spec.trim().is_empty() && spec.parse::<KeyCombo>().is_ok_and(|panic_key| panic_key.matches(pressed))
+    }
+
+    /// 检查一批事件能否发送
+    ///
+    /// 按键释放和鼠标按钮释放总是放行且不计入速率，保证按住的输入始终能被释放
+    pub fn admit(&self, events: &[InputEvent]) -> ActionResult<()> {
+        let count = events.iter()
+            .filter(|event| !matches!(event, InputEvent::KeyUp(_) | InputEvent::MouseUp(_)))
+            .count();
+        if count == 0 {
+            return Ok(());
+        }
+        if self.is_engaged() {
+            return Err(ActionError::EmergencyStop);
+        }
+
+        let limit = self.settings().max_events_per_second;
+        if limit == 0 {
+            return Ok(());
+        }
+
+        let Ok(mut window) = self.window.lock() else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        while window.front().is_some_and(|sent| now.duration_since(*sent) >= RATE_WINDOW) {
+            window.pop_front();
+        }
+        if window.len() + count > limit as usize {
+            return Err(ActionError::RateLimitExceeded(limit));
+        }
+        window.extend(std::iter::repeat_n(now, count));
+        Ok(())
+    }
+
+    /// 进入紧急停止状态
+    pub fn engage(&self) {
+        if !self.engaged.swap(true, Ordering::SeqCst) {
+            log::warn!("紧急停止已触发");
+        }
+    }
+
+    /// 解除紧急停止状态
+    pub fn reset(&self) {
+        if self.engaged.swap(false, Ordering::SeqCst) {
+            log::info!("紧急停止已解除");
+        }
+        if let Ok(mut window) = self.window.lock() {
+            window.clear();
+        }
+    }
+
+    /// 是否处于紧急停止状态
+    pub fn is_engaged(&self) -> bool {
+        self.engaged.load(Ordering::SeqCst)
+    }
+
+    /// 发布违规通知
+    pub fn report(&self, violation: SafetyViolation) {
+        log::warn!("安全保护: {}", violation);
+        let _ = self.violations.send(violation);
+    }
+
+    /// 订阅违规通知
+    pub fn subscribe(&self) -> broadcast::Receiver<SafetyViolation> {
+        self.violations.subscribe()
+    }
+}
+
+impl Default for SafetyGuard {
+    fn default() -> Self {
+        Self::new(SafetySettings::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MouseButton;
+
+    fn limited(max_events_per_second: u32) -> SafetyGuard {
+        SafetyGuard::new(SafetySettings {
+            max_events_per_second,
+            ..SafetySettings::default()
+        })
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let guard = limited(3);
+
+        assert!(guard.admit(&[InputEvent::KeyDown(0x41), InputEvent::MouseMove(1, 1)]).is_ok());
+        assert!(guard.admit(&[InputEvent::KeyDown(0x42)]).is_ok());
+        assert!(matches!(
+            guard.admit(&[InputEvent::KeyDown(0x43)]),
+            Err(ActionError::RateLimitExceeded(3))
+        ));
+
+        // 释放事件不受限制
+        assert!(guard.admit(&[InputEvent::KeyUp(0x41), InputEvent::MouseUp(MouseButton::Left)]).is_ok());
+
+        // 不限制
+        let unlimited = limited(0);
+        for _ in 0..1000 {
+            assert!(unlimited.admit(&[InputEvent::KeyDown(0x41)]).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_emergency_stop_blocks_until_reset() {
+        let guard = limited(0);
+        let mut violations = guard.subscribe();
+
+        guard.engage();
+        guard.report(SafetyViolation::EmergencyStop);
+        assert!(matches!(guard.admit(&[InputEvent::KeyDown(0x41)]), Err(ActionError::EmergencyStop)));
+        assert!(guard.admit(&[InputEvent::KeyUp(0x41)]).is_ok());
+        assert_eq!(violations.try_recv().unwrap(), SafetyViolation::EmergencyStop);
+
+        guard.reset();
+        assert!(guard.admit(&[InputEvent::KeyDown(0x41)]).is_ok());
+    }
+
+    #[test]
+    fn test_settings() {
+        let guard = SafetyGuard::default();
+        assert_eq!(guard.max_sequence_duration(), Some(Duration::from_secs(30)));
+        assert_eq!(guard.panic_key(), Some("Ctrl+Shift+F12".parse().unwrap()));
+        assert!(guard.is_panic_key(&"LeftCtrl+Shift+F12".parse().unwrap()));
+
+        // 修改后的紧急停止按键立即生效
+        guard.update_settings(SafetySettings {
+            panic_key: "F9".to_string(),
+            ..guard.settings()
+        });
+        assert!(guard.is_panic_key(&"F9".parse().unwrap()));
+        assert!(!guard.is_panic_key(&"Ctrl+Shift+F12".parse().unwrap()));
+
+        guard.update_settings(SafetySettings {
+            max_events_per_second: 0,
+            max_sequence_duration_ms: 0,
+            panic_key: String::new(),
+        });
+        assert_eq!(guard.max_sequence_duration(), None);
+        assert_eq!(guard.panic_key(), None);
+        assert!(!guard.is_panic_key(&"F9".parse().unwrap()));
+    }
+}
@@ -0,0 +1,283 @@
+//! 序列控制流支持
+//!
+//! 定义 `Call` 使用的序列解析器和 `Loop` / `If` 使用的条件来源
+
+use crate::models::{parse_slot_operation, ActionSequence, AppState, Condition, GameState, ProgramState, SLOT_PARAM};
+use crate::services::mode_manager::ModeManager;
+use crate::utils::{ActionError, ActionResult};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+/// 序列调用的最大嵌套深度
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 16;
+
+/// 单个循环允许的最大次数上限（与 `max_iterations` 取较小值），也是 `Repeat` 允许的最大次数
+pub const MAX_LOOP_ITERATIONS: u32 = 100_000;
+
+/// 查找序列和计算条件时等待锁的 Future
+pub type FlowFuture<'a, T> = Pin<Box<dyn Future<Output = ActionResult<T>> + Send + 'a>>;
+
+/// 序列解析器
+///
+/// 根据名称查找 `ActionType::Call` 引用的序列
+pub trait SequenceResolver: Send + Sync {
+    /// 查找序列，未找到时返回错误
+    fn resolve_sequence(&self, name: &str) -> ActionResult<ActionSequence>;
+
+    /// 查找序列，注册表正在更新时等待更新完成。序列执行时使用此方法
+    fn resolve_sequence_async<'a>(&'a self, name: &'a str) -> FlowFuture<'a, ActionSequence> {
+        Box::pin(async move { self.resolve_sequence(name) })
+    }
+}
+
+impl SequenceResolver for HashMap<String, ActionSequence> {
+    fn resolve_sequence(&self, name: &str) -> ActionResult<ActionSequence> {
+        self.get(name)
+            .cloned()
+            .ok_or_else(|| ActionError::SequenceError(format!("未找到序列: {}", name)))
+    }
+}
+
+impl SequenceResolver for ModeManager {
+    /// 从已启用的游戏操作中查找，其次查找宏模式配置中的自定义序列。
+    /// `deploy_operator[3]` 这样的槽位操作解析为该操作的序列，并带上槽位参数和槽位按键
+    fn resolve_sequence(&self, name: &str) -> ActionResult<ActionSequence> {
+        if let Some((operation, slot)) = parse_slot_operation(name) {
+            let sequence = self.resolve_sequence(operation)?.with_param(SLOT_PARAM, slot as i64);
+            return Ok(match self.slot_key(operation, slot) {
+                Some(key) => sequence.with_slot_key(&key),
+                None => sequence,
+            });
+        }
+        match self.get_game_operation(name) {
+            Some(operation) if operation.enabled => Ok(operation.sequence.clone()),
+            Some(_) => Err(ActionError::SequenceError(format!("操作已禁用: {}", name))),
+            None => self.get_macro_config().sequences.get(name)
+                .cloned()
+                .ok_or_else(|| ActionError::SequenceError(format!("未找到操作: {}", name))),
+        }
+    }
+}
+
+impl<T: SequenceResolver> SequenceResolver for tokio::sync::RwLock<T> {
+    /// 不等待写锁，注册表正在更新时返回错误
+    fn resolve_sequence(&self, name: &str) -> ActionResult<ActionSequence> {
+        self.try_read()
+            .map_err(|_| ActionError::SequenceError("操作注册表正在更新".to_string()))?
+            .resolve_sequence(name)
+    }
+
+    fn resolve_sequence_async<'a>(&'a self, name: &'a str) -> FlowFuture<'a, ActionSequence> {
+        Box::pin(async move { self.read().await.resolve_sequence(name) })
+    }
+}
+
+/// 条件来源
+pub trait ConditionSource: Send + Sync {
+    /// 当前程序状态
+    fn program_state(&self) -> ActionResult<ProgramState>;
+
+    /// 当前游戏状态
+    fn game_state(&self) -> ActionResult<GameState>;
+
+    /// 命名的图像识别判断
+    fn vision_predicate(&self, name: &str) -> ActionResult<bool>;
+
+    /// 计算条件，状态正在更新时等待更新完成。序列执行时使用此方法
+    fn evaluate_async<'a>(&'a self, condition: &'a Condition) -> FlowFuture<'a, bool> {
+        Box::pin(async move { evaluate_condition(condition, self) })
+    }
+}
+
+/// 计算条件
+pub fn evaluate_condition<S: ConditionSource + ?Sized>(condition: &Condition, source: &S) -> ActionResult<bool> {
+    match condition {
+        Condition::ProgramState(state) => Ok(source.program_state()? == *state),
+        Condition::GameState(state) => Ok(source.game_state()? == *state),
+        Condition::Vision(name) => source.vision_predicate(name),
+        Condition::Not(inner) => Ok(!evaluate_condition(inner, source)?),
+        Condition::All(conditions) => {
+            for condition in conditions {
+                if !evaluate_condition(condition, source)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        Condition::Any(conditions) => {
+            for condition in conditions {
+                if evaluate_condition(condition, source)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+    }
+}
+
+/// 条件快照
+///
+/// 放在 `std::sync::RwLock` 中可以在序列执行期间更新
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConditionSnapshot {
+    /// 程序状态
+    pub program_state: ProgramState,
+    /// 游戏状态
+    pub game_state: GameState,
+    /// 图像识别判断结果
+    pub vision: HashMap<String, bool>,
+}
+
+impl Default for ConditionSnapshot {
+    fn default() -> Self {
+        Self {
+            program_state: ProgramState::Stopped,
+            game_state: GameState::NotDetected,
+            vision: HashMap::new(),
+        }
+    }
+}
+
+impl ConditionSource for ConditionSnapshot {
+    fn program_state(&self) -> ActionResult<ProgramState> {
+        Ok(self.program_state)
+    }
+
+    fn game_state(&self) -> ActionResult<GameState> {
+        Ok(self.game_state)
+    }
+
+    fn vision_predicate(&self, name: &str) -> ActionResult<bool> {
+        self.vision.get(name)
+            .copied()
+            .ok_or_else(|| ActionError::InvalidParameter(format!("未知的图像识别判断: {}", name)))
+    }
+}
+
+impl<T: ConditionSource> ConditionSource for std::sync::RwLock<T> {
+    fn program_state(&self) -> ActionResult<ProgramState> {
+        self.read()
+            .map_err(|_| ActionError::SystemCall("条件来源锁定失败".to_string()))?
+            .program_state()
+    }
+
+    fn game_state(&self) -> ActionResult<GameState> {
+        self.read()
+            .map_err(|_| ActionError::SystemCall("条件来源锁定失败".to_string()))?
+            .game_state()
+    }
+
+    fn vision_predicate(&self, name: &str) -> ActionResult<bool> {
+        self.read()
+            .map_err(|_| ActionError::SystemCall("条件来源锁定失败".to_string()))?
+            .vision_predicate(name)
+    }
+}
+
+/// 应用状态，不支持图像识别判断
+impl ConditionSource for AppState {
+    fn program_state(&self) -> ActionResult<ProgramState> {
+        Ok(self.program_state)
+    }
+
+    fn game_state(&self) -> ActionResult<GameState> {
+        Ok(self.game_state)
+    }
+
+    fn vision_predicate(&self, name: &str) -> ActionResult<bool> {
+        Err(ActionError::UnsupportedOperation(format!("应用状态不提供图像识别判断: {}", name)))
+    }
+}
+
+/// 直接读取 `StateManager` 共享的应用状态，不支持图像识别判断
+impl ConditionSource for tokio::sync::RwLock<AppState> {
+    fn program_state(&self) -> ActionResult<ProgramState> {
+        self.try_read()
+            .map(|state| state.program_state)
+            .map_err(|_| ActionError::SequenceError("应用状态正在更新".to_string()))
+    }
+
+    fn game_state(&self) -> ActionResult<GameState> {
+        self.try_read()
+            .map(|state| state.game_state)
+            .map_err(|_| ActionError::SequenceError("应用状态正在更新".to_string()))
+    }
+
+    fn vision_predicate(&self, name: &str) -> ActionResult<bool> {
+        Err(ActionError::UnsupportedOperation(format!("应用状态不提供图像识别判断: {}", name)))
+    }
+
+    /// 在同一个状态快照上计算整个条件
+    fn evaluate_async<'a>(&'a self, condition: &'a Condition) -> FlowFuture<'a, bool> {
+        Box::pin(async move { evaluate_condition(condition, &*self.read().await) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ActionType;
+
+    #[test]
+    fn test_evaluate_condition() {
+        let mut snapshot = ConditionSnapshot {
+            program_state: ProgramState::Running,
+            game_state: GameState::InBattle,
+            vision: HashMap::new(),
+        };
+        snapshot.vision.insert("skill_ready".to_string(), true);
+
+        assert!(evaluate_condition(&Condition::ProgramState(ProgramState::Running), &snapshot).unwrap());
+        assert!(!evaluate_condition(&Condition::GameState(GameState::Detected), &snapshot).unwrap());
+        assert!(evaluate_condition(&Condition::Vision("skill_ready".to_string()), &snapshot).unwrap());
+        assert!(evaluate_condition(&Condition::Vision("unknown".to_string()), &snapshot).is_err());
+
+        let combined = Condition::All(vec![
+            Condition::GameState(GameState::InBattle),
+            Condition::Not(Box::new(Condition::ProgramState(ProgramState::Stopped))),
+        ]);
+        assert!(evaluate_condition(&combined, &snapshot).unwrap());
+        assert!(!evaluate_condition(&Condition::Any(vec![]), &snapshot).unwrap());
+    }
+
+    #[test]
+    fn test_mode_manager_resolver() {
+        let mut manager = ModeManager::new();
+
+        let sequence = manager.resolve_sequence("deploy_operator").unwrap();
+        assert_eq!(sequence.name, "deploy_operator");
+        assert!(manager.resolve_sequence("missing").is_err());
+
+        let sequence = manager.resolve_sequence("activate_skill[4]").unwrap();
+        assert_eq!(sequence.name, "activate_skill");
+        assert_eq!(sequence.params.get(SLOT_PARAM), Some(&4));
+        assert_eq!(sequence.actions[0], ActionType::KeyPress("R".to_string()));
+        assert!(manager.resolve_sequence("activate_skill[0]").is_err());
+        assert!(manager.resolve_sequence("focus_view[1]").is_err());
+
+        let mut operation = manager.get_game_operation("focus_view").unwrap().clone();
+        operation.enabled = false;
+        manager.set_game_operation(operation);
+        assert!(manager.resolve_sequence("focus_view").is_err());
+    }
+
+    #[test]
+    fn test_mode_manager_resolves_configured_sequences() {
+        let mut macro_config = crate::models::MacroModeConfig::default();
+        for (name, script) in [("deploy_operator", "key 1\nwait 3f"), ("combo", "call deploy_operator")] {
+            let mut sequence: ActionSequence = script.parse().unwrap();
+            sequence.name = name.to_string();
+            macro_config.sequences.insert(name.to_string(), sequence);
+        }
+        let manager = ModeManager::with_configs(macro_config, Default::default());
+
+        // 替换默认序列
+        let deploy = manager.resolve_sequence("deploy_operator").unwrap();
+        assert_eq!(deploy.to_string(), "@name deploy_operator\n\nkey 1\nwait 100ms\n");
+        assert_eq!(manager.get_game_operation("deploy_operator").unwrap().sequence, deploy);
+
+        // 不对应操作的序列可以被调用
+        assert_eq!(manager.resolve_sequence("combo").unwrap().actions.len(), 1);
+    }
+}
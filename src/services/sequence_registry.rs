@@ -0,0 +1,218 @@
+//! 序列注册表
+//!
+//! 记录所有正在执行的操作序列，支持单独或全部取消
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// 取消令牌
+///
+/// 克隆的令牌共享同一个取消状态
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<CancellationState>,
+}
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    /// 创建新的取消令牌
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 取消
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// 是否已取消
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// 等待直到被取消
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// 序列注册表
+#[derive(Debug, Default)]
+pub struct SequenceRegistry {
+    /// 下一个序列 ID
+    next_id: AtomicU64,
+    /// 正在执行的序列（ID -> (名称, 取消令牌)）
+    active: Mutex<HashMap<u64, (String, CancellationToken)>>,
+}
+
+impl SequenceRegistry {
+    /// 创建新的序列注册表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册一个序列，返回的登记在释放时自动注销
+    pub fn register(self: &Arc<Self>, name: &str) -> SequenceRegistration {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let token = CancellationToken::new();
+
+        if let Ok(mut active) = self.active.lock() {
+            active.insert(id, (name.to_string(), token.clone()));
+        }
+
+        SequenceRegistration {
+            id,
+            token,
+            registry: Arc::clone(self),
+        }
+    }
+
+    /// 取消指定序列
+    pub fn cancel(&self, id: u64) -> bool {
+        let token = self.active.lock()
+            .ok()
+            .and_then(|active| active.get(&id).map(|(_, token)| token.clone()));
+
+        match token {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 取消所有正在执行的序列，返回取消的数量
+    pub fn cancel_all(&self) -> usize {
+        let tokens: Vec<CancellationToken> = match self.active.lock() {
+            Ok(active) => active.values().map(|(_, token)| token.clone()).collect(),
+            Err(_) => return 0,
+        };
+
+        for token in &tokens {
+            token.cancel();
+        }
+
+        if !tokens.is_empty() {
+            log::info!("已取消 {} 个正在执行的操作序列", tokens.len());
+        }
+        tokens.len()
+    }
+
+    /// 正在执行的序列数量
+    pub fn active_count(&self) -> usize {
+        self.active.lock().map(|active| active.len()).unwrap_or(0)
+    }
+
+    /// 正在执行的序列（ID, 名称），按 ID 排序
+    pub fn active_sequences(&self) -> Vec<(u64, String)> {
+        let mut sequences: Vec<(u64, String)> = self.active.lock()
+            .map(|active| active.iter().map(|(id, (name, _))| (*id, name.clone())).collect())
+            .unwrap_or_default();
+        sequences.sort_by_key(|(id, _)| *id);
+        sequences
+    }
+
+    /// 注销序列
+    fn unregister(&self, id: u64) {
+        if let Ok(mut active) = self.active.lock() {
+            active.remove(&id);
+        }
+    }
+}
+
+/// 序列登记
+///
+/// 释放时从注册表中移除
+#[derive(Debug)]
+pub struct SequenceRegistration {
+    id: u64,
+    token: CancellationToken,
+    registry: Arc<SequenceRegistry>,
+}
+
+impl SequenceRegistration {
+    /// 序列 ID
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// 取消令牌
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for SequenceRegistration {
+    fn drop(&mut self) {
+        self.registry.unregister(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancellation_token_wakes_waiters() {
+        let token = CancellationToken::new();
+        let waiter = token.clone();
+
+        let task = tokio::spawn(async move {
+            waiter.cancelled().await;
+        });
+
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(!token.is_cancelled());
+        token.cancel();
+
+        tokio::time::timeout(Duration::from_secs(1), task).await.unwrap().unwrap();
+        assert!(token.is_cancelled());
+
+        // 已取消的令牌立即返回
+        token.cancelled().await;
+    }
+
+    #[test]
+    fn test_registry_register_and_cancel_all() {
+        let registry = Arc::new(SequenceRegistry::new());
+
+        let first = registry.register("first");
+        let second = registry.register("second");
+        assert_eq!(registry.active_count(), 2);
+        assert_eq!(
+            registry.active_sequences(),
+            vec![(first.id(), "first".to_string()), (second.id(), "second".to_string())]
+        );
+
+        assert!(registry.cancel(first.id()));
+        assert!(first.token().is_cancelled());
+        assert!(!second.token().is_cancelled());
+
+        assert_eq!(registry.cancel_all(), 2);
+        assert!(second.token().is_cancelled());
+
+        drop(first);
+        drop(second);
+        assert_eq!(registry.active_count(), 0);
+        assert!(!registry.cancel(1));
+    }
+}
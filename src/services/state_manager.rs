@@ -4,6 +4,7 @@
 //! 以及状态变更通知和状态持久化功能
 
 use crate::models::{AppState, ProgramState, GameState};
+use crate::services::sequence_registry::SequenceRegistry;
 use crate::utils::{StateError, StateResult};
 use std::sync::Arc;
 use tokio::sync::{RwLock, broadcast};
@@ -56,6 +57,8 @@ pub struct StateManager {
     persistence_config: Option<StatePersistenceConfig>,
     /// 自动保存任务句柄
     auto_save_handle: Option<tokio::task::JoinHandle<()>>,
+    /// 正在执行的操作序列
+    sequence_registry: Arc<SequenceRegistry>,
 }
 
 impl StateManager {
@@ -69,6 +72,7 @@ impl StateManager {
             event_sender,
             persistence_config: None,
             auto_save_handle: None,
+            sequence_registry: Arc::new(SequenceRegistry::new()),
         }
     }
     
@@ -82,9 +86,20 @@ impl StateManager {
             event_sender,
             persistence_config: Some(persistence_config),
             auto_save_handle: None,
+            sequence_registry: Arc::new(SequenceRegistry::new()),
         }
     }
     
+    /// 获取序列注册表，与 `ActionService::with_registry` 配合使用
+    pub fn sequence_registry(&self) -> Arc<SequenceRegistry> {
+        Arc::clone(&self.sequence_registry)
+    }
+    
+    /// 设置序列注册表
+    pub fn set_sequence_registry(&mut self, registry: Arc<SequenceRegistry>) {
+        self.sequence_registry = registry;
+    }
+    
     /// 初始化状态管理器（加载持久化状态）
     pub async fn initialize(&mut self) -> StateResult<()> {
         if let Some(config) = &self.persistence_config {
@@ -195,8 +210,10 @@ impl StateManager {
             observer.on_program_state_changed(old_state, ProgramState::Stopping);
         }
         
-        // TODO: 实际停止核心功能的逻辑
-        // 这里应该调用其他服务来停止核心功能
+        // 取消所有正在执行的操作序列
+        self.sequence_registry.cancel_all();
+        
+        // TODO: 停止其他核心功能
         
         // 模拟停止过程
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -219,7 +236,35 @@ impl StateManager {
         log::info!("核心功能已停止");
         Ok(())
     }
-    
+
+    /// 紧急停止
+    ///
+    /// 取消所有正在执行的操作序列，并从任意状态立即切换到停止状态
+    pub async fn emergency_stop(&mut self) {
+        self.sequence_registry.cancel_all();
+
+        let mut state = self.state.write().await;
+        let old_state = state.program_state;
+        if old_state == ProgramState::Stopped {
+            return;
+        }
+
+        state.update_program_state(ProgramState::Stopped);
+
+        let event = StateChangeEvent::ProgramStateChanged {
+            old_state,
+            new_state: ProgramState::Stopped,
+            timestamp: std::time::SystemTime::now(),
+        };
+        let _ = self.event_sender.send(event);
+
+        for observer in &self.observers {
+            observer.on_program_state_changed(old_state, ProgramState::Stopped);
+        }
+
+        log::warn!("核心功能已紧急停止");
+    }
+
     /// 暂停核心功能
     pub async fn pause_core(&mut self) -> StateResult<()> {
         let state = self.state.read().await;
@@ -402,6 +447,7 @@ impl StateManager {
                             event_sender: broadcast::channel(1).0,
                             persistence_config: Some(config_clone.clone()),
                             auto_save_handle: None,
+                            sequence_registry: Arc::new(SequenceRegistry::new()),
                         };
                         
                         if let Err(e) = temp_manager.save_state().await {
@@ -489,6 +535,45 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_stop_core_cancels_sequences() {
+        let mut manager = StateManager::new();
+        let registry = manager.sequence_registry();
+        
+        manager.update_game_state(GameState::Detected).await;
+        manager.start_core().await.unwrap();
+        let registration = registry.register("running");
+        assert!(!registration.token().is_cancelled());
+        
+        manager.stop_core().await.unwrap();
+        assert!(registration.token().is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_emergency_stop() {
+        let mut manager = StateManager::new();
+        let registry = manager.sequence_registry();
+        let mut events = manager.subscribe_events();
+
+        manager.update_game_state(GameState::Detected).await;
+        manager.start_core().await.unwrap();
+        let registration = registry.register("running");
+
+        manager.emergency_stop().await;
+        assert!(registration.token().is_cancelled());
+        assert_eq!(manager.get_program_state().await, ProgramState::Stopped);
+
+        // 最后一个事件是 Running -> Stopped
+        let mut last = None;
+        while let Ok(event) = events.try_recv() {
+            last = Some(event);
+        }
+        assert!(matches!(
+            last,
+            Some(StateChangeEvent::ProgramStateChanged { old_state: ProgramState::Running, new_state: ProgramState::Stopped, .. })
+        ));
+    }
+
     #[tokio::test]
     async fn test_state_manager_creation() {
         let manager = StateManager::new();
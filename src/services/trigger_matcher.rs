@@ -0,0 +1,333 @@
+//! 热键触发方式匹配
+//!
+//! 根据按键按下和释放的时间判断单击、长按、双击和释放触发。时间点由调用方传入，
+//! 相同的事件时间线总是得到相同的结果；长按和单击的判定需要在没有新事件时调用 `poll`
+
+use crate::models::{Hotkey, Key, KeyCombo, TriggerKind, TriggerSettings};
+use crate::services::global_input::{key_from_rdev, key_from_rdev_button, KeyTracker};
+use rdev::EventType;
+use std::time::Instant;
+
+/// 按住的按键
+#[derive(Debug, Clone)]
+struct HeldKey {
+    /// 按下时的组合键
+    combo: KeyCombo,
+    /// 按下时间
+    pressed_at: Instant,
+    /// 已触发长按
+    hold_fired: bool,
+    /// 双击的第二次按下，不再判定单击和长按
+    double_tapped: bool,
+}
+
+/// 等待双击的单击
+#[derive(Debug, Clone)]
+struct PendingTap {
+    /// 组合键
+    combo: KeyCombo,
+    /// 释放时间
+    released_at: Instant,
+}
+
+/// 热键触发方式匹配器
+///
+/// 只判定已绑定的触发方式：同一按键绑定了双击时，单击要等双击间隔过后才能确定
+#[derive(Debug, Clone)]
+pub struct TriggerMatcher {
+    /// 触发方式设置
+    settings: TriggerSettings,
+    /// 已绑定的热键
+    hotkeys: Vec<Hotkey>,
+    /// 按键状态
+    tracker: KeyTracker,
+    /// 按住的普通按键
+    held: Vec<HeldKey>,
+    /// 等待双击的单击
+    pending_tap: Option<PendingTap>,
+}
+
+impl TriggerMatcher {
+    /// 创建匹配器
+    pub fn new(settings: TriggerSettings) -> Self {
+        Self {
+            settings,
+            hotkeys: Vec::new(),
+            tracker: KeyTracker::new(),
+            held: Vec::new(),
+            pending_tap: None,
+        }
+    }
+
+    /// 更新触发方式设置
+    pub fn set_settings(&mut self, settings: TriggerSettings) {
+        self.settings = settings;
+    }
+
+    /// 更新已绑定的热键
+    ///
+    /// 保留按键状态；绑定变化的按住按键和等待中的单击被清除，不再按旧的绑定触发
+    pub fn set_hotkeys(&mut self, hotkeys: impl IntoIterator<Item = Hotkey>) {
+        let previous = std::mem::replace(&mut self.hotkeys, hotkeys.into_iter().collect());
+        let changed = |combo: &KeyCombo| TriggerKind::all().iter()
+            .any(|trigger| is_bound(&previous, combo, *trigger) != is_bound(&self.hotkeys, combo, *trigger));
+        self.held.retain(|held| !changed(&held.combo));
+        if self.pending_tap.as_ref().is_some_and(|pending| changed(&pending.combo)) {
+            self.pending_tap = None;
+        }
+    }
+
+    /// 处理 rdev 事件，返回触发的热键（组合键为实际按下的组合键）
+    pub fn handle(&mut self, event: &EventType, at: Instant) -> Vec<Hotkey> {
+        let (key, pressed) = match event {
+            EventType::KeyPress(key) => (key_from_rdev(*key), true),
+            EventType::KeyRelease(key) => (key_from_rdev(*key), false),
+            EventType::ButtonPress(button) => (key_from_rdev_button(*button), true),
+            EventType::ButtonRelease(button) => (key_from_rdev_button(*button), false),
+            _ => (None, false),
+        };
+        match key {
+            Some(key) if pressed => match self.tracker.press(key) {
+                Some(combo) => self.press(combo, at),
+                None => self.poll(at),
+            },
+            Some(key) => {
+                self.tracker.release(key);
+                self.release(key, at)
+            }
+            None => self.poll(at),
+        }
+    }
+
+    /// 处理普通按键按下
+    pub fn press(&mut self, combo: KeyCombo, at: Instant) -> Vec<Hotkey> {
+        let mut fired = self.poll(at);
+
+        // 未过期的单击：同一组合键为双击，其他按键使单击立即生效
+        let mut double_tapped = false;
+        if let Some(pending) = self.pending_tap.take() {
+            if pending.combo.matches(&combo) {
+                fired.push(Hotkey::new(combo.clone(), TriggerKind::DoubleTap));
+                double_tapped = true;
+            } else if self.is_bound(&pending.combo, TriggerKind::Tap) {
+                fired.push(Hotkey::new(pending.combo, TriggerKind::Tap));
+            }
+        }
+
+        if self.is_bound(&combo, TriggerKind::Press) {
+            fired.push(Hotkey::press(combo.clone()));
+        }
+        self.held.retain(|held| held.combo.key() != combo.key());
+        self.held.push(HeldKey {
+            combo,
+            pressed_at: at,
+            hold_fired: false,
+            double_tapped,
+        });
+        fired
+    }
+
+    /// 处理按键释放
+    pub fn release(&mut self, key: Key, at: Instant) -> Vec<Hotkey> {
+        let mut fired = self.poll(at);
+        let Some(index) = self.held.iter().position(|held| held.combo.key() == key) else {
+            return fired;
+        };
+        let held = self.held.remove(index);
+
+        if self.is_bound(&held.combo, TriggerKind::Release) {
+            fired.push(Hotkey::new(held.combo.clone(), TriggerKind::Release));
+        }
+        let short = at.saturating_duration_since(held.pressed_at) < self.settings.hold_threshold();
+        if short && !held.hold_fired && !held.double_tapped {
+            if self.is_bound(&held.combo, TriggerKind::DoubleTap) {
+                self.pending_tap = Some(PendingTap { combo: held.combo, released_at: at });
+            } else if self.is_bound(&held.combo, TriggerKind::Tap) {
+                fired.push(Hotkey::new(held.combo, TriggerKind::Tap));
+            }
+        }
+        fired
+    }
+
+    /// 判定到达时间点的长按和单击
+    pub fn poll(&mut self, at: Instant) -> Vec<Hotkey> {
+        let mut fired = Vec::new();
+
+        let threshold = self.settings.hold_threshold();
+        for held in &mut self.held {
+            if held.hold_fired || held.double_tapped || at < held.pressed_at + threshold {
+                continue;
+            }
+            if is_bound(&self.hotkeys, &held.combo, TriggerKind::Hold) {
+                held.hold_fired = true;
+                fired.push(Hotkey::new(held.combo.clone(), TriggerKind::Hold));
+            }
+        }
+
+        let window = self.settings.double_tap_window();
+        if let Some(pending) = self.pending_tap.take_if(|pending| at >= pending.released_at + window) {
+            if self.is_bound(&pending.combo, TriggerKind::Tap) {
+                fired.push(Hotkey::new(pending.combo, TriggerKind::Tap));
+            }
+        }
+        fired
+    }
+
+    /// 下一个需要调用 `poll` 的时间点
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let threshold = self.settings.hold_threshold();
+        let holds = self.held.iter()
+            .filter(|held| !held.hold_fired && !held.double_tapped)
+            .filter(|held| is_bound(&self.hotkeys, &held.combo, TriggerKind::Hold))
+            .map(|held| held.pressed_at + threshold);
+        let taps = self.pending_tap.iter()
+            .map(|pending| pending.released_at + self.settings.double_tap_window());
+        holds.chain(taps).min()
+    }
+
+    /// 按键是否正在按住
+    pub fn is_pressed(&self, key: Key) -> bool {
+        self.tracker.pressed().contains(&key)
+    }
+
+    /// 清空按键状态和等待中的单击
+    pub fn clear(&mut self) {
+        self.tracker.clear();
+        self.held.clear();
+        self.pending_tap = None;
+    }
+
+    fn is_bound(&self, combo: &KeyCombo, trigger: TriggerKind) -> bool {
+        is_bound(&self.hotkeys, combo, trigger)
+    }
+}
+
+/// 组合键是否绑定了指定的触发方式
+fn is_bound(hotkeys: &[Hotkey], combo: &KeyCombo, trigger: TriggerKind) -> bool {
+    hotkeys.iter().any(|hotkey| hotkey.trigger() == trigger && hotkey.combo().matches(combo))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rdev::Key as RKey;
+    use std::time::Duration;
+
+    /// 按时间线依次输入事件，记录每个事件和时间点触发的热键
+    struct Timeline {
+        matcher: TriggerMatcher,
+        origin: Instant,
+    }
+
+    impl Timeline {
+        fn new(hotkeys: &str) -> Self {
+            let mut matcher = TriggerMatcher::new(TriggerSettings::default());
+            matcher.set_hotkeys(Hotkey::parse_list(hotkeys).unwrap());
+            Self { matcher, origin: Instant::now() }
+        }
+
+        fn at(&self, ms: u64) -> Instant {
+            self.origin + Duration::from_millis(ms)
+        }
+
+        fn press(&mut self, ms: u64, key: RKey) -> Vec<String> {
+            let at = self.at(ms);
+            format(self.matcher.handle(&EventType::KeyPress(key), at))
+        }
+
+        fn release(&mut self, ms: u64, key: RKey) -> Vec<String> {
+            let at = self.at(ms);
+            format(self.matcher.handle(&EventType::KeyRelease(key), at))
+        }
+
+        fn poll(&mut self, ms: u64) -> Vec<String> {
+            let at = self.at(ms);
+            format(self.matcher.poll(at))
+        }
+
+        fn deadline(&self) -> Option<u64> {
+            self.matcher.next_deadline().map(|deadline| (deadline - self.origin).as_millis() as u64)
+        }
+    }
+
+    fn format(hotkeys: Vec<Hotkey>) -> Vec<String> {
+        hotkeys.iter().map(|hotkey| hotkey.generic().to_string()).collect()
+    }
+
+    #[test]
+    fn test_tap_and_hold_share_key() {
+        let mut timeline = Timeline::new("tap:1,hold:1,release:1");
+
+        // 短按：释放时单击
+        assert!(timeline.press(0, RKey::Num1).is_empty());
+        assert_eq!(timeline.deadline(), Some(300));
+        assert_eq!(timeline.release(120, RKey::Num1), vec!["release:1", "tap:1"]);
+        assert_eq!(timeline.deadline(), None);
+
+        // 长按：到达长按时间时触发，释放时不再单击
+        assert!(timeline.press(1000, RKey::Num1).is_empty());
+        assert!(timeline.poll(1299).is_empty());
+        assert_eq!(timeline.poll(1300), vec!["hold:1"]);
+        assert!(timeline.poll(1400).is_empty());
+        assert_eq!(timeline.release(1500, RKey::Num1), vec!["release:1"]);
+
+        // 按住不放的重复按下事件被忽略；没有及时 poll 时由释放事件补判长按
+        assert!(timeline.press(2000, RKey::Num1).is_empty());
+        assert!(timeline.press(2100, RKey::Num1).is_empty());
+        assert_eq!(timeline.release(2400, RKey::Num1), vec!["hold:1", "release:1"]);
+
+        // 没有绑定长按时，超过长按时间的释放不算单击
+        let mut timeline = Timeline::new("tap:1");
+        assert_eq!(timeline.deadline(), None);
+        timeline.press(0, RKey::Num1);
+        assert_eq!(timeline.deadline(), None);
+        assert!(timeline.release(300, RKey::Num1).is_empty());
+    }
+
+    #[test]
+    fn test_rebind_keeps_key_state() {
+        let mut timeline = Timeline::new("tap:Ctrl+1,Ctrl+2");
+
+        // 重新绑定前按住的修饰键仍然有效，绑定未变的按键照常触发
+        timeline.press(0, RKey::ControlLeft);
+        timeline.press(10, RKey::Num1);
+        timeline.matcher.set_hotkeys(Hotkey::parse_list("tap:Ctrl+1,Ctrl+2,tap:3").unwrap());
+        assert_eq!(timeline.press(20, RKey::Num2), vec!["Ctrl+2"]);
+        assert_eq!(timeline.release(50, RKey::Num1), vec!["tap:Ctrl+1"]);
+
+        // 绑定变化的按键不再按旧的绑定触发
+        timeline.press(100, RKey::Num1);
+        timeline.matcher.set_hotkeys(Hotkey::parse_list("hold:Ctrl+1").unwrap());
+        assert!(timeline.release(150, RKey::Num1).is_empty());
+    }
+
+    #[test]
+    fn test_double_tap() {
+        let mut timeline = Timeline::new("tap:Q,double:Q,hold:Q,Ctrl+W");
+
+        // 双击在第二次按下时触发，第二次按下不再判定单击和长按
+        timeline.press(0, RKey::KeyQ);
+        assert!(timeline.release(50, RKey::KeyQ).is_empty());
+        assert_eq!(timeline.deadline(), Some(300));
+        assert_eq!(timeline.press(200, RKey::KeyQ), vec!["double:Q"]);
+        assert_eq!(timeline.deadline(), None);
+        assert!(timeline.poll(1000).is_empty());
+        assert!(timeline.release(1000, RKey::KeyQ).is_empty());
+
+        // 超过双击间隔后单击生效
+        timeline.press(2000, RKey::KeyQ);
+        timeline.release(2050, RKey::KeyQ);
+        assert!(timeline.poll(2299).is_empty());
+        assert_eq!(timeline.poll(2300), vec!["tap:Q"]);
+        assert!(timeline.press(2400, RKey::KeyQ).is_empty());
+        assert_eq!(timeline.poll(2700), vec!["hold:Q"]);
+        timeline.release(2800, RKey::KeyQ);
+
+        // 按下其他按键时单击立即生效，左右修饰键不影响匹配
+        timeline.press(3000, RKey::KeyQ);
+        timeline.release(3050, RKey::KeyQ);
+        assert!(timeline.press(3100, RKey::ControlRight).is_empty());
+        assert_eq!(timeline.press(3150, RKey::KeyW), vec!["tap:Q", "Ctrl+W"]);
+        assert_eq!(timeline.deadline(), None);
+    }
+}
@@ -47,6 +47,33 @@ pub enum WindowEvent {
 /// 窗口检测回调
 pub type WindowCallback = Box<dyn Fn(WindowEvent) + Send + Sync>;
 
+/// 目标窗口来源
+///
+/// 操作执行时用于将窗口坐标转换为屏幕坐标
+pub trait TargetWindow: Send + Sync {
+    /// 当前锁定的目标窗口
+    fn target_window(&self) -> Option<WindowInfo>;
+}
+
+impl TargetWindow for WindowService {
+    fn target_window(&self) -> Option<WindowInfo> {
+        self.get_window_info()
+    }
+}
+
+impl TargetWindow for Mutex<Option<WindowInfo>> {
+    fn target_window(&self) -> Option<WindowInfo> {
+        self.lock().ok()?.clone()
+    }
+}
+
+/// 固定的窗口
+impl TargetWindow for WindowInfo {
+    fn target_window(&self) -> Option<WindowInfo> {
+        Some(self.clone())
+    }
+}
+
 /// 用于枚举窗口的上下文结构
 #[cfg(windows)]
 struct EnumWindowsContext<'a> {
@@ -357,6 +384,11 @@ impl WindowService {
         self.target_window.lock().ok()?.clone()
     }
     
+    /// 获取共享的目标窗口，检测线程更新窗口后立即可见
+    pub fn shared_target_window(&self) -> Arc<Mutex<Option<WindowInfo>>> {
+        Arc::clone(&self.target_window)
+    }
+    
     /// 检查是否有窗口被锁定
     pub fn has_window(&self) -> bool {
         self.target_window.lock()
@@ -2,6 +2,7 @@
 //! 
 //! 集成所有组件和服务，实现应用程序生命周期管理
 
+use crate::models::OperationMode;
 use crate::services::*;
 use crate::utils::*;
 use std::sync::Arc;
@@ -26,6 +27,20 @@ pub struct MainApp {
     window_service: Arc<RwLock<WindowService>>,
     /// 模式管理器
     mode_manager: Arc<RwLock<ModeManager>>,
+    /// 操作执行服务
+    action_service: Arc<ActionService>,
+    /// 安全保护
+    safety_guard: Arc<SafetyGuard>,
+    /// 战斗速度跟踪
+    game_speed: Arc<GameSpeedTracker>,
+    /// 玩家按下的变速键监视
+    speed_watch: Option<ComboWatch>,
+    /// 紧急停止按键监视
+    panic_watch: Option<ComboWatch>,
+    /// 全局热键服务
+    hotkey_service: Arc<HotkeyService>,
+    /// 全局热键监听
+    hotkey_handle: Option<HotkeyHandle>,
 }
 
 impl MainApp {
@@ -49,13 +64,42 @@ impl MainApp {
         );
         
         // 初始化状态管理器
-        let state_manager = Arc::new(RwLock::new(StateManager::new()));
+        let mut state_manager = StateManager::new();
+        
+        // 初始化操作执行服务，与状态管理器共享序列注册表
+        let global_settings = config_service.get_config().global_settings;
+        let safety_guard = Arc::new(SafetyGuard::new(global_settings.safety));
+        let game_speed = Arc::new(GameSpeedTracker::from_game_keys(&global_settings.game_keys));
+        let action_service = Arc::new(
+            ActionService::new()
+                .with_registry(state_manager.sequence_registry())
+                .with_safety_guard(Arc::clone(&safety_guard))
+                .with_game_speed(Arc::clone(&game_speed))
+                .with_injected_keys(InjectedKeys::shared())
+        );
+        // 进入战斗时重置战斗速度
+        state_manager.add_observer(Arc::clone(&game_speed) as Arc<dyn StateObserver>);
+        // 程序 panic 时释放所有按住的输入
+        action_service.install_panic_hook();
+        let state_manager = Arc::new(RwLock::new(state_manager));
         
         // 初始化窗口管理服务
         let window_service = Arc::new(RwLock::new(WindowService::new()));
         
-        // 初始化模式管理器
-        let mode_manager = Arc::new(RwLock::new(ModeManager::new()));
+        // 初始化模式管理器，使用配置中的热键和当前模式
+        let config = config_service.get_config();
+        let mut mode_manager = ModeManager::with_configs(config.macro_config, config.intelligent_config);
+        if let Err(e) = mode_manager.switch_mode(config.mode) {
+            warn!("切换到配置的模式失败: {}", e);
+        }
+        let mode_manager = Arc::new(RwLock::new(mode_manager));
+        
+        // 初始化全局热键服务
+        let hotkey_service = HotkeyService::new(
+            Arc::clone(&mode_manager),
+            OperationExecutor::new(Arc::clone(&action_service), DEFAULT_QUEUE_CAPACITY),
+        );
+        hotkey_service.set_trigger_settings(config.global_settings.triggers);
         
         info!("主应用程序初始化完成");
         
@@ -65,6 +109,13 @@ impl MainApp {
             state_manager,
             window_service,
             mode_manager,
+            action_service,
+            safety_guard,
+            game_speed,
+            speed_watch: None,
+            panic_watch: None,
+            hotkey_service,
+            hotkey_handle: None,
         })
     }
     
@@ -78,6 +129,23 @@ impl MainApp {
         // 设置UI回调
         self.setup_ui_callbacks()?;
         
+        // 启动紧急停止按键和安全通知
+        self.setup_safety_monitor();
+        
+        // 配置变化时更新运行中的设置
+        self.setup_settings_monitor();
+        
+        // 检查热键冲突，配置变化时重新检查
+        self.setup_conflict_monitor();
+        
+        // 跟踪玩家按下的变速键
+        self.speed_watch = Some(self.game_speed.watch(GlobalInputListener::shared().as_ref()));
+        
+        // 启动全局热键，程序运行时分派游戏操作
+        self.hotkey_handle = Some(
+            self.hotkey_service.start(GlobalInputListener::shared().as_ref(), &self.state_manager).await
+        );
+        
         // 显示主窗口
         self.ui_handle.show()
             .map_err(|e| AppError::UI(format!("显示窗口失败: {}", e)))?;
@@ -109,19 +177,19 @@ impl MainApp {
         
         // 应用宏模式配置
         let macro_config = MacroConfig::get(&self.ui_handle);
-        if let Some(key) = config.macro_config.hotkeys.get("deploy") {
+        if let Some(key) = config.macro_config.hotkeys.get("deploy_operator") {
             macro_config.set_deploy_operator(key.into());
         }
-        if let Some(key) = config.macro_config.hotkeys.get("skill") {
+        if let Some(key) = config.macro_config.hotkeys.get("activate_skill") {
             macro_config.set_activate_skill(key.into());
         }
-        if let Some(key) = config.macro_config.hotkeys.get("retreat") {
+        if let Some(key) = config.macro_config.hotkeys.get("retreat_operator") {
             macro_config.set_retreat_operator(key.into());
         }
-        if let Some(key) = config.macro_config.hotkeys.get("focus") {
+        if let Some(key) = config.macro_config.hotkeys.get("focus_view") {
             macro_config.set_focus_view(key.into());
         }
-        if let Some(key) = config.macro_config.hotkeys.get("pause") {
+        if let Some(key) = config.macro_config.hotkeys.get("pause_game") {
             macro_config.set_pause_game(key.into());
         }
         macro_config.set_show_overlay(config.macro_config.overlay_settings.enabled);
@@ -130,19 +198,19 @@ impl MainApp {
         
         // 应用智能模式配置
         let smart_config = SmartConfig::get(&self.ui_handle);
-        if let Some(key) = config.intelligent_config.hotkeys.get("deploy") {
+        if let Some(key) = config.intelligent_config.hotkeys.get("deploy_operator") {
             smart_config.set_deploy_operator(key.into());
         }
-        if let Some(key) = config.intelligent_config.hotkeys.get("skill") {
+        if let Some(key) = config.intelligent_config.hotkeys.get("activate_skill") {
             smart_config.set_activate_skill(key.into());
         }
-        if let Some(key) = config.intelligent_config.hotkeys.get("retreat") {
+        if let Some(key) = config.intelligent_config.hotkeys.get("retreat_operator") {
             smart_config.set_retreat_operator(key.into());
         }
-        if let Some(key) = config.intelligent_config.hotkeys.get("focus") {
+        if let Some(key) = config.intelligent_config.hotkeys.get("focus_view") {
             smart_config.set_focus_view(key.into());
         }
-        if let Some(key) = config.intelligent_config.hotkeys.get("pause") {
+        if let Some(key) = config.intelligent_config.hotkeys.get("pause_game") {
             smart_config.set_pause_game(key.into());
         }
         smart_config.set_show_overlay(config.intelligent_config.overlay_settings.enabled);
@@ -155,28 +223,28 @@ impl MainApp {
         
         // 应用游戏配置
         let game_config = GameConfig::get(&self.ui_handle);
-        if let Some(key) = config.global_settings.game_keys.get("deploy") {
+        if let Some(key) = config.global_settings.game_keys.get(game_function_name("deploy")) {
             game_config.set_game_deploy(key.into());
         }
-        if let Some(key) = config.global_settings.game_keys.get("skill") {
+        if let Some(key) = config.global_settings.game_keys.get(game_function_name("skill")) {
             game_config.set_game_skill(key.into());
         }
-        if let Some(key) = config.global_settings.game_keys.get("retreat") {
+        if let Some(key) = config.global_settings.game_keys.get(game_function_name("retreat")) {
             game_config.set_game_retreat(key.into());
         }
-        if let Some(key) = config.global_settings.game_keys.get("pause") {
+        if let Some(key) = config.global_settings.game_keys.get(game_function_name("pause")) {
             game_config.set_game_pause(key.into());
         }
-        if let Some(key) = config.global_settings.game_keys.get("speed_up") {
+        if let Some(key) = config.global_settings.game_keys.get(game_function_name("speed-up")) {
             game_config.set_game_speed_up(key.into());
         }
-        if let Some(key) = config.global_settings.game_keys.get("view_left") {
+        if let Some(key) = config.global_settings.game_keys.get(game_function_name("view-left")) {
             game_config.set_game_view_left(key.into());
         }
-        if let Some(key) = config.global_settings.game_keys.get("view_right") {
+        if let Some(key) = config.global_settings.game_keys.get(game_function_name("view-right")) {
             game_config.set_game_view_right(key.into());
         }
-        if let Some(key) = config.global_settings.game_keys.get("view_reset") {
+        if let Some(key) = config.global_settings.game_keys.get(game_function_name("view-reset")) {
             game_config.set_game_view_reset(key.into());
         }
         game_config.set_auto_start(config.global_settings.auto_start_on_detection);
@@ -197,6 +265,135 @@ impl MainApp {
         Ok(())
     }
     
+    /// 启动紧急停止按键监视，并将安全违规显示为通知
+    fn setup_safety_monitor(&mut self) {
+        // ===== 紧急停止按键 =====
+        // 每次按键时按当前设置判断，修改紧急停止按键后立即生效
+        if let Some(panic_key) = self.safety_guard.panic_key() {
+            info!("紧急停止按键: {}", panic_key);
+        }
+        let safety_guard = Arc::clone(&self.safety_guard);
+        let action_service = Arc::clone(&self.action_service);
+        let state_manager = Arc::clone(&self.state_manager);
+        let ui_handle_weak = self.ui_handle.as_weak();
+        let runtime = tokio::runtime::Handle::current();
+        self.panic_watch = Some(ComboWatch::each_press(
+            GlobalInputListener::shared().as_ref(),
+            "panic-key-watch".to_string(),
+            move |pressed| {
+                if !safety_guard.is_panic_key(pressed) {
+                    return;
+                }
+                action_service.emergency_stop();
+                
+                let state_manager = Arc::clone(&state_manager);
+                runtime.spawn(async move {
+                    state_manager.write().await.emergency_stop().await;
+                });
+                
+                let ui_weak = ui_handle_weak.clone();
+                let _ = slint::invoke_from_event_loop(move || {
+                    if let Some(ui) = ui_weak.upgrade() {
+                        AppState::get(&ui).set_is_running(false);
+                        AppState::get(&ui).set_status_text("已停止".into());
+                    }
+                });
+            },
+        ));
+        
+        // ===== 安全通知 =====
+        let mut violations = self.safety_guard.subscribe();
+        let ui_handle_weak = self.ui_handle.as_weak();
+        std::thread::spawn(move || loop {
+            let violation = match violations.blocking_recv() {
+                Ok(violation) => violation,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+            
+            let notification_type = match violation {
+                SafetyViolation::EmergencyStop => "warning",
+                _ => "error",
+            };
+            let text = violation.to_string();
+            let ui_weak = ui_handle_weak.clone();
+            let _ = slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_weak.upgrade() {
+                    AppState::get(&ui).set_notification_text(text.into());
+                    AppState::get(&ui).set_notification_type(notification_type.into());
+                    AppState::get(&ui).set_show_notification(true);
+                }
+            });
+        });
+    }
+    
+    /// 配置加载、更新或保存时，将设置应用到运行中的服务
+    fn setup_settings_monitor(&self) {
+        let config_service = Arc::clone(&self.config_service);
+        let mut changes = config_service.subscribe_changes();
+        let safety_guard = Arc::clone(&self.safety_guard);
+        let game_speed = Arc::clone(&self.game_speed);
+        let hotkey_service = Arc::clone(&self.hotkey_service);
+        std::thread::spawn(move || loop {
+            match changes.blocking_recv() {
+                Ok(ConfigChangeEvent::Loaded | ConfigChangeEvent::Updated | ConfigChangeEvent::Saved)
+                | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                Ok(_) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+            
+            let settings = config_service.get_config().global_settings;
+            safety_guard.update_settings(settings.safety);
+            game_speed.update_game_keys(&settings.game_keys);
+            hotkey_service.set_trigger_settings(settings.triggers);
+        });
+    }
+    
+    /// 设置热键冲突提示
+    fn setup_conflict_monitor(&self) {
+        let config_service = Arc::clone(&self.config_service);
+        let mut changes = config_service.subscribe_changes();
+        let ui_handle_weak = self.ui_handle.as_weak();
+        std::thread::spawn(move || {
+            let mut reported = Vec::new();
+            loop {
+                let conflicts = config_service.get_config().validate().unwrap_or_default();
+                // 只在冲突变化时提示
+                if conflicts != reported {
+                    for conflict in &conflicts {
+                        warn!("热键冲突: {}", conflict);
+                    }
+                    if let Some(first) = conflicts.first() {
+                        let text = if conflicts.len() > 1 {
+                            format!("{}（共 {} 个热键冲突）", first, conflicts.len())
+                        } else {
+                            first.to_string()
+                        };
+                        let ui_weak = ui_handle_weak.clone();
+                        let _ = slint::invoke_from_event_loop(move || {
+                            if let Some(ui) = ui_weak.upgrade() {
+                                AppState::get(&ui).set_notification_text(text.into());
+                                AppState::get(&ui).set_notification_type("warning".into());
+                                AppState::get(&ui).set_show_notification(true);
+                            }
+                        });
+                    }
+                    reported = conflicts;
+                }
+                
+                // 等待配置加载或更新
+                loop {
+                    match changes.blocking_recv() {
+                        Ok(ConfigChangeEvent::Loaded | ConfigChangeEvent::Updated)
+                        | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => break,
+                        Ok(_) => {}
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+                    }
+                }
+            }
+        });
+    }
+    
     /// 应用主题
     fn apply_theme(&self, _theme_mode: i32) {
         // 移除主题切换功能，只使用浅色主题
@@ -208,10 +405,13 @@ impl MainApp {
         
         // ===== 启动按钮 =====
         let state_manager = Arc::clone(&self.state_manager);
+        let safety_guard = Arc::clone(&self.safety_guard);
         let ui_handle_weak = self.ui_handle.as_weak();
         self.ui_handle.on_start_clicked(move || {
             let state_manager = Arc::clone(&state_manager);
             let ui_weak = ui_handle_weak.clone();
+            // 重新启动时解除紧急停止
+            safety_guard.reset();
             std::thread::spawn(move || {
                 let rt = tokio::runtime::Runtime::new().unwrap();
                 rt.block_on(async {
@@ -281,7 +481,6 @@ impl MainApp {
         });
         
         // ===== 按键检测 =====
-        let config_service = Arc::clone(&self.config_service);
         let ui_handle_weak = self.ui_handle.as_weak();
         self.ui_handle.on_start_key_detection(move |config_key| {
             let config_key = config_key.to_string();
@@ -289,58 +488,70 @@ impl MainApp {
             info!("开始检测按键配置: {}", config_key);
             
             // 设置检测状态
+            let detecting_for = config_key.clone();
             let _ = slint::invoke_from_event_loop(move || {
                 if let Some(ui) = ui_weak.upgrade() {
                     AppState::get(&ui).set_is_detecting_key(true);
-                    AppState::get(&ui).set_detecting_for(config_key.into());
+                    AppState::get(&ui).set_detecting_for(detecting_for.into());
                 }
             });
             
-            // TODO: 实现实际的按键检测逻辑
-            // 这里应该启动一个后台任务来监听按键输入
-            // 当检测到按键组合时，调用 key-detected 回调
+            // 在后台线程等待下一个组合键
+            let ui_weak = ui_handle_weak.clone();
+            std::thread::spawn(move || {
+                let outcome = KeyCapture::new().capture();
+                
+                let _ = slint::invoke_from_event_loop(move || {
+                    let Some(ui) = ui_weak.upgrade() else {
+                        return;
+                    };
+                    
+                    // 任何结果都结束检测状态
+                    AppState::get(&ui).set_is_detecting_key(false);
+                    AppState::get(&ui).set_detecting_for("".into());
+                    
+                    let notification = match outcome {
+                        CaptureOutcome::Captured(combo) => {
+                            ui.invoke_key_detected(config_key.into(), combo.to_string().into());
+                            None
+                        }
+                        CaptureOutcome::Cancelled => {
+                            debug!("取消按键检测: {}", config_key);
+                            None
+                        }
+                        CaptureOutcome::TimedOut => Some(("按键检测超时，请重试", "warning")),
+                        CaptureOutcome::Closed => Some(("无法监听键盘输入", "error")),
+                    };
+                    if let Some((text, notification_type)) = notification {
+                        AppState::get(&ui).set_notification_text(text.into());
+                        AppState::get(&ui).set_notification_type(notification_type.into());
+                        AppState::get(&ui).set_show_notification(true);
+                    }
+                });
+            });
         });
         
         let config_service = Arc::clone(&self.config_service);
+        let mode_manager = Arc::clone(&self.mode_manager);
         let ui_handle_weak = self.ui_handle.as_weak();
         self.ui_handle.on_key_detected(move |config_key, key_combination| {
             let config_key = config_key.to_string();
             let key_combination = key_combination.to_string();
             let ui_weak = ui_handle_weak.clone();
             let config_service = Arc::clone(&config_service);
+            let mode_manager = Arc::clone(&mode_manager);
             
             info!("检测到按键组合: {} -> {}", config_key, key_combination);
             
             std::thread::spawn(move || {
                 let rt = tokio::runtime::Runtime::new().unwrap();
                 rt.block_on(async {
-                    // 根据配置键更新相应的配置
-                    match config_key.as_str() {
-                        "macro-deploy" => {
-                            let mut hotkeys = std::collections::HashMap::new();
-                            hotkeys.insert("deploy".to_string(), key_combination.clone());
-                            if let Err(e) = config_service.update_hotkeys(hotkeys).await {
-                                error!("保存宏模式部署按键失败: {}", e);
-                            }
-                        }
-                        "macro-skill" => {
-                            let mut hotkeys = std::collections::HashMap::new();
-                            hotkeys.insert("skill".to_string(), key_combination.clone());
-                            if let Err(e) = config_service.update_hotkeys(hotkeys).await {
-                                error!("保存宏模式技能按键失败: {}", e);
-                            }
-                        }
-                        "smart-deploy" => {
-                            let mut hotkeys = std::collections::HashMap::new();
-                            hotkeys.insert("deploy".to_string(), key_combination.clone());
-                            if let Err(e) = config_service.update_smart_hotkeys(hotkeys).await {
-                                error!("保存智能模式部署按键失败: {}", e);
-                            }
-                        }
-                        // 添加更多按键配置处理...
-                        _ => {
-                            warn!("未知的配置键: {}", config_key);
+                    // 根据配置键更新相应模式的热键
+                    match hotkey_target(&config_key) {
+                        Some((mode, operation)) => {
+                            apply_hotkey(&mode_manager, &config_service, mode, operation, key_combination.clone()).await;
                         }
+                        None => warn!("未知的配置键: {}", config_key),
                     }
                     
                     // 重置检测状态并更新UI
@@ -405,95 +616,46 @@ impl MainApp {
     /// 设置宏模式配置回调
     fn setup_macro_config_callbacks(&self) {
         let config_service = Arc::clone(&self.config_service);
+        let mode_manager = Arc::clone(&self.mode_manager);
         
         // 宏模式按键配置
         self.ui_handle.on_macro_deploy_changed({
             let config_service = Arc::clone(&config_service);
+            let mode_manager = Arc::clone(&mode_manager);
             move |keys| {
-                let keys = keys.to_string();
-                let config_service = Arc::clone(&config_service);
-                std::thread::spawn(move || {
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    rt.block_on(async {
-                        let mut hotkeys = std::collections::HashMap::new();
-                        hotkeys.insert("deploy".to_string(), keys);
-                        if let Err(e) = config_service.update_hotkeys(hotkeys).await {
-                            error!("保存宏模式部署按键失败: {}", e);
-                        }
-                    });
-                });
+                spawn_hotkey_update(&mode_manager, &config_service, OperationMode::Macro, "deploy_operator", keys.to_string());
             }
         });
         
         self.ui_handle.on_macro_skill_changed({
             let config_service = Arc::clone(&config_service);
+            let mode_manager = Arc::clone(&mode_manager);
             move |keys| {
-                let keys = keys.to_string();
-                let config_service = Arc::clone(&config_service);
-                std::thread::spawn(move || {
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    rt.block_on(async {
-                        let mut hotkeys = std::collections::HashMap::new();
-                        hotkeys.insert("skill".to_string(), keys);
-                        if let Err(e) = config_service.update_hotkeys(hotkeys).await {
-                            error!("保存宏模式技能按键失败: {}", e);
-                        }
-                    });
-                });
+                spawn_hotkey_update(&mode_manager, &config_service, OperationMode::Macro, "activate_skill", keys.to_string());
             }
         });
         
         self.ui_handle.on_macro_retreat_changed({
             let config_service = Arc::clone(&config_service);
+            let mode_manager = Arc::clone(&mode_manager);
             move |keys| {
-                let keys = keys.to_string();
-                let config_service = Arc::clone(&config_service);
-                std::thread::spawn(move || {
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    rt.block_on(async {
-                        let mut hotkeys = std::collections::HashMap::new();
-                        hotkeys.insert("retreat".to_string(), keys);
-                        if let Err(e) = config_service.update_hotkeys(hotkeys).await {
-                            error!("保存宏模式撤退按键失败: {}", e);
-                        }
-                    });
-                });
+                spawn_hotkey_update(&mode_manager, &config_service, OperationMode::Macro, "retreat_operator", keys.to_string());
             }
         });
         
         self.ui_handle.on_macro_focus_changed({
             let config_service = Arc::clone(&config_service);
+            let mode_manager = Arc::clone(&mode_manager);
             move |keys| {
-                let keys = keys.to_string();
-                let config_service = Arc::clone(&config_service);
-                std::thread::spawn(move || {
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    rt.block_on(async {
-                        let mut hotkeys = std::collections::HashMap::new();
-                        hotkeys.insert("focus".to_string(), keys);
-                        if let Err(e) = config_service.update_hotkeys(hotkeys).await {
-                            error!("保存宏模式聚焦按键失败: {}", e);
-                        }
-                    });
-                });
+                spawn_hotkey_update(&mode_manager, &config_service, OperationMode::Macro, "focus_view", keys.to_string());
             }
         });
         
         self.ui_handle.on_macro_pause_changed({
             let config_service = Arc::clone(&config_service);
+            let mode_manager = Arc::clone(&mode_manager);
             move |keys| {
-                let keys = keys.to_string();
-                let config_service = Arc::clone(&config_service);
-                std::thread::spawn(move || {
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    rt.block_on(async {
-                        let mut hotkeys = std::collections::HashMap::new();
-                        hotkeys.insert("pause".to_string(), keys);
-                        if let Err(e) = config_service.update_hotkeys(hotkeys).await {
-                            error!("保存宏模式暂停按键失败: {}", e);
-                        }
-                    });
-                });
+                spawn_hotkey_update(&mode_manager, &config_service, OperationMode::Macro, "pause_game", keys.to_string());
             }
         });
         
@@ -562,95 +724,46 @@ impl MainApp {
     /// 设置智能模式配置回调
     fn setup_smart_config_callbacks(&self) {
         let config_service = Arc::clone(&self.config_service);
+        let mode_manager = Arc::clone(&self.mode_manager);
         
         // 智能模式按键配置
         self.ui_handle.on_smart_deploy_changed({
             let config_service = Arc::clone(&config_service);
+            let mode_manager = Arc::clone(&mode_manager);
             move |keys| {
-                let keys = keys.to_string();
-                let config_service = Arc::clone(&config_service);
-                std::thread::spawn(move || {
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    rt.block_on(async {
-                        let mut hotkeys = std::collections::HashMap::new();
-                        hotkeys.insert("deploy".to_string(), keys);
-                        if let Err(e) = config_service.update_smart_hotkeys(hotkeys).await {
-                            error!("保存智能模式部署按键失败: {}", e);
-                        }
-                    });
-                });
+                spawn_hotkey_update(&mode_manager, &config_service, OperationMode::Intelligent, "deploy_operator", keys.to_string());
             }
         });
         
         self.ui_handle.on_smart_skill_changed({
             let config_service = Arc::clone(&config_service);
+            let mode_manager = Arc::clone(&mode_manager);
             move |keys| {
-                let keys = keys.to_string();
-                let config_service = Arc::clone(&config_service);
-                std::thread::spawn(move || {
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    rt.block_on(async {
-                        let mut hotkeys = std::collections::HashMap::new();
-                        hotkeys.insert("skill".to_string(), keys);
-                        if let Err(e) = config_service.update_smart_hotkeys(hotkeys).await {
-                            error!("保存智能模式技能按键失败: {}", e);
-                        }
-                    });
-                });
+                spawn_hotkey_update(&mode_manager, &config_service, OperationMode::Intelligent, "activate_skill", keys.to_string());
             }
         });
         
         self.ui_handle.on_smart_retreat_changed({
             let config_service = Arc::clone(&config_service);
+            let mode_manager = Arc::clone(&mode_manager);
             move |keys| {
-                let keys = keys.to_string();
-                let config_service = Arc::clone(&config_service);
-                std::thread::spawn(move || {
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    rt.block_on(async {
-                        let mut hotkeys = std::collections::HashMap::new();
-                        hotkeys.insert("retreat".to_string(), keys);
-                        if let Err(e) = config_service.update_smart_hotkeys(hotkeys).await {
-                            error!("保存智能模式撤退按键失败: {}", e);
-                        }
-                    });
-                });
+                spawn_hotkey_update(&mode_manager, &config_service, OperationMode::Intelligent, "retreat_operator", keys.to_string());
             }
         });
         
         self.ui_handle.on_smart_focus_changed({
             let config_service = Arc::clone(&config_service);
+            let mode_manager = Arc::clone(&mode_manager);
             move |keys| {
-                let keys = keys.to_string();
-                let config_service = Arc::clone(&config_service);
-                std::thread::spawn(move || {
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    rt.block_on(async {
-                        let mut hotkeys = std::collections::HashMap::new();
-                        hotkeys.insert("focus".to_string(), keys);
-                        if let Err(e) = config_service.update_smart_hotkeys(hotkeys).await {
-                            error!("保存智能模式聚焦按键失败: {}", e);
-                        }
-                    });
-                });
+                spawn_hotkey_update(&mode_manager, &config_service, OperationMode::Intelligent, "focus_view", keys.to_string());
             }
         });
         
         self.ui_handle.on_smart_pause_changed({
             let config_service = Arc::clone(&config_service);
+            let mode_manager = Arc::clone(&mode_manager);
             move |keys| {
-                let keys = keys.to_string();
-                let config_service = Arc::clone(&config_service);
-                std::thread::spawn(move || {
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    rt.block_on(async {
-                        let mut hotkeys = std::collections::HashMap::new();
-                        hotkeys.insert("pause".to_string(), keys);
-                        if let Err(e) = config_service.update_smart_hotkeys(hotkeys).await {
-                            error!("保存智能模式暂停按键失败: {}", e);
-                        }
-                    });
-                });
+                spawn_hotkey_update(&mode_manager, &config_service, OperationMode::Intelligent, "pause_game", keys.to_string());
             }
         });
         
@@ -725,7 +838,8 @@ impl MainApp {
         self.ui_handle.on_game_config_changed({
             let config_service = Arc::clone(&config_service);
             move |key_type, key_value| {
-                let key_type = key_type.to_string();
+                // 变速键由配置更新时的设置监视应用到战斗速度跟踪
+                let key_type = game_function_name(&key_type).to_string();
                 let key_value = key_value.to_string();
                 let config_service = Arc::clone(&config_service);
                 std::thread::spawn(move || {
@@ -794,6 +908,75 @@ impl MainApp {
     }
 }
 
+/// 界面中的按键配置键对应的模式和操作，如 `macro-deploy` 对应宏模式的 `deploy_operator`
+fn hotkey_target(config_key: &str) -> Option<(OperationMode, &'static str)> {
+    let (mode, name) = config_key.split_once('-')?;
+    let mode = match mode {
+        "macro" => OperationMode::Macro,
+        "smart" => OperationMode::Intelligent,
+        _ => return None,
+    };
+    let operation = match name {
+        "deploy" => "deploy_operator",
+        "skill" => "activate_skill",
+        "retreat" => "retreat_operator",
+        "focus" => "focus_view",
+        "pause" => "pause_game",
+        _ => return None,
+    };
+    Some((mode, operation))
+}
+
+/// 更新指定模式的热键
+///
+/// 先应用到模式管理器，热键服务收到热键更新事件后重新绑定；再保存规范化后的热键，只修改这一项
+async fn apply_hotkey(
+    mode_manager: &RwLock<ModeManager>,
+    config_service: &ConfigService,
+    mode: OperationMode,
+    operation: &str,
+    hotkey: String,
+) {
+    let updated = mode_manager.write().await.update_mode_hotkey(mode, operation.to_string(), hotkey);
+    match updated {
+        Ok(hotkey) => {
+            if let Err(e) = config_service.update_mode_hotkey(mode, operation, &hotkey).await {
+                error!("保存热键失败 {}: {}", operation, e);
+            }
+        }
+        Err(e) => error!("更新热键失败 {}: {}", operation, e),
+    }
+}
+
+/// 在后台线程中更新热键（UI 回调中使用）
+fn spawn_hotkey_update(
+    mode_manager: &Arc<RwLock<ModeManager>>,
+    config_service: &Arc<ConfigService>,
+    mode: OperationMode,
+    operation: &'static str,
+    hotkey: String,
+) {
+    let mode_manager = Arc::clone(mode_manager);
+    let config_service = Arc::clone(config_service);
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(apply_hotkey(&mode_manager, &config_service, mode, operation, hotkey));
+    });
+}
+
+/// 界面中的游戏按键名称对应的游戏内功能名称（`GlobalSettings.game_keys` 的键）
+fn game_function_name(ui_name: &str) -> &str {
+    match ui_name {
+        "speed-up" => BATTLE_SPEED_FUNCTION,
+        "skill" => "skill_activation",
+        "retreat" => "retreat_operator",
+        "view-left" => "view_left",
+        "view-right" => "view_right",
+        "view-reset" => "view_reset",
+        other => other,
+    }
+}
+
 /// 应用程序构建器
 pub struct MainAppBuilder {
     config_path: Option<PathBuf>,
@@ -344,6 +344,25 @@ pub enum ActionError {
     
     #[error("平台不支持: {0}")]
     UnsupportedPlatform(String),
+    
+    #[error("输入速率超过限制: 每秒 {0} 个事件")]
+    RateLimitExceeded(u32),
+    
+    #[error("序列执行时间超过上限: {0:?}")]
+    SequenceDurationExceeded(std::time::Duration),
+    
+    #[error("紧急停止")]
+    EmergencyStop,
+    
+    #[error("序列脚本第 {line} 行第 {column} 列: {message}")]
+    ScriptSyntax {
+        /// 行号（从 1 开始）
+        line: usize,
+        /// 列号（从 1 开始，按字符计）
+        column: usize,
+        /// 错误说明
+        message: String,
+    },
 }
 
 impl ActionError {
@@ -362,6 +381,10 @@ impl ActionError {
             ActionError::InvalidKey(_) => ErrorSeverity::Error,
             ActionError::SystemCall(_) => ErrorSeverity::Error,
             ActionError::UnsupportedPlatform(_) => ErrorSeverity::Warning,
+            ActionError::RateLimitExceeded(_) => ErrorSeverity::Warning,
+            ActionError::SequenceDurationExceeded(_) => ErrorSeverity::Warning,
+            ActionError::EmergencyStop => ErrorSeverity::Warning,
+            ActionError::ScriptSyntax { .. } => ErrorSeverity::Error,
         }
     }
     